@@ -1,25 +1,7 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use rand::{prelude::StdRng, Rng, RngCore, SeedableRng};
 use rowan::{GreenNodeBuilder, SyntaxNode};
-use syntree::{Builder, Error, FlavorDefault, Tree, TreeIndex};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[repr(u16)]
-#[allow(clippy::upper_case_acronyms)]
-enum Syntax {
-    STRING,
-    ENTRY,
-    WHITESPACE,
-    ROOT,
-}
-
-use Syntax::*;
-
-impl From<Syntax> for rowan::SyntaxKind {
-    fn from(kind: Syntax) -> Self {
-        Self(kind as u16)
-    }
-}
+use syntree::bench_support::{build_entries, build_flat, generate_random, Syntax};
+use syntree::{FlavorDefault, TreeIndex};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Lang {}
@@ -28,29 +10,17 @@ impl rowan::Language for Lang {
     type Kind = Syntax;
 
     fn kind_from_raw(raw: rowan::SyntaxKind) -> Self::Kind {
-        assert!(raw.0 <= ROOT as u16);
+        assert!(raw.0 <= Syntax::Root as u16);
         unsafe { std::mem::transmute::<u16, Syntax>(raw.0) }
     }
 
     fn kind_to_raw(kind: Self::Kind) -> rowan::SyntaxKind {
-        kind.into()
+        kind_raw(kind)
     }
 }
 
-fn syntree_build<F>(strings: &[Box<str>], count: usize) -> Result<Tree<Syntax, F>, Error<F::Error>>
-where
-    F: syntree::Flavor<Length: From<usize>>,
-{
-    let mut builder = Builder::<_, F>::new_with();
-
-    let c = builder.checkpoint()?;
-
-    for s in strings.iter().cycle().take(count) {
-        builder.token(STRING, s.len().into())?;
-    }
-
-    builder.close_at(&c, ROOT)?;
-    builder.build()
+fn kind_raw(kind: Syntax) -> rowan::SyntaxKind {
+    rowan::SyntaxKind(kind as u16)
 }
 
 fn rowan_build(strings: &[Box<str>], count: usize) -> SyntaxNode<Lang> {
@@ -59,10 +29,10 @@ fn rowan_build(strings: &[Box<str>], count: usize) -> SyntaxNode<Lang> {
     let c = builder.checkpoint();
 
     for s in strings.iter().cycle().take(count) {
-        builder.token(STRING.into(), s);
+        builder.token(kind_raw(Syntax::String), s);
     }
 
-    builder.start_node_at(c, ROOT.into());
+    builder.start_node_at(c, kind_raw(Syntax::Root));
     builder.finish_node();
     SyntaxNode::new_root(builder.finish())
 }
@@ -73,36 +43,17 @@ fn rowan_tree(n: usize, strings: &[Box<str>]) -> SyntaxNode<Lang> {
     let c = builder.checkpoint();
 
     for (_, s) in (0..n).zip(strings.iter().cycle()) {
-        builder.start_node(ENTRY.into());
-        builder.token(STRING.into(), s);
+        builder.start_node(kind_raw(Syntax::Entry));
+        builder.token(kind_raw(Syntax::String), s);
         builder.finish_node();
-        builder.token(WHITESPACE.into(), " ");
+        builder.token(kind_raw(Syntax::Whitespace), " ");
     }
 
-    builder.start_node_at(c, ROOT.into());
+    builder.start_node_at(c, kind_raw(Syntax::Root));
     builder.finish_node();
     SyntaxNode::new_root(builder.finish())
 }
 
-fn syntree_tree<F>(n: usize, strings: &[Box<str>]) -> Result<Tree<Syntax, F>, Error<F::Error>>
-where
-    F: syntree::Flavor<Length: From<usize>>,
-{
-    let mut builder = Builder::<_, F>::new_with();
-
-    let c = builder.checkpoint()?;
-
-    for (_, s) in (0..n).zip(strings.iter().cycle()) {
-        builder.open(ENTRY)?;
-        builder.token(STRING, s.len().into())?;
-        builder.close()?;
-        builder.token(WHITESPACE, 1usize.into())?;
-    }
-
-    builder.close_at(&c, ROOT)?;
-    builder.build()
-}
-
 fn setup(c: &mut Criterion) {
     syntree::flavor! {
         struct FlavorUsize {
@@ -141,20 +92,16 @@ fn setup(c: &mut Criterion) {
         for size in sizes {
             group.bench_with_input(BenchmarkId::new("syntree-u32", size), &size, |b, size| {
                 b.iter(|| {
-                    syntree_build::<FlavorDefault>(&strings, *size).expect("failed to build tree")
+                    build_flat::<FlavorDefault>(&strings, *size).expect("failed to build tree")
                 })
             });
 
             group.bench_with_input(BenchmarkId::new("syntree-usize", size), &size, |b, size| {
-                b.iter(|| {
-                    syntree_build::<FlavorUsize>(&strings, *size).expect("failed to build tree")
-                })
+                b.iter(|| build_flat::<FlavorUsize>(&strings, *size).expect("failed to build tree"))
             });
 
             group.bench_with_input(BenchmarkId::new("syntree-empty", size), &size, |b, size| {
-                b.iter(|| {
-                    syntree_build::<FlavorEmpty>(&strings, *size).expect("failed to build tree")
-                })
+                b.iter(|| build_flat::<FlavorEmpty>(&strings, *size).expect("failed to build tree"))
             });
 
             group.bench_with_input(BenchmarkId::new("rowan", size), &size, |b, size| {
@@ -171,7 +118,7 @@ fn setup(c: &mut Criterion) {
                 BenchmarkId::new("syntree-u32-usize", size),
                 &size,
                 |b, size| {
-                    let syntree = syntree_tree::<FlavorDefault>(*size, &strings).unwrap();
+                    let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
                     let root = syntree.first().unwrap();
                     b.iter(|| root.children().count())
                 },
@@ -181,14 +128,14 @@ fn setup(c: &mut Criterion) {
                 BenchmarkId::new("syntree-u32-u16", size),
                 &size,
                 |b, size| {
-                    let syntree = syntree_tree::<FlavorU16>(*size, &strings).unwrap();
+                    let syntree = build_entries::<FlavorU16>(&strings, *size).unwrap();
                     let root = syntree.first().unwrap();
                     b.iter(|| root.children().count())
                 },
             );
 
             group.bench_with_input(BenchmarkId::new("syntree-usize", size), &size, |b, size| {
-                let syntree = syntree_tree::<FlavorUsize>(*size, &strings).unwrap();
+                let syntree = build_entries::<FlavorUsize>(&strings, *size).unwrap();
                 let root = syntree.first().unwrap();
                 b.iter(|| root.children().count())
             });
@@ -205,13 +152,13 @@ fn setup(c: &mut Criterion) {
 
         for size in sizes {
             group.bench_with_input(BenchmarkId::new("syntree-u32", size), &size, |b, size| {
-                let syntree = syntree_tree::<FlavorDefault>(*size, &strings).unwrap();
+                let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
                 let root = syntree.first().unwrap();
                 b.iter(|| root.children().skip_tokens().count())
             });
 
             group.bench_with_input(BenchmarkId::new("syntree-usize", size), &size, |b, size| {
-                let syntree = syntree_tree::<FlavorUsize>(*size, &strings).unwrap();
+                let syntree = build_entries::<FlavorUsize>(&strings, *size).unwrap();
                 let root = syntree.first().unwrap();
                 b.iter(|| root.children().skip_tokens().count())
             });
@@ -222,27 +169,43 @@ fn setup(c: &mut Criterion) {
             });
         }
     }
-}
 
-criterion_group!(benches, setup);
-criterion_main!(benches);
+    {
+        let mut group = c.benchmark_group("scan");
 
-fn generate_random(count: usize, min: usize, max: usize) -> Vec<Box<str>> {
-    let mut rng = StdRng::seed_from_u64(0x12345678);
-    let mut s = String::with_capacity(16);
+        for size in sizes {
+            group.bench_with_input(BenchmarkId::new("walk-values", size), &size, |b, size| {
+                let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
+                b.iter(|| {
+                    syntree
+                        .walk()
+                        .filter(|n| n.value() == Syntax::String)
+                        .count()
+                })
+            });
 
-    let mut output = Vec::with_capacity(count);
+            group.bench_with_input(BenchmarkId::new("flat-values", size), &size, |b, size| {
+                let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
+                b.iter(|| {
+                    syntree
+                        .flat()
+                        .filter(|(v, _, _)| *v == Syntax::String)
+                        .count()
+                })
+            });
 
-    for _ in 0..count {
-        let target = (rng.gen::<usize>() % (max - min)) + min;
-        assert!(target >= min && target < max, "target was {}", target);
+            group.bench_with_input(BenchmarkId::new("walk-spans", size), &size, |b, size| {
+                let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
+                b.iter(|| syntree.walk().map(|n| n.span().len()).sum::<usize>())
+            });
 
-        while s.len() < target {
-            s.extend(char::from_u32(rng.next_u32()).filter(|c| !c.is_whitespace()));
+            group.bench_with_input(BenchmarkId::new("flat-spans", size), &size, |b, size| {
+                let syntree = build_entries::<FlavorDefault>(&strings, *size).unwrap();
+                b.iter(|| syntree.flat().map(|(_, s, _)| s.len()).sum::<usize>())
+            });
         }
-
-        output.push(s.as_str().into());
     }
-
-    output
 }
+
+criterion_group!(benches, setup);
+criterion_main!(benches);
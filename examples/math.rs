@@ -3,7 +3,8 @@
 use std::iter::Peekable;
 
 use anyhow::Result;
-use syntree::{print, Builder, Error, FlavorDefault, Tree};
+use syntree::parse::{self, Lookahead, TokenSource};
+use syntree::{print, Builder, Error, FlavorDefault, Tree, TreeSink};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
@@ -23,79 +24,63 @@ enum Syntax {
 
 use Syntax::*;
 
-struct Parser<Iter>
-where
-    Iter: Iterator<Item = (Syntax, usize)>,
-{
-    builder: Builder<Syntax, FlavorDefault>,
-    iter: Peekable<Iter>,
-}
+type Parser<I> = Lookahead<Builder<Syntax, FlavorDefault>, I, fn(Syntax) -> bool>;
 
-impl<Iter> Parser<Iter>
+fn parse_val<I>(p: &mut Parser<I>) -> Result<(), Error>
 where
-    Iter: Iterator<Item = (Syntax, usize)>,
+    I: Iterator<Item = (Syntax, usize)>,
 {
-    fn peek(&mut self) -> Result<Option<Syntax>, Error> {
-        while self.iter.peek().map_or(false, |&(t, _)| t == Whitespace) {
-            self.bump()?;
-        }
-        Ok(self.iter.peek().map(|&(t, _)| t))
+    if !parse::eat(p, Number)? {
+        p.open(Error)?;
+        p.bump()?;
+        p.close()?;
     }
 
-    fn bump(&mut self) -> Result<(), Error> {
-        if let Some((token, len)) = self.iter.next() {
-            self.builder.token(token, len)?;
-        }
-
-        Ok(())
-    }
+    Ok(())
+}
 
-    fn parse_val(&mut self) -> Result<(), Error> {
-        match self.peek()? {
-            Some(Number) => {
-                self.bump()?;
-            }
-            _ => {
-                self.builder.open(Error)?;
-                self.bump()?;
-                self.builder.close()?;
-            }
-        }
+fn handle_operation<I>(
+    p: &mut Parser<I>,
+    tokens: &[Syntax],
+    next: fn(&mut Parser<I>) -> Result<(), Error>,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = (Syntax, usize)>,
+{
+    let c = p.checkpoint()?;
+    next(p)?;
 
-        Ok(())
+    while p.peek()?.is_some_and(|t| tokens.contains(&t)) {
+        p.bump()?;
+        next(p)?;
+        p.close_at(&c, Operation)?;
     }
 
-    fn handle_operation(
-        &mut self,
-        tokens: &[Syntax],
-        next: fn(&mut Self) -> Result<(), Error>,
-    ) -> Result<(), Error> {
-        let c = self.builder.checkpoint()?;
-        next(self)?;
-
-        while self.peek()?.map_or(false, |t| tokens.contains(&t)) {
-            self.bump()?;
-            next(self)?;
-            self.builder.close_at(&c, Operation)?;
-        }
-
-        Ok(())
-    }
+    Ok(())
+}
 
-    fn parse_mul(&mut self) -> Result<(), Error> {
-        self.handle_operation(&[Mul, Div], Self::parse_val)
-    }
+fn parse_mul<I>(p: &mut Parser<I>) -> Result<(), Error>
+where
+    I: Iterator<Item = (Syntax, usize)>,
+{
+    handle_operation(p, &[Mul, Div], parse_val)
+}
 
-    fn parse_add(&mut self) -> Result<(), Error> {
-        self.handle_operation(&[Add, Sub], Self::parse_mul)
-    }
+fn parse_add<I>(p: &mut Parser<I>) -> Result<(), Error>
+where
+    I: Iterator<Item = (Syntax, usize)>,
+{
+    handle_operation(p, &[Add, Sub], parse_mul)
+}
 
-    fn parse(mut self) -> Result<Tree<Syntax, FlavorDefault>, Error> {
-        self.builder.open(Root)?;
-        self.parse_add()?;
-        self.builder.close()?;
-        self.builder.build()
-    }
+fn parse<I>(mut p: Parser<I>) -> Result<Tree<Syntax, FlavorDefault>, Error>
+where
+    I: Iterator<Item = (Syntax, usize)>,
+{
+    p.open(Root)?;
+    parse_add(&mut p)?;
+    p.close()?;
+    p.into_sink().build()
 }
 
 fn lexer(source: &str) -> impl Iterator<Item = (Syntax, usize)> + '_ {
@@ -139,14 +124,8 @@ fn lexer(source: &str) -> impl Iterator<Item = (Syntax, usize)> + '_ {
 fn main() -> Result<()> {
     let source = std::env::args().skip(1).collect::<String>();
 
-    let iter = lexer(&source);
-
-    let parser = Parser {
-        builder: Builder::new(),
-        iter: iter.peekable(),
-    };
-
-    let tree = parser.parse()?;
+    let parser: Parser<_> = Lookahead::new(Builder::new(), lexer(&source), |t| t == Whitespace);
+    let tree = parse(parser)?;
 
     print::print_with_source(std::io::stdout(), &tree, &source)?;
     Ok(())
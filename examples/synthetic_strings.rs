@@ -136,5 +136,13 @@ fn main() -> Result<()> {
         count += 1;
     }
 
+    let mut expanded = String::new();
+
+    tree.write_source(&mut expanded, |value, span| match value {
+        Synthetic(id) => storage.get(id).unwrap_or(&source[span.range()]),
+        _ => &source[span.range()],
+    })?;
+
+    println!("Expanded: {expanded}");
     Ok(())
 }
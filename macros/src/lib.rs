@@ -0,0 +1,210 @@
+//! Derive macros for [syntree].
+//!
+//! These are re-exported from `syntree` when its `derive` feature is enabled,
+//! so this crate shouldn't be depended on directly.
+//!
+//! [syntree]: https://docs.rs/syntree
+
+#![deny(missing_docs)]
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+/// Derive helpers for an enum used as the syntax kind of a tree.
+///
+/// This generates the following inherent methods for the enum:
+/// * `is_trivia(&self) -> bool` which is `true` for variants marked with
+///   `#[syntax(trivia)]`, such as whitespace and comments.
+/// * `is_keyword(&self) -> bool` which is `true` for variants marked with
+///   `#[syntax(keyword)]`.
+/// * `display_name(&self) -> &'static str` which is the name of the variant
+///   unless overriden with `#[syntax(name = "...")]`. This is suitable for use
+///   when printing a tree for users.
+/// * `to_raw(self) -> u16` and `from_raw(u16) -> Option<Self>` which maps each
+///   variant to and from a compact number in declaration order, as expected by
+///   libraries such as [rowan].
+///
+/// Only enums with fieldless variants are supported.
+///
+/// [rowan]: https://docs.rs/rowan
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, syntree::Syntax)]
+/// enum Syntax {
+///     #[syntax(trivia)]
+///     Whitespace,
+///     #[syntax(trivia, name = "comment")]
+///     Comment,
+///     #[syntax(keyword, name = "`fn`")]
+///     Fn,
+///     Ident,
+/// }
+///
+/// assert!(Syntax::Whitespace.is_trivia());
+/// assert!(!Syntax::Ident.is_trivia());
+/// assert!(Syntax::Fn.is_keyword());
+///
+/// assert_eq!(Syntax::Comment.display_name(), "comment");
+/// assert_eq!(Syntax::Fn.display_name(), "`fn`");
+/// assert_eq!(Syntax::Ident.display_name(), "Ident");
+///
+/// assert_eq!(Syntax::Fn.to_raw(), 2);
+/// assert_eq!(Syntax::from_raw(2), Some(Syntax::Fn));
+/// assert_eq!(Syntax::from_raw(4), None);
+/// ```
+#[proc_macro_derive(Syntax, attributes(syntax))]
+pub fn derive_syntax(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    match expand(&input) {
+        Ok(stream) => stream.into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+/// Attributes of a single variant.
+struct Variant<'a> {
+    ident: &'a syn::Ident,
+    trivia: bool,
+    keyword: bool,
+    name: LitStr,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "#[derive(Syntax)] is only supported on enums",
+        ));
+    };
+
+    let mut variants = Vec::with_capacity(data.variants.len());
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "#[derive(Syntax)] only supports fieldless variants",
+            ));
+        }
+
+        let mut trivia = false;
+        let mut keyword = false;
+        let mut name = None;
+
+        for attr in &variant.attrs {
+            if !attr.path().is_ident("syntax") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("trivia") {
+                    trivia = true;
+                } else if meta.path.is_ident("keyword") {
+                    keyword = true;
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                } else {
+                    return Err(meta.error("unsupported syntax attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let name =
+            name.unwrap_or_else(|| LitStr::new(&variant.ident.to_string(), Span::call_site()));
+
+        variants.push(Variant {
+            ident: &variant.ident,
+            trivia,
+            keyword,
+            name,
+        });
+    }
+
+    if u16::try_from(variants.len()).is_err() {
+        return Err(syn::Error::new_spanned(
+            input,
+            "#[derive(Syntax)] supports at most 65535 variants",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let trivia = matches_any(variants.iter().filter(|v| v.trivia));
+    let keyword = matches_any(variants.iter().filter(|v| v.keyword));
+
+    let names = variants.iter().map(|v| {
+        let (ident, name) = (v.ident, &v.name);
+        quote!(Self::#ident => #name)
+    });
+
+    let to_raw = variants.iter().enumerate().map(|(n, v)| {
+        let (ident, n) = (v.ident, n as u16);
+        quote!(Self::#ident => #n)
+    });
+
+    let from_raw = variants.iter().enumerate().map(|(n, v)| {
+        let (ident, n) = (v.ident, n as u16);
+        quote!(#n => ::core::option::Option::Some(Self::#ident))
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Test if this kind is trivia, such as whitespace or comments.
+            #[inline]
+            pub const fn is_trivia(&self) -> bool {
+                #trivia
+            }
+
+            /// Test if this kind is a keyword.
+            #[inline]
+            pub const fn is_keyword(&self) -> bool {
+                #keyword
+            }
+
+            /// Get the name of this kind as displayed to users.
+            #[inline]
+            pub const fn display_name(&self) -> &'static str {
+                match *self {
+                    #(#names,)*
+                }
+            }
+
+            /// Convert this kind into its raw number.
+            #[inline]
+            pub const fn to_raw(self) -> u16 {
+                match self {
+                    #(#to_raw,)*
+                }
+            }
+
+            /// Convert a raw number into a kind, returning `None` if it
+            /// doesn't correspond to a kind.
+            #[inline]
+            pub const fn from_raw(raw: u16) -> ::core::option::Option<Self> {
+                match raw {
+                    #(#from_raw,)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+/// Expand to an expression testing if `self` is any of the given variants.
+fn matches_any<'a>(mut variants: impl Iterator<Item = &'a Variant<'a>>) -> TokenStream {
+    let Some(first) = variants.next() else {
+        return quote!(false);
+    };
+
+    let first = first.ident;
+    let rest = variants.map(|v| v.ident);
+    quote!(::core::matches!(*self, Self::#first #(| Self::#rest)*))
+}
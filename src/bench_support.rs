@@ -0,0 +1,196 @@
+//! Workloads used by the benchmarks of this crate.
+//!
+//! These are exposed so that downstream crates can benchmark their own
+//! [`Flavor`] implementations against the same trees that syntree is
+//! benchmarked against, without having to copy the routines used to generate
+//! them.
+//!
+//! This module requires the `bench-support` feature to be enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use syntree::bench_support::{self, Syntax};
+//! use syntree::FlavorDefault;
+//!
+//! let strings = bench_support::generate_random(100, 5, 20);
+//! let tree = bench_support::build_entries::<FlavorDefault>(&strings, 16)?;
+//!
+//! let root = tree.first().ok_or("missing root")?;
+//! assert_eq!(root.value(), Syntax::Root);
+//! assert_eq!(root.children().skip_tokens().count(), 16);
+//! # Ok::<_, Box<dyn core::error::Error>>(())
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::builder::Builder;
+use crate::error::BuildResult;
+use crate::flavor::Flavor;
+use crate::tree::Tree;
+
+/// The seed used by [`generate_random`].
+const SEED: u64 = 0x1234_5678;
+
+/// The kind of values which are used in benchmark trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+#[non_exhaustive]
+pub enum Syntax {
+    /// A token holding one of the generated strings.
+    String,
+    /// A node wrapping a single [`Syntax::String`] token.
+    Entry,
+    /// A single byte whitespace token.
+    Whitespace,
+    /// The root node of the tree.
+    Root,
+}
+
+/// Generate `count` strings whose byte lengths are in the range `min..max`.
+///
+/// The strings are generated from a fixed seed, so the output is the same
+/// every time this function is called with the same arguments.
+///
+/// # Panics
+///
+/// Panics if `min >= max`.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::bench_support;
+///
+/// let a = bench_support::generate_random(10, 5, 20);
+/// let b = bench_support::generate_random(10, 5, 20);
+///
+/// assert_eq!(a.len(), 10);
+/// assert_eq!(a, b);
+/// assert!(a.iter().all(|s| s.len() >= 5));
+/// ```
+#[must_use]
+pub fn generate_random(count: usize, min: usize, max: usize) -> Vec<Box<str>> {
+    assert!(min < max, "min must be smaller than max");
+
+    let mut rng = Rng::new(SEED);
+    let mut output = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let target = (rng.next_u64() as usize % (max - min)) + min;
+        let mut s = String::with_capacity(target + 4);
+
+        while s.len() < target {
+            s.extend(char::from_u32(rng.next_u64() as u32).filter(|c| !c.is_whitespace()));
+        }
+
+        output.push(s.into());
+    }
+
+    output
+}
+
+/// Build a tree with a single [`Syntax::Root`] node containing `count`
+/// [`Syntax::String`] tokens, cycling through `strings`.
+///
+/// The root is constructed using [`Builder::close_at`] once all tokens have
+/// been added.
+///
+/// # Errors
+///
+/// Errors if the tree could not be constructed, such as if the flavor runs out
+/// of identifiers.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::bench_support;
+/// use syntree::FlavorDefault;
+///
+/// let strings = bench_support::generate_random(10, 5, 20);
+/// let tree = bench_support::build_flat::<FlavorDefault>(&strings, 32)?;
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// assert_eq!(root.children().count(), 32);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub fn build_flat<F>(strings: &[Box<str>], count: usize) -> BuildResult<Tree<Syntax, F>, F>
+where
+    F: Flavor,
+{
+    let mut builder = Builder::<_, F>::new_with();
+
+    let c = builder.checkpoint()?;
+
+    for s in strings.iter().cycle().take(count) {
+        builder.token_usize(Syntax::String, s.len())?;
+    }
+
+    builder.close_at(&c, Syntax::Root)?;
+    builder.build()
+}
+
+/// Build a tree with a single [`Syntax::Root`] node containing `count`
+/// [`Syntax::Entry`] nodes, each followed by a [`Syntax::Whitespace`] token.
+///
+/// Every entry wraps one [`Syntax::String`] token, cycling through `strings`.
+///
+/// # Errors
+///
+/// Errors if the tree could not be constructed, such as if the flavor runs out
+/// of identifiers.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::bench_support::{self, Syntax};
+/// use syntree::FlavorDefault;
+///
+/// let strings = bench_support::generate_random(10, 5, 20);
+/// let tree = bench_support::build_entries::<FlavorDefault>(&strings, 4)?;
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// assert_eq!(root.children().count(), 8);
+/// assert!(root.children().skip_tokens().all(|n| n.value() == Syntax::Entry));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub fn build_entries<F>(strings: &[Box<str>], count: usize) -> BuildResult<Tree<Syntax, F>, F>
+where
+    F: Flavor,
+{
+    let mut builder = Builder::<_, F>::new_with();
+
+    let c = builder.checkpoint()?;
+
+    for s in strings.iter().cycle().take(count) {
+        builder.open(Syntax::Entry)?;
+        builder.token_usize(Syntax::String, s.len())?;
+        builder.close()?;
+        builder.token_usize(Syntax::Whitespace, 1)?;
+    }
+
+    builder.close_at(&c, Syntax::Root)?;
+    builder.build()
+}
+
+/// A small deterministic xorshift generator, so that the workloads don't
+/// depend on an external source of randomness.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+}
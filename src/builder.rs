@@ -1,13 +1,31 @@
 mod checkpoint;
+mod dual;
+mod expecting;
+mod fixed_checkpoint;
+mod guard;
+#[cfg(feature = "instrument")]
+mod metrics;
+mod pending;
+mod sink;
 
 use core::cell::Cell;
 
-use crate::links::Links;
+use crate::error::BuildResult;
+use crate::links::{Links, LinksOf};
 use crate::{
-    Error, Flavor, FlavorDefault, Index, Length, Pointer, Span, Storage, Tree, TreeIndex, Width,
+    Error, Flags, Flavor, FlavorDefault, Index, Indexes, IntoLength, Kind, Length, Pointer, Span,
+    Tree, Width,
 };
 
 pub use self::checkpoint::Checkpoint;
+pub use self::dual::{DualBuilder, DualCheckpoint};
+pub use self::expecting::Expecting;
+pub use self::fixed_checkpoint::FixedCheckpoint;
+pub use self::guard::NodeGuard;
+#[cfg(feature = "instrument")]
+pub use self::metrics::Metrics;
+pub use self::pending::PendingNode;
+pub use self::sink::TreeSink;
 
 /// A builder for a [Tree].
 ///
@@ -68,6 +86,15 @@ where
     sibling: Option<F::Pointer>,
     /// The current cursor.
     cursor: F::Index,
+    /// Flags which cause another set of flags to be set on every ancestor of
+    /// the node they are set on, see [`Builder::propagate_flags`].
+    propagate: Option<(F::Flags, F::Flags)>,
+    /// If spans must be specified explicitly, see
+    /// [`Builder::set_explicit_spans`].
+    explicit_spans: bool,
+    /// Collected metrics.
+    #[cfg(feature = "instrument")]
+    metrics: Metrics,
 }
 
 impl<T> Builder<T, FlavorDefault>
@@ -172,9 +199,151 @@ where
             checkpoint: None,
             sibling: None,
             cursor: F::Index::EMPTY,
+            propagate: None,
+            explicit_spans: false,
+            #[cfg(feature = "instrument")]
+            metrics: Metrics::EMPTY,
         }
     }
 
+    /// Reopen a finished tree so that more nodes can be appended to the end of
+    /// it.
+    ///
+    /// The cursor of the builder is placed at the end of the tree and new
+    /// nodes are added as siblings of its last top-level node. Existing nodes
+    /// keep their identifiers, and whether empty tokens are indexed is
+    /// preserved from the tree.
+    ///
+    /// This is useful for REPL-style parsers which append new statements to an
+    /// existing session tree without re-parsing earlier input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Builder;
+    ///
+    /// let mut tree = Builder::new();
+    ///
+    /// tree.open("stmt")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let mut tree = Builder::from_tree(tree);
+    /// assert_eq!(*tree.cursor(), 3);
+    ///
+    /// tree.open("stmt")?;
+    /// tree.token("lit", 2)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "stmt" => {
+    ///         ("lit", 3)
+    ///     },
+    ///     "stmt" => {
+    ///         ("lit", 2)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    ///
+    /// let second = tree.node_with_range(3..5).ok_or("missing second")?;
+    /// assert_eq!(second.value(), "stmt");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn from_tree(tree: Tree<T, F>) -> Self {
+        let sibling = tree.last().map(|n| n.id());
+        let cursor = tree.span().end;
+
+        Builder {
+            tree,
+            parent: None,
+            checkpoint: None,
+            sibling,
+            cursor,
+            propagate: None,
+            explicit_spans: false,
+            #[cfg(feature = "instrument")]
+            metrics: Metrics::EMPTY,
+        }
+    }
+
+    /// Construct a new builder with capacity reserved for `nodes` nodes and
+    /// `tokens` entries in the token index.
+    ///
+    /// Only tokens with non-empty spans are indexed by default, so `tokens`
+    /// only needs to account for those.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Flavor`] if the storage of the flavor fails to
+    /// allocate the requested capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Builder;
+    ///
+    /// let mut tree: Builder<_> = Builder::try_with_capacity(2, 1)?;
+    ///
+    /// tree.open("root")?;
+    /// tree.token("lit", 4)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// assert!(tree.capacity() >= 2);
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 4)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn try_with_capacity(nodes: usize, tokens: usize) -> BuildResult<Self, F> {
+        let mut builder = Self::new_with();
+        builder.tree = Tree::with_capacity(nodes, tokens)?;
+        Ok(builder)
+    }
+
+    /// Wrap the builder in an [`Expecting`] builder, which panics instead of
+    /// returning errors.
+    ///
+    /// See [`Expecting`] for documentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    /// tree.open("root")?;
+    ///
+    /// let mut tree = tree.expecting();
+    /// tree.token("lit", 3);
+    /// tree.close();
+    ///
+    /// let tree = tree.build();
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn expecting(self) -> Expecting<T, F> {
+        Expecting::from(self)
+    }
+
     /// Get a reference to the current cursor position of the syntax tree.
     ///
     /// The cursor position is the position in which it's been advanced so far
@@ -243,6 +412,251 @@ where
         self.cursor = cursor;
     }
 
+    /// Configure whether tokens with an empty span should be included in the
+    /// position index of the tree.
+    ///
+    /// By default, empty tokens are not indexed which means that they are
+    /// invisible to queries such as [`Tree::node_with_span`]. When enabled,
+    /// a query for a position at which an empty token is located will resolve
+    /// to the node containing that token. This is useful for zero-width
+    /// markers such as a synthesized end of file or a missing item.
+    ///
+    /// This only affects tokens inserted after it has been called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    /// tree.set_index_empty_tokens(true);
+    ///
+    /// tree.open("root")?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// tree.open("missing")?;
+    /// tree.token_empty("expected-comma")?;
+    /// tree.close()?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 2)?;
+    /// tree.close()?;
+    ///
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let node = tree.node_with_span(Span::point(3)).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "missing");
+    ///
+    /// let node = tree.node_with_span(Span::point(4)).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "number");
+    ///
+    /// // Queries which aren't points are unaffected by empty tokens.
+    /// let node = tree.node_with_span(Span::new(3, 5)).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "number");
+    ///
+    /// let node = tree.node_with_span(Span::new(3, 4)).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "number");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn set_index_empty_tokens(&mut self, enabled: bool) {
+        self.tree.set_index_empty(enabled);
+    }
+
+    /// Configure whether every span must be specified explicitly.
+    ///
+    /// By default spans are derived from a cursor which advances as tokens
+    /// are inserted, and nodes grow to cover their children. This doesn't fit
+    /// trees over sources which aren't laid out linearly, such as snippets
+    /// concatenated from different places or binary formats with offsets.
+    ///
+    /// When enabled, spans are taken as-is from
+    /// [`Builder::open_with`] and [`Builder::token_with`], and the span of
+    /// the tree covers every span in it. Anything which would derive a span
+    /// from the cursor, such as [`Builder::open`] or [`Builder::token`],
+    /// errors with [`Error::ImplicitSpan`]. Nodes inserted with
+    /// [`Builder::close_at`] still span the nodes they wrap.
+    ///
+    /// Tokens don't have to be inserted in order of their spans, since the
+    /// token index is sorted once the tree is built. So queries by position
+    /// such as [`Tree::token_at`] still work.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    /// tree.set_explicit_spans(true);
+    ///
+    /// // A table of contents which refers to sections later in the file.
+    /// tree.open_with("toc", Span::new(0, 8))?;
+    /// tree.token_with("entry", Span::new(100, 140))?;
+    /// tree.token_with("entry", Span::new(40, 100))?;
+    /// tree.close()?;
+    ///
+    /// tree.open_with("section", Span::new(40, 100))?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let toc = tree.first().ok_or("missing toc")?;
+    /// assert_eq!(toc.span(), &Span::new(0, 8));
+    ///
+    /// let entries = toc.children().map(|n| n.span().range());
+    /// assert!(entries.eq([100..140, 40..100]));
+    ///
+    /// assert_eq!(tree.span(), &Span::new(0, 140));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn set_explicit_spans(&mut self, enabled: bool) {
+        self.explicit_spans = enabled;
+
+        // Tokens might be out of order from here on, even if explicit spans
+        // are disabled again.
+        if enabled {
+            self.tree.set_explicit_spans();
+        }
+    }
+
+    /// Set the identifier of the source the tree is built from.
+    ///
+    /// This is a user supplied value such as a checksum of the source text,
+    /// which is stored in the built tree and can be retrieved with
+    /// [`Tree::source_id`]. It is retained by trees produced through
+    /// [`Builder::flush`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// let source = "foo";
+    ///
+    /// let mut hasher = DefaultHasher::new();
+    /// source.hash(&mut hasher);
+    /// let checksum = hasher.finish();
+    ///
+    /// let mut tree = syntree::Builder::new();
+    /// tree.set_source_id(checksum);
+    /// tree.token_str("ident", source)?;
+    /// let tree = tree.build()?;
+    ///
+    /// assert_eq!(tree.source_id(), Some(checksum));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn set_source_id(&mut self, id: u64) {
+        self.tree.set_source_id(Some(id));
+    }
+
+    /// Set the flags in `flag` on the node identified by `id`, in addition to
+    /// any flags which are already set on it.
+    ///
+    /// See [Flags] for documentation.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::MissingNode`] if `id` doesn't identify a node in
+    /// the tree being built.
+    pub fn set_flag(&mut self, id: F::Pointer, flag: F::Flags) -> BuildResult<(), F> {
+        let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+        links.flags = links.flags.union(flag);
+
+        let Some((from, to)) = self.propagate else {
+            return Ok(());
+        };
+
+        if !flag.intersects(from.union(to)) {
+            return Ok(());
+        }
+
+        let mut current = links.parent;
+
+        while let Some(id) = current {
+            let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+
+            // Ancestors of a node which already has the flags are guaranteed to
+            // have them as well.
+            if links.flags.contains(to) {
+                break;
+            }
+
+            links.flags = links.flags.union(to);
+            current = links.parent;
+        }
+
+        Ok(())
+    }
+
+    /// Configure the builder so that setting any of the flags in `from` on a
+    /// node with [`Builder::set_flag`] also sets the flags in `to` on all of
+    /// its ancestors.
+    ///
+    /// This is typically used to maintain a "contains error" flag, so that
+    /// testing whether a subtree contains any errors is a constant time
+    /// operation through [`Node::has_flag`][crate::Node::has_flag] instead of
+    /// a walk. Ancestors are updated when the flag is set, and nodes which are
+    /// inserted as ancestors later through [`Builder::close_at`] or
+    /// [`Builder::open_above`] inherit `to` if any of the nodes they wrap have
+    /// flags in `from` or `to`.
+    ///
+    /// This only affects flags set after it has been called, and replaces any
+    /// previous configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// syntree::flavor! {
+    ///     struct FlavorFlags {
+    ///         type Index = u32;
+    ///         type Flags = u8;
+    ///     }
+    /// }
+    ///
+    /// const ERROR: u8 = 1 << 0;
+    /// const CONTAINS_ERROR: u8 = 1 << 1;
+    ///
+    /// let mut tree = syntree::Builder::<_, FlavorFlags>::new_with();
+    /// tree.propagate_flags(ERROR, CONTAINS_ERROR);
+    ///
+    /// let c = tree.checkpoint()?;
+    ///
+    /// tree.open("item")?;
+    /// tree.token("ident", 3)?;
+    /// tree.close()?;
+    ///
+    /// tree.open("item")?;
+    /// tree.open("expr")?;
+    /// let error = tree.token("error", 1)?;
+    /// tree.set_flag(error, ERROR)?;
+    /// tree.close()?;
+    /// tree.close()?;
+    ///
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let clean = tree
+    ///     .walk()
+    ///     .filter(|n| !n.has_flag(ERROR) && !n.has_flag(CONTAINS_ERROR))
+    ///     .map(|n| n.value());
+    ///
+    /// assert!(clean.eq(["item", "ident"]));
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// assert_eq!(root.value(), "root");
+    /// assert!(root.has_flag(CONTAINS_ERROR));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn propagate_flags(&mut self, from: F::Flags, to: F::Flags) {
+        self.propagate = Some((from, to));
+    }
+
     /// Start a node with the given `data`.
     ///
     /// This pushes a new link with the given type onto the stack which links
@@ -271,8 +685,13 @@ where
     /// tree.close()?;
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn open(&mut self, data: T) -> Result<F::Pointer, Error<F::Error>> {
-        let id = self.insert(data, Span::point(self.cursor))?;
+    pub fn open(&mut self, data: T) -> BuildResult<F::Pointer, F> {
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.opens += 1;
+        }
+
+        let id = self.insert(data, Kind::Node, Span::point(self.implicit_cursor()?))?;
         self.parent = Some(id);
         Ok(id)
     }
@@ -320,12 +739,13 @@ where
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn open_with(
-        &mut self,
-        data: T,
-        span: Span<F::Index>,
-    ) -> Result<F::Pointer, Error<F::Error>> {
-        let id = self.insert(data, span)?;
+    pub fn open_with(&mut self, data: T, span: Span<F::Index>) -> BuildResult<F::Pointer, F> {
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.opens += 1;
+        }
+
+        let id = self.insert(data, Kind::Node, span)?;
         self.parent = Some(id);
         Ok(id)
     }
@@ -356,33 +776,48 @@ where
     /// tree.close()?;
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn close(&mut self) -> Result<(), Error<F::Error>> {
+    pub fn close(&mut self) -> BuildResult<(), F> {
         let head = self.parent.take().ok_or(Error::CloseError)?;
 
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.closes += 1;
+        }
+
         self.sibling = Some(head);
 
         let &mut Links { parent, span, .. } = self
             .tree
             .get_mut(head)
-            .ok_or_else(|| Error::MissingNode(head.get()))?;
+            .ok_or_else(|| Error::MissingNode(head))?;
 
         if let Some(id) = parent {
             let parent = self
                 .tree
                 .get_mut(id)
-                .ok_or_else(|| Error::MissingNode(id.get()))?;
+                .ok_or_else(|| Error::MissingNode(id))?;
+
+            if !self.explicit_spans {
+                parent.span = parent.span.join(&span);
+            }
 
-            parent.span = parent.span.join(&span);
             self.parent = Some(id);
         }
 
-        self.cursor = span.end;
+        if !self.explicit_spans {
+            self.cursor = span.end;
+        }
+
         Ok(())
     }
 
-    /// Declare a token with the specified `value` and a corresponding `len`.
+    /// Start a node whose value is supplied once it's closed with
+    /// [`Builder::close_with`].
     ///
-    /// A token is always a terminating element without children.
+    /// This is useful for grammars which only know what kind of node they are
+    /// building after having parsed its contents, such as telling a tuple
+    /// apart from a parenthesized expression. Until the node is closed it holds
+    /// the default value of `T`.
     ///
     /// # Errors
     ///
@@ -394,367 +829,1679 @@ where
     /// ```
     /// let mut tree = syntree::Builder::new();
     ///
-    /// tree.open("child")?;
-    /// tree.token("lit", 4)?;
-    /// tree.close()?;
+    /// let pending = tree.open_deferred()?;
+    /// tree.token("open", 1)?;
+    /// tree.token("number", 1)?;
+    /// tree.token("comma", 1)?;
+    /// tree.token("close", 1)?;
+    /// tree.close_with(pending, "tuple")?;
     ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     "child" => {
-    ///         ("lit", 4)
+    ///     "tuple" => {
+    ///         ("open", 1),
+    ///         ("number", 1),
+    ///         ("comma", 1),
+    ///         ("close", 1),
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn token(&mut self, value: T, len: F::Length) -> Result<F::Pointer, Error<F::Error>> {
-        let start = self.cursor;
-
-        if !len.is_empty() {
-            self.cursor = self.cursor.checked_add_len(len).ok_or(Error::Overflow)?;
-            self.tree.span_mut().end = self.cursor;
-        }
-
-        let id = self.insert(value, Span::new(start, self.cursor))?;
-        self.sibling = Some(id);
-
-        if !len.is_empty() {
-            self.tree.indexes_mut().push(TreeIndex {
-                index: self.cursor,
-                id,
-            })?;
-        }
-
-        Ok(id)
+    pub fn open_deferred(&mut self) -> BuildResult<PendingNode<F::Pointer>, F>
+    where
+        T: Default,
+    {
+        let id = self.open(T::default())?;
+        Ok(PendingNode::new(id))
     }
 
-    /// Insert a token with a custom span.
+    /// End a node opened with [`Builder::open_deferred`] and give it its
+    /// `value`.
     ///
     /// # Errors
     ///
-    /// Errors with [`Error::Overflow`] in case we run out of node identifiers.
+    /// Errors with [`Error::CloseError`] if the pending node is not the
+    /// innermost open node, such as when nodes opened inside of it have not
+    /// been closed.
     ///
     /// # Examples
     ///
     /// ```
-    /// use syntree::Span;
+    /// use syntree::Error;
     ///
     /// let mut tree = syntree::Builder::new();
     ///
-    /// tree.open_with("child", Span::new(0, 10))?;
-    /// tree.token_with("lit", Span::new(2, 4))?;
-    /// tree.token_with("lit2", Span::new(4, 6))?;
-    /// tree.close()?;
-    ///
-    /// let tree = tree.build()?;
+    /// let pending = tree.open_deferred()?;
+    /// tree.open("child")?;
     ///
-    /// let expected = syntree::tree! {
-    ///     ("child", (0, 10)) => {
-    ///         ("lit", (2, 4)),
-    ///         ("lit2", (4, 6)),
-    ///     }
-    /// };
+    /// assert!(matches!(tree.close_with(pending, "root"), Err(Error::CloseError)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn close_with(&mut self, pending: PendingNode<F::Pointer>, value: T) -> BuildResult<(), F> {
+        let id = pending.id();
+
+        if self.parent != Some(id) {
+            return Err(Error::CloseError);
+        }
+
+        self.tree
+            .get_mut(id)
+            .ok_or_else(|| Error::MissingNode(id))?
+            .data
+            .set(value);
+
+        self.close()
+    }
+
+    /// Get the identifier and value of the most recently inserted node or
+    /// token, or `None` if nothing has been inserted.
     ///
-    /// assert_eq!(tree, expected);
+    /// This allows a parser to look back at what it just emitted, such as
+    /// checking whether the previous token was a `>` when deciding how to
+    /// handle another one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// assert_eq!(tree.last(), None);
+    ///
+    /// let op = tree.open("operation")?;
+    /// assert_eq!(tree.last(), Some((op, "operation")));
+    ///
+    /// let gt = tree.token(">", 1)?;
+    /// tree.close()?;
+    ///
+    /// assert_eq!(tree.last(), Some((gt, ">")));
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
+    #[must_use]
+    pub fn last(&self) -> Option<(F::Pointer, T)> {
+        let id = F::Pointer::new(self.tree.len().checked_sub(1)?)?;
+        Some((id, self.get(id)?))
+    }
+
+    /// Get the value of the node or token identified by `id`, or `None` if it
+    /// hasn't been inserted.
     ///
-    /// Tokens with spans forces the cursor to be set to the end of the span.
+    /// # Examples
     ///
     /// ```
-    /// use syntree::Span;
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let root = tree.open("root")?;
+    /// let lit = tree.token("lit", 3)?;
+    ///
+    /// assert_eq!(tree.get(root), Some("root"));
+    /// assert_eq!(tree.get(lit), Some("lit"));
+    ///
+    /// tree.close()?;
     ///
+    /// let tree = tree.build()?;
+    /// assert_eq!(tree.get(lit).map(|n| n.value()), Some("lit"));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn get(&self, id: F::Pointer) -> Option<T> {
+        Some(self.tree.get(id)?.value())
+    }
+
+    /// Replace the value of the node or token identified by `id`, returning
+    /// the old value.
+    ///
+    /// This can be used on nodes which are still open as well as on those
+    /// which have already been closed, allowing a parser to refine a node
+    /// after having seen more input. The span and structure of the tree are
+    /// left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::MissingNode`] if there is no node with the given
+    /// `id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
     /// let mut tree = syntree::Builder::new();
     ///
-    /// tree.open("child")?;
-    /// tree.token_with("lit", Span::new(2, 4))?;
-    /// tree.token_with("lit2", Span::new(4, 6))?;
-    /// tree.token("lit3", 6)?;
+    /// let op = tree.open("operation")?;
+    /// tree.token("number", 1)?;
+    /// tree.token("plus", 1)?;
+    /// tree.token("number", 1)?;
     /// tree.close()?;
     ///
+    /// assert_eq!(tree.set_value(op, "add")?, "operation");
+    ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     ("child", (0, 6)) => {
-    ///         ("lit", (2, 4)),
-    ///         ("lit2", (4, 6)),
-    ///         ("lit3", (6, 12)),
+    ///     "add" => {
+    ///         ("number", 1),
+    ///         ("plus", 1),
+    ///         ("number", 1),
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn token_with(
-        &mut self,
-        value: T,
-        span: Span<F::Index>,
-    ) -> Result<F::Pointer, Error<F::Error>> {
-        let id = self.insert(value, span)?;
-
-        self.sibling = Some(id);
-        self.tree.indexes_mut().push(TreeIndex {
-            index: span.start,
-            id,
-        })?;
-
-        if let Some(parent) = self.parent.and_then(|id| self.tree.get_mut(id)) {
-            parent.span = parent.span.join(&span);
-        }
-
-        self.cursor = span.end;
-        Ok(id)
+    pub fn set_value(&mut self, id: F::Pointer, value: T) -> BuildResult<T, F> {
+        let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+        Ok(links.data.replace(value))
     }
 
-    /// Declare a token with the specified `value` and an empty length.
+    /// Construct a node with the given `data` whose children are built by the
+    /// closure `f`.
     ///
-    /// A token is always a terminating element without children.
+    /// The node is opened before `f` is called and closed afterwards, even if
+    /// `f` returns early with an error. Any nodes which `f` opened inside of
+    /// the node but didn't close are closed as well, so the builder is always
+    /// left balanced.
     ///
     /// # Errors
     ///
-    /// Errors with [`Error::Overflow`] in case we run out of node
-    /// identifiers.
+    /// Errors with the error returned by `f` after the node has been closed.
+    /// Otherwise errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers, or with [`Error::CloseError`] if `f` closed the node
+    /// itself.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut tree = syntree::Builder::new();
     ///
-    /// tree.open("child")?;
-    /// tree.token_empty("lit")?;
-    /// tree.close()?;
+    /// tree.node("root", |b| {
+    ///     b.node("number", |b| b.token("lit", 3))?;
+    ///     b.token("ws", 1)?;
+    ///     b.node("ident", |b| b.token("lit", 2))
+    /// })?;
     ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     "child" => {
-    ///         "lit"
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 3)
+    ///         },
+    ///         ("ws", 1),
+    ///         "ident" => {
+    ///             ("lit", 2)
+    ///         }
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn token_empty(&mut self, value: T) -> Result<F::Pointer, Error<F::Error>> {
-        self.token(value, F::Length::EMPTY)
-    }
-
-    /// Get a checkpoint corresponding to the current position in the tree.
-    ///
-    /// # Mixing checkpoints
     ///
-    /// Note that using checkpoints from a different tree doesn't have a
-    /// well-specified behavior - it might seemingly work or it might raise an
-    /// error during closing such as [`Error::MissingNode`].
-    ///
-    /// The following *is not* well-defined, here we're using a checkpoint for a
-    /// different tree but it "just happens" to work because both trees have
-    /// identical internal topologies:
+    /// Nodes are closed even if the closure fails:
     ///
     /// ```
     /// use syntree::{Builder, Error};
     ///
-    /// let mut a = Builder::new();
-    /// let mut b = Builder::new();
+    /// let mut tree = Builder::new();
     ///
-    /// let c = b.checkpoint()?;
+    /// let result = tree.node("root", |b| {
+    ///     b.open("unclosed")?;
+    ///     b.token("lit", 3)?;
+    ///     Err::<(), _>(Error::Overflow)
+    /// });
     ///
-    /// a.open("child")?;
-    /// a.close()?;
+    /// assert!(matches!(result, Err(Error::Overflow)));
     ///
-    /// b.open("child")?;
-    /// b.close()?;
+    /// // The error from the closure is preserved even if the node can't be
+    /// // closed afterwards.
+    /// let result = tree.node("closed", |b| {
+    ///     b.close()?;
+    ///     Err::<(), _>(Error::Overflow)
+    /// });
     ///
-    /// // Checkpoint use from different tree.
-    /// a.close_at(&c, "root")?;
+    /// assert!(matches!(result, Err(Error::Overflow)));
     ///
-    /// let unexpected = syntree::tree! {
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
     ///     "root" => {
-    ///         "child"
-    ///     }
+    ///         "unclosed" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     },
+    ///     "closed",
     /// };
     ///
-    /// assert_eq!(a.build()?, unexpected);
+    /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
+    pub fn node<O>(
+        &mut self,
+        data: T,
+        f: impl FnOnce(&mut Self) -> BuildResult<O, F>,
+    ) -> BuildResult<O, F> {
+        let id = self.open(data)?;
+
+        match f(self) {
+            Ok(output) => {
+                self.close_through(id)?;
+                Ok(output)
+            }
+            Err(error) => {
+                // The error from the closure takes precedence, it might for
+                // example have closed the node itself before failing.
+                _ = self.close_through(id);
+                Err(error)
+            }
+        }
+    }
+
+    /// Start a node with the given `data` which is closed when the returned
+    /// guard is dropped.
+    ///
+    /// See [`NodeGuard`] for documentation.
     ///
     /// # Errors
     ///
     /// Errors with [`Error::Overflow`] in case we run out of node
     /// identifiers.
+    pub fn open_scoped(&mut self, data: T) -> BuildResult<NodeGuard<'_, T, F>, F> {
+        let id = self.open(data)?;
+        Ok(NodeGuard::new(self, id))
+    }
+
+    /// Close every open node up until and including the node `id`.
+    fn close_through(&mut self, id: F::Pointer) -> BuildResult<(), F> {
+        // Make sure that the node is still open before closing anything, so
+        // that nodes outside of it are left alone.
+        let mut current = self.parent;
+
+        loop {
+            let Some(head) = current else {
+                return Err(Error::CloseError);
+            };
+
+            if head == id {
+                break;
+            }
+
+            current = self.tree.get(head).and_then(|n| n.parent()).map(|n| n.id());
+        }
+
+        loop {
+            let head = self.parent;
+            self.close()?;
+
+            if head == Some(id) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Declare a token with the specified `value` and a corresponding `len`.
     ///
-    /// # Panics
+    /// A token is always a terminating element without children.
     ///
-    /// This panics if the number of nodes are too many to fit in a vector on
-    /// your architecture. This corresponds to [`usize::MAX`].
+    /// The length can be given either as the length of the flavor, or as a
+    /// plain integer which is converted into it, see [`IntoLength`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers, if `len` doesn't fit in the length of the flavor, or if
+    /// the end of the token doesn't fit in the index of the tree unless the
+    /// flavor is saturating. Errors with [`Error::UnsupportedLength`] if `len`
+    /// is an integer and the length of the flavor can't be constructed from
+    /// one, such as with a [`Dual`][crate::Dual] index.
     ///
     /// # Examples
     ///
     /// ```
     /// let mut tree = syntree::Builder::new();
     ///
-    /// let c = tree.checkpoint()?;
     /// tree.open("child")?;
-    /// tree.token("lit", 3)?;
+    /// tree.token("lit", 4)?;
     /// tree.close()?;
-    /// tree.close_at(&c, "root")?;
     ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     "root" => {
-    ///         "child" => {
-    ///             ("lit", 3)
-    ///         }
+    ///     "child" => {
+    ///         ("lit", 4)
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn checkpoint(&mut self) -> Result<Checkpoint<F::Pointer>, Error<F::Error>> {
-        let node = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+    pub fn token<L>(&mut self, value: T, len: L) -> BuildResult<F::Pointer, F>
+    where
+        L: IntoLength<F::Length>,
+    {
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.tokens += 1;
+        }
 
-        if let Some(c) = &self.checkpoint {
-            if c.node() == node {
-                return Ok(c.clone());
-            }
+        let len = into_length::<F, L>(len)?;
+        let start = self.implicit_cursor()?;
+
+        if !len.is_empty() {
+            self.cursor = if F::SATURATING {
+                self.cursor.saturating_add_len(len)
+            } else {
+                self.cursor.checked_add_len(len).ok_or(Error::Overflow)?
+            };
+
+            self.tree.span_mut().end = self.cursor;
         }
 
-        let c = Checkpoint::new(node, self.parent);
-        self.checkpoint = Some(c.clone());
-        Ok(c)
+        let id = self.insert(value, Kind::Token, Span::new(start, self.cursor))?;
+        self.sibling = Some(id);
+
+        if !len.is_empty() || self.tree.index_empty() {
+            self.tree.indexes_mut().push(self.cursor, id)?;
+        }
+
+        Ok(id)
     }
 
-    /// Insert a node that wraps from the given checkpointed location.
+    /// Declare a token with the specified `value` whose length is the length
+    /// of `text`.
+    ///
+    /// The length is measured in the [`Flavor::UNIT`] of the tree, which is
+    /// bytes by default. Passing the lexed text directly avoids accidentally
+    /// computing the length of the token from a different string than the
+    /// one which was lexed.
     ///
     /// # Errors
     ///
-    /// The checkpoint being closed *must* be a sibling. Otherwise a
-    /// [`Error::CloseAtError`] will be raised.
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers, or if the length of `text` doesn't fit in the length of the
+    /// flavor. Errors with [`Error::UnsupportedLength`] if the length of the
+    /// flavor can't be constructed from a `usize` at all, which is the case
+    /// for indexes which track more than one offset such as [`Dual`].
     ///
-    /// This might also sporadically error with [`Error::MissingNode`], in case
-    /// a checkpoint is used that was constructed from another tree.
+    /// [`Dual`]: crate::Dual
     ///
     /// # Examples
     ///
     /// ```
+    /// let source = "let ö";
+    ///
     /// let mut tree = syntree::Builder::new();
     ///
-    /// let c = tree.checkpoint()?;
-    /// tree.token("lit", 3)?;
-    /// tree.close_at(&c, "root")?;
+    /// tree.open("let")?;
+    ///
+    /// for (value, text) in [("kw", "let"), ("ws", " "), ("ident", "ö")] {
+    ///     tree.token_str(value, text)?;
+    /// }
+    ///
+    /// tree.close()?;
     ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     "root" => {
-    ///         ("lit", 3)
+    ///     "let" => {
+    ///         ("kw", 3),
+    ///         ("ws", 1),
+    ///         ("ident", 2),
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
+    /// tree.verify_source(source)?;
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
+    pub fn token_str(&mut self, value: T, text: &str) -> BuildResult<F::Pointer, F> {
+        self.token(value, F::UNIT.len(text))
+    }
+
+    /// Insert a token with a custom span.
     ///
-    /// More complex example:
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node identifiers.
+    ///
+    /// # Examples
     ///
     /// ```
-    /// let mut tree = syntree::Builder::new();
+    /// use syntree::Span;
     ///
-    /// let c = tree.checkpoint()?;
+    /// let mut tree = syntree::Builder::new();
     ///
-    /// tree.open("number")?;
-    /// tree.token("lit", 3)?;
+    /// tree.open_with("child", Span::new(0, 10))?;
+    /// tree.token_with("lit", Span::new(2, 4))?;
+    /// tree.token_with("lit2", Span::new(4, 6))?;
     /// tree.close()?;
     ///
-    /// tree.token("whitespace", 1)?;
+    /// let tree = tree.build()?;
     ///
-    /// tree.open("number")?;
-    /// tree.token("lit", 2)?;
-    /// tree.close()?;
+    /// let expected = syntree::tree! {
+    ///     ("child", (0, 10)) => {
+    ///         ("lit", (2, 4)),
+    ///         ("lit2", (4, 6)),
+    ///     }
+    /// };
     ///
-    /// tree.close_at(&c, "root")?;
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// Tokens with spans forces the cursor to be set to the end of the span.
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("child")?;
+    /// tree.token_with("lit", Span::new(2, 4))?;
+    /// tree.token_with("lit2", Span::new(4, 6))?;
+    /// tree.token("lit3", 6)?;
+    /// tree.close()?;
     ///
     /// let tree = tree.build()?;
     ///
     /// let expected = syntree::tree! {
-    ///     "root" => {
-    ///         "number" => {
-    ///             ("lit", 3)
-    ///         },
-    ///         ("whitespace", 1),
-    ///         "number" => {
-    ///             ("lit", 2)
-    ///         },
+    ///     ("child", (0, 6)) => {
+    ///         ("lit", (2, 4)),
+    ///         ("lit2", (4, 6)),
+    ///         ("lit3", (6, 12)),
     ///     }
     /// };
     ///
     /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
+    pub fn token_with(&mut self, value: T, span: Span<F::Index>) -> BuildResult<F::Pointer, F> {
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.tokens += 1;
+        }
+
+        let id = self.insert(value, Kind::Token, span)?;
+
+        self.sibling = Some(id);
+
+        if !span.is_empty() || self.tree.index_empty() {
+            self.tree.indexes_mut().push(span.end, id)?;
+        }
+
+        if self.explicit_spans {
+            return Ok(id);
+        }
+
+        if let Some(parent) = self.parent.and_then(|id| self.tree.get_mut(id)) {
+            parent.span = parent.span.join(&span);
+        }
+
+        self.cursor = span.end;
+        Ok(id)
+    }
+
+    /// Declare a token with the specified `value` and an empty length.
+    ///
+    /// A token is always a terminating element without children.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("child")?;
+    /// tree.token_empty("lit")?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "child" => {
+    ///         "lit"
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn token_empty(&mut self, value: T) -> BuildResult<F::Pointer, F> {
+        self.token(value, F::Length::EMPTY)
+    }
+
+    /// Insert a token without a span, regardless of the index used by the
+    /// flavor.
+    ///
+    /// This is an alias for [`Builder::token_empty`], which reads better in
+    /// parsers that are generic over whether spans are tracked or not.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Empty, EmptyVec, Flavor, Tree, TreeIndex};
+    ///
+    /// syntree::flavor! {
+    ///     struct FlavorEmpty {
+    ///         type Index = Empty;
+    ///         type Indexes = EmptyVec<TreeIndex<Self>>;
+    ///     }
+    /// }
+    ///
+    /// fn parse<F>(tree: &mut Builder<&'static str, F>) -> Result<(), syntree::Error<F::Error, F::Pointer>>
+    /// where
+    ///     F: Flavor,
+    /// {
+    ///     tree.open("root")?;
+    ///     tree.token_usize("lit", 3)?;
+    ///     tree.token_unspanned("eof")?;
+    ///     tree.close()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut tree = Builder::new();
+    /// parse(&mut tree)?;
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3),
+    ///         "eof",
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    ///
+    /// let mut tree = Builder::<_, FlavorEmpty>::new_with();
+    /// parse(&mut tree)?;
+    /// let tree = tree.build()?;
+    ///
+    /// let expected: Tree<_, FlavorEmpty> = syntree::tree_with! {
+    ///     "root" => {
+    ///         "lit",
+    ///         "eof",
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn token_unspanned(&mut self, value: T) -> BuildResult<F::Pointer, F> {
+        self.token_empty(value)
+    }
+
+    /// Declare a token with the specified `value` and a length given as a
+    /// `usize`, which is converted into the length of the flavor.
+    ///
+    /// Every flavor can construct its length from a `usize` through
+    /// [`Length::from_usize`], so this allows for writing parsers which are
+    /// generic over both spanned and unspanned flavors. With an
+    /// [`Empty`][crate::Empty] index the length is discarded.
+    ///
+    /// See [`Builder::token_unspanned`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers, or if `len` doesn't fit in the length of the flavor.
+    /// Errors with [`Error::UnsupportedLength`] if the length of the flavor
+    /// can't be constructed from a `usize` at all, which is the case for
+    /// indexes which track more than one offset such as
+    /// [`Dual`][crate::Dual].
+    pub fn token_usize(&mut self, value: T, len: usize) -> BuildResult<F::Pointer, F> {
+        self.token(value, len)
+    }
+
+    /// Replace the last `n` tokens inserted at the current level of the tree
+    /// with a single token with the specified `value`, whose span covers all of
+    /// them.
+    ///
+    /// This is useful for parsers which are fed a stream of fine-grained
+    /// tokens, such as a `>` which might either be a standalone operator or
+    /// part of a `>>=`, and only know how to combine them once they've seen
+    /// what follows.
+    ///
+    /// The glued token re-uses the identifier of the first token being
+    /// replaced, and the identifiers of the remaining tokens become available
+    /// again. So any checkpoint taken after the first of the glued tokens was
+    /// inserted no longer refers to the same position in the tree, except for
+    /// the most recent checkpoint if it was taken after all of them.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::GlueError`] if `n` is zero, or if the last `n`
+    /// elements inserted into the tree are not all tokens which are siblings of
+    /// each other at the current level of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("assign")?;
+    /// tree.token("ident", 1)?;
+    /// let gt = tree.token(">", 1)?;
+    /// tree.token(">", 1)?;
+    /// tree.token("=", 1)?;
+    ///
+    /// assert_eq!(tree.glue_last(3, ">>=")?, gt);
+    ///
+    /// tree.token("ident", 1)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "assign" => {
+    ///         ("ident", 1),
+    ///         (">>=", 3),
+    ///         ("ident", 1),
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// assert_eq!(tree.nth_token(1).map(|n| n.value()), Some(">>="));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn glue_last(&mut self, n: usize, value: T) -> BuildResult<F::Pointer, F> {
+        let len = self.tree.len();
+        let first = len
+            .checked_sub(n)
+            .filter(|_| n > 0)
+            .ok_or(Error::GlueError)?;
+
+        let mut current = self.sibling;
+        let mut end = None;
+        let mut start = None;
+
+        // Walk backwards through the siblings at the current level, which must
+        // be tokens occupying the last `n` slots of the tree.
+        for expected in (first..len).rev() {
+            let id = current.ok_or(Error::GlueError)?;
+
+            if id.get() != expected {
+                return Err(Error::GlueError);
+            }
+
+            let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+
+            if links.kind != Kind::Token {
+                return Err(Error::GlueError);
+            }
+
+            end.get_or_insert(links.span.end);
+            start = Some((id, links.span.start));
+            current = links.prev;
+        }
+
+        let (Some((id, start)), Some(end)) = (start, end) else {
+            return Err(Error::GlueError);
+        };
+
+        let span = Span::new(start, end);
+        self.tree.truncate(first + 1);
+
+        let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+        links.data.set(value);
+        links.span = span;
+        links.next = None;
+
+        if let Some(parent) = self.parent.and_then(|p| self.tree.get_mut(p)) {
+            parent.last = Some(id);
+        } else {
+            *self.tree.links_mut().1 = Some(id);
+        }
+
+        // The index entry of the first token, if any, is the last one
+        // remaining and needs to be replaced to cover the glued span.
+        let indexes = self.tree.indexes_mut();
+
+        if let Some(last) = indexes.len().checked_sub(1) {
+            if matches!(indexes.get(last), Some((_, last_id)) if last_id == id) {
+                indexes.truncate(last);
+            }
+        }
+
+        if !span.is_empty() || self.tree.index_empty() {
+            self.tree.indexes_mut().push(span.end, id)?;
+        }
+
+        if let Some(c) = &self.checkpoint {
+            let node = c.node().get();
+
+            if node == len {
+                let next = F::Pointer::new(first + 1).ok_or(Error::Overflow)?;
+                c.set(next, self.parent);
+            } else if node > first {
+                self.checkpoint = None;
+            }
+        }
+
+        self.sibling = Some(id);
+        Ok(id)
+    }
+
+    /// Get a checkpoint corresponding to the current position in the tree.
+    ///
+    /// # Mixing checkpoints
+    ///
+    /// Note that using checkpoints from a different tree doesn't have a
+    /// well-specified behavior - it might seemingly work or it might raise an
+    /// error during closing such as [`Error::MissingNode`].
+    ///
+    /// The following *is not* well-defined, here we're using a checkpoint for a
+    /// different tree but it "just happens" to work because both trees have
+    /// identical internal topologies:
+    ///
+    /// ```
+    /// use syntree::{Builder, Error};
+    ///
+    /// let mut a = Builder::new();
+    /// let mut b = Builder::new();
+    ///
+    /// let c = b.checkpoint()?;
+    ///
+    /// a.open("child")?;
+    /// a.close()?;
+    ///
+    /// b.open("child")?;
+    /// b.close()?;
+    ///
+    /// // Checkpoint use from different tree.
+    /// a.close_at(&c, "root")?;
+    ///
+    /// let unexpected = syntree::tree! {
+    ///     "root" => {
+    ///         "child"
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(a.build()?, unexpected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the number of nodes are too many to fit in a vector on
+    /// your architecture. This corresponds to [`usize::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.open("child")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn checkpoint(&mut self) -> BuildResult<Checkpoint<F::Pointer>, F> {
+        let node = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+        Ok(self.checkpoint_at(node))
+    }
+
+    /// Adopt a checkpoint taken from the builder this builder was cloned
+    /// from, so that it can be used with this builder.
+    ///
+    /// Checkpoints are shared handles which are updated by the builder when
+    /// they are closed with [`Builder::close_at`], so that checkpoints taken
+    /// at the same position can be nested. This means that a checkpoint taken
+    /// before a builder was cloned is still shared by the original and the
+    /// clone, and using it with one of them affects the other. Adopting it
+    /// constructs a checkpoint at the same position which belongs to this
+    /// builder.
+    ///
+    /// Adopting checkpoints taken at the same position returns checkpoints
+    /// which are also shared with each other, like the originals were.
+    ///
+    /// # Examples
+    ///
+    /// Speculatively parsing with a clone of the builder:
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.token("lit", 3)?;
+    ///
+    /// let mut attempt = tree.clone();
+    /// let attempt_c = attempt.adopt(&c);
+    /// attempt.token("colon", 1)?;
+    /// attempt.close_at(&attempt_c, "label")?;
+    ///
+    /// // The attempt is discarded, which doesn't affect the checkpoint of the
+    /// // original builder.
+    /// drop(attempt);
+    ///
+    /// tree.close_at(&c, "number")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "number" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn adopt(&mut self, c: &Checkpoint<F::Pointer>) -> Checkpoint<F::Pointer> {
+        if let Some(own) = &self.checkpoint {
+            if own.get() == c.get() {
+                return own.clone();
+            }
+        }
+
+        c.detach()
+    }
+
+    /// Construct a checkpoint for the next node to be inserted, which is
+    /// `node`.
+    fn checkpoint_at(&mut self, node: F::Pointer) -> Checkpoint<F::Pointer> {
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.checkpoints += 1;
+        }
+
+        if let Some(c) = &self.checkpoint {
+            if c.node() == node {
+                return c.clone();
+            }
+        }
+
+        let c = Checkpoint::new(node, self.parent);
+        self.checkpoint = Some(c.clone());
+        c
+    }
+
+    /// Rebase the checkpointed node `id` onto the outermost node which has
+    /// since wrapped it, which is the one that is a child of `parent`.
+    ///
+    /// Wrapping nodes through another checkpoint, [`Builder::close_at_fixed`]
+    /// or [`Builder::open_above`] doesn't update the checkpoints referencing
+    /// the wrapped node.
+    fn rebase(&self, id: F::Pointer, parent: Option<F::Pointer>) -> BuildResult<F::Pointer, F> {
+        let mut node = self.tree.get(id).ok_or(Error::MissingNode(id))?;
+
+        while node.parent().map(|n| n.id()) != parent {
+            node = node.parent().ok_or(Error::CloseAtError)?;
+        }
+
+        Ok(node.id())
+    }
+
+    /// Insert a node that wraps from the given checkpointed location.
+    ///
+    /// # Errors
+    ///
+    /// The checkpoint being closed *must* be a sibling. Otherwise a
+    /// [`Error::CloseAtError`] will be raised.
+    ///
+    /// This might also sporadically error with [`Error::MissingNode`], in case
+    /// a checkpoint is used that was constructed from another tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.token("lit", 3)?;
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// More complex example:
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// tree.token("whitespace", 1)?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 2)?;
+    /// tree.close()?;
+    ///
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 3)
+    ///         },
+    ///         ("whitespace", 1),
+    ///         "number" => {
+    ///             ("lit", 2)
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// Adding a token after a checkpoint:
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.open("child")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    /// tree.close_at(&c, "root")?;
+    /// tree.token("sibling", 3)?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let child = tree.node_with_range(0..3).ok_or("missing at 0..3")?;
+    /// assert_eq!(child.value(), "child");
+    ///
+    /// let lit = tree.first().and_then(|n| n.first()).and_then(|n| n.first()).ok_or("expected lit")?;
+    /// assert_eq!(lit.value(), "lit");
+    ///
+    /// let root = lit.ancestors().last().ok_or("missing root")?;
+    /// assert_eq!(root.value(), "root");
+    /// assert_eq!(root.parent(), None);
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     },
+    ///     ("sibling", 3)
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn close_at(&mut self, c: &Checkpoint<F::Pointer>, data: T) -> BuildResult<F::Pointer, F> {
+        let (id, parent) = c.get();
+
+        if parent != self.parent {
+            return Err(Error::CloseAtError);
+        }
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.close_ats += 1;
+        }
+
+        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+
+        if self.tree.get(id).is_none() {
+            let new_id = self.insert(data, Kind::Node, Span::point(self.implicit_cursor()?))?;
+
+            if new_id != id {
+                return Err(Error::MissingNode(new_id));
+            }
+
+            self.sibling = Some(new_id);
+            return Ok(new_id);
+        }
+
+        let id = self.rebase(id, parent)?;
+        let parent = self.wrap(id, new_id, data, None)?;
+        c.set(new_id, parent);
+        Ok(new_id)
+    }
+
+    /// Get a checkpoint corresponding to the current position in the tree
+    /// which doesn't use interior mutability.
+    ///
+    /// See [`FixedCheckpoint`] for documentation.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn is_send_sync<T: Send + Sync>(_: &T) {}
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let a = tree.checkpoint_fixed()?;
+    /// let b = tree.checkpoint_fixed()?;
+    /// is_send_sync(&a);
+    ///
+    /// tree.token("lit", 3)?;
+    /// tree.close_at_fixed(b, "inner")?;
+    /// tree.token("ws", 1)?;
+    /// tree.close_at_fixed(a, "outer")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "outer" => {
+    ///         "inner" => {
+    ///             ("lit", 3)
+    ///         },
+    ///         ("ws", 1)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn checkpoint_fixed(&self) -> BuildResult<FixedCheckpoint<F::Pointer>, F> {
+        F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+        Ok(FixedCheckpoint::new(self.sibling, self.parent))
+    }
+
+    /// Insert a node that wraps from the given fixed checkpoint.
+    ///
+    /// This behaves like [`Builder::close_at`], except that the node to wrap
+    /// is the one following the sibling which preceded the checkpoint, or
+    /// rather the outermost node which has since wrapped that sibling.
+    ///
+    /// See [`Builder::checkpoint_fixed`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// The checkpoint being closed *must* be a sibling. Otherwise a
+    /// [`Error::CloseAtError`] will be raised.
+    ///
+    /// This might also sporadically error with [`Error::MissingNode`], in case
+    /// a checkpoint is used that was constructed from another tree.
+    pub fn close_at_fixed(
+        &mut self,
+        c: FixedCheckpoint<F::Pointer>,
+        data: T,
+    ) -> BuildResult<F::Pointer, F> {
+        let (prev, parent) = c.get();
+
+        if parent != self.parent {
+            return Err(Error::CloseAtError);
+        }
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.close_ats += 1;
+        }
+
+        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+
+        let id = match prev {
+            Some(prev) => {
+                let prev = self.rebase(prev, parent)?;
+                self.tree.get(prev).and_then(|n| n.next())
+            }
+            None => match parent {
+                Some(parent) => self
+                    .tree
+                    .get(parent)
+                    .ok_or(Error::MissingNode(parent))?
+                    .first(),
+                None => self.tree.first(),
+            },
+        };
+
+        let Some(id) = id.map(|n| n.id()) else {
+            let new_id = self.insert(data, Kind::Node, Span::point(self.implicit_cursor()?))?;
+            self.sibling = Some(new_id);
+            return Ok(new_id);
+        };
+
+        self.wrap(id, new_id, data, None)?;
+        Ok(new_id)
+    }
+
+    /// Insert a node with a custom length.
+    ///
+    /// # Errors
+    ///
+    /// The checkpoint being closed *must* be a sibling. Otherwise a
+    /// [`Error::CloseAtError`] will be raised.
+    ///
+    /// This might also sporadically error with [`Error::MissingNode`], in case
+    /// a checkpoint is used that was constructed from another tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.token_with("lit", Span::new(1, 3))?;
+    /// tree.close_at_with(&c, "custom", Span::new(0, 6))?;
+    /// tree.token_with("lit2", Span::new(6, 8))?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// assert_eq!(root.value(), "custom");
+    /// assert_eq!(root.span().start, 0);
+    /// assert_eq!(root.span().end, 6);
+    ///
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn close_at_with(
+        &mut self,
+        c: &Checkpoint<F::Pointer>,
+        data: T,
+        span: Span<F::Index>,
+    ) -> BuildResult<F::Pointer, F> {
+        let (id, parent) = c.get();
+
+        if parent != self.parent {
+            return Err(Error::CloseAtError);
+        }
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.close_ats += 1;
+        }
+
+        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+
+        if self.tree.get(id).is_none() {
+            let new_id = self.insert(data, Kind::Node, span)?;
+
+            if new_id != id {
+                return Err(Error::MissingNode(new_id));
+            }
+
+            self.sibling = Some(new_id);
+            return Ok(new_id);
+        }
+
+        let id = self.rebase(id, parent)?;
+        let parent = self.wrap(id, new_id, data, Some(span))?;
+        c.set(new_id, parent);
+        Ok(new_id)
+    }
+
+    /// Insert a node that wraps everything after the first `skip` siblings
+    /// recorded since the given checkpoint.
+    ///
+    /// This is useful when a checkpoint has been taken before something which
+    /// should not be part of the wrapped node, such as leading trivia, since it
+    /// avoids having to take another checkpoint after it just in case.
+    ///
+    /// If exactly `skip` siblings have been added since the checkpoint, an
+    /// empty node is inserted at the current cursor. Calling this with a
+    /// `skip` of zero is the same as calling [`Builder::close_at`].
+    ///
+    /// # Errors
+    ///
+    /// The checkpoint being closed *must* be a sibling and there must be at
+    /// least `skip` nodes after it. Otherwise a [`Error::CloseAtError`] will be
+    /// raised.
+    ///
+    /// This might also sporadically error with [`Error::MissingNode`], in case
+    /// a checkpoint is used that was constructed from another tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.token("ws", 1)?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// tree.token("plus", 1)?;
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 2)?;
+    /// tree.close()?;
+    ///
+    /// tree.close_at_from(&c, 1, "binary")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     ("ws", 1),
+    ///     "binary" => {
+    ///         "number" => {
+    ///             ("lit", 3)
+    ///         },
+    ///         ("plus", 1),
+    ///         "number" => {
+    ///             ("lit", 2)
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// Skipping more siblings than there are is an error:
+    ///
+    /// ```
+    /// use syntree::Error;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.token("ws", 1)?;
+    ///
+    /// assert_eq!(tree.close_at_from(&c, 2, "binary"), Err(Error::CloseAtError));
+    ///
+    /// tree.close_at_from(&c, 1, "empty")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     ("ws", 1),
+    ///     ("empty", (1, 1)) => {}
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn close_at_from(
+        &mut self,
+        c: &Checkpoint<F::Pointer>,
+        skip: usize,
+        data: T,
+    ) -> BuildResult<F::Pointer, F> {
+        if skip == 0 {
+            return self.close_at(c, data);
+        }
+
+        let (id, parent) = c.get();
+
+        if parent != self.parent {
+            return Err(Error::CloseAtError);
+        }
+
+        let mut start = match self.tree.get(id) {
+            Some(..) => Some(self.rebase(id, parent)?),
+            None => None,
+        };
+
+        for _ in 0..skip {
+            let links = start
+                .and_then(|id| self.tree.get_mut(id))
+                .ok_or(Error::CloseAtError)?;
+            start = links.next;
+        }
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.close_ats += 1;
+        }
+
+        let Some(start) = start else {
+            let new_id = self.insert(data, Kind::Node, Span::point(self.implicit_cursor()?))?;
+            self.sibling = Some(new_id);
+            return Ok(new_id);
+        };
+
+        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+        self.wrap(start, new_id, data, None)?;
+        Ok(new_id)
+    }
+
+    /// Insert a node that wraps the node identified by `id` and all of its
+    /// subsequent siblings.
+    ///
+    /// This is like [`Builder::close_at`], except that the first node to wrap
+    /// is identified by its id rather than a checkpoint. This is useful for
+    /// parsers which only discover after the fact that an earlier node should
+    /// be wrapped, such as operator precedence parsers which record the id of
+    /// the left hand side of an expression.
+    ///
+    /// # Errors
+    ///
+    /// The node identified by `id` *must* be a child of the currently open
+    /// node. Otherwise a [`Error::CloseAtError`] will be raised.
+    ///
+    /// Errors with [`Error::MissingNode`] if `id` doesn't identify a node in
+    /// the tree being built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let lhs = tree.token("lit", 1)?;
+    /// tree.token("plus", 1)?;
+    /// let rhs = tree.token("lit", 1)?;
+    /// tree.token("star", 1)?;
+    /// tree.token("lit", 1)?;
+    ///
+    /// tree.open_above(rhs, "mul")?;
+    /// tree.open_above(lhs, "add")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "add" => {
+    ///         ("lit", 1),
+    ///         ("plus", 1),
+    ///         "mul" => {
+    ///             ("lit", 1),
+    ///             ("star", 1),
+    ///             ("lit", 1),
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// The node must be a child of the currently open node:
+    ///
+    /// ```
+    /// use syntree::Error;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("number")?;
+    /// let lit = tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// assert_eq!(tree.open_above(lit, "wrapped"), Err(Error::CloseAtError));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn open_above(&mut self, id: F::Pointer, data: T) -> BuildResult<F::Pointer, F> {
+        let node = self.tree.get(id).ok_or(Error::MissingNode(id))?;
+
+        if node.parent().map(|n| n.id()) != self.parent {
+            return Err(Error::CloseAtError);
+        }
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.close_ats += 1;
+        }
+
+        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+        self.wrap(id, new_id, data, None)?;
+        Ok(new_id)
+    }
+
+    /// Access the metrics collected by the builder so far.
+    ///
+    /// This requires the `instrument` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    /// tree.token("ws", 1)?;
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let metrics = tree.metrics();
+    /// assert_eq!(metrics.opens, 1);
+    /// assert_eq!(metrics.closes, 1);
+    /// assert_eq!(metrics.tokens, 2);
+    /// assert_eq!(metrics.checkpoints, 1);
+    /// assert_eq!(metrics.close_ats, 1);
+    /// assert_eq!(metrics.restructurings, 1);
+    /// assert_eq!(metrics.restructured_nodes, 1);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[cfg(feature = "instrument")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "instrument")))]
+    #[must_use]
+    pub const fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Build a [Tree] from the current state of the builder.
+    ///
+    /// # Errors
+    ///
+    /// This requires the stack in the builder to be empty. Otherwise a
+    /// [`Error::BuildError`] will be raised.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("child")?;
+    /// tree.token("number", 3)?;
+    /// tree.close()?;
+    /// tree.open("child")?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "child" => {
+    ///         ("number", 3)
+    ///     },
+    ///     "child" => {},
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// If a tree is unbalanced during construction, building will fail with an error:
+    ///
+    /// ```
+    /// use syntree::{Error, Span, Builder};
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    /// tree.close()?;
+    ///
+    /// tree.open("number")?;
+    ///
+    /// // "number" is left open.
+    /// assert!(matches!(tree.build(), Err(Error::BuildError)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn build(mut self) -> BuildResult<Tree<T, F>, F> {
+        if self.parent.is_some() {
+            return Err(Error::BuildError);
+        }
+
+        if self.tree.explicit_spans() {
+            self.tree.indexes_mut().sort();
+        }
+
+        Ok(self.tree)
+    }
+
+    /// Take every node built so far out of the builder as a finished tree,
+    /// leaving the builder empty but positioned at its current cursor.
+    ///
+    /// This allows for parsing extremely large inputs with bounded memory,
+    /// since completed top-level nodes can be handed off as they are built
+    /// instead of growing the builder with the whole input. Spans are not
+    /// reset, so each flushed tree starts where the previous one ended as
+    /// reported by [`Tree::base`]. Whether empty tokens are indexed is
+    /// preserved.
+    ///
+    /// Any outstanding checkpoints or node identifiers refer to the flushed
+    /// tree and must not be used with the builder afterwards.
+    ///
+    /// # Errors
     ///
-    /// Adding a token after a checkpoint:
+    /// Errors with [`Error::BuildError`] if a node is still open, since it
+    /// can't be moved out of the builder before it's complete.
+    ///
+    /// # Examples
     ///
     /// ```
+    /// use syntree::Span;
+    ///
     /// let mut tree = syntree::Builder::new();
     ///
-    /// let c = tree.checkpoint()?;
-    /// tree.open("child")?;
-    /// tree.token("lit", 3)?;
+    /// tree.open("record")?;
+    /// tree.token("field", 3)?;
     /// tree.close()?;
-    /// tree.close_at(&c, "root")?;
-    /// tree.token("sibling", 3)?;
-    ///
-    /// let tree = tree.build()?;
-    ///
-    /// let child = tree.node_with_range(0..3).ok_or("missing at 0..3")?;
-    /// assert_eq!(child.value(), "child");
+    /// let first = tree.flush()?;
     ///
-    /// let lit = tree.first().and_then(|n| n.first()).and_then(|n| n.first()).ok_or("expected lit")?;
-    /// assert_eq!(lit.value(), "lit");
+    /// tree.open("record")?;
+    /// tree.token("field", 2)?;
+    /// tree.close()?;
+    /// let second = tree.flush()?;
     ///
-    /// let root = lit.ancestors().last().ok_or("missing root")?;
-    /// assert_eq!(root.value(), "root");
-    /// assert_eq!(root.parent(), None);
+    /// let rest = tree.build()?;
+    /// assert!(rest.is_empty());
+    /// assert_eq!(rest.base(), 5);
     ///
     /// let expected = syntree::tree! {
-    ///     "root" => {
-    ///         "child" => {
-    ///             ("lit", 3)
-    ///         }
-    ///     },
-    ///     ("sibling", 3)
+    ///     "record" => {
+    ///         ("field", 3)
+    ///     }
     /// };
     ///
-    /// assert_eq!(tree, expected);
+    /// assert_eq!(first, expected);
+    ///
+    /// let record = second.first().ok_or("missing record")?;
+    /// assert_eq!(record.value(), "record");
+    /// assert_eq!(record.span(), Span::new(3, 5));
+    /// assert_eq!(second.span(), Span::new(3, 5));
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn close_at(
-        &mut self,
-        c: &Checkpoint<F::Pointer>,
-        data: T,
-    ) -> Result<F::Pointer, Error<F::Error>> {
-        let (id, parent) = c.get();
+    ///
+    /// Flushing while a node is open fails:
+    ///
+    /// ```
+    /// use syntree::Error;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("record")?;
+    /// assert_eq!(tree.flush(), Err(Error::BuildError));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn flush(&mut self) -> BuildResult<Tree<T, F>, F> {
+        if self.parent.is_some() {
+            return Err(Error::BuildError);
+        }
 
-        if parent != self.parent {
-            return Err(Error::CloseAtError);
+        let mut tree = Tree::new_with();
+        tree.set_index_empty(self.tree.index_empty());
+        tree.set_source_id(self.tree.source_id());
+        *tree.span_mut() = Span::point(self.cursor);
+
+        if self.explicit_spans {
+            tree.set_explicit_spans();
         }
 
-        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+        self.checkpoint = None;
+        self.sibling = None;
 
-        let Some(links) = self.tree.get_mut(id) else {
-            let new_id = self.insert(data, Span::point(self.cursor))?;
+        let mut tree = core::mem::replace(&mut self.tree, tree);
 
-            if new_id != id {
-                return Err(Error::MissingNode(new_id.get()));
+        if tree.explicit_spans() {
+            tree.indexes_mut().sort();
+        }
+
+        Ok(tree)
+    }
+
+    /// Access the tree being built.
+    pub(crate) const fn tree(&self) -> &Tree<T, F> {
+        &self.tree
+    }
+
+    /// Get the tree being built mutably.
+    pub(crate) fn tree_mut(&mut self) -> &mut Tree<T, F> {
+        &mut self.tree
+    }
+
+    /// Get the innermost node which is currently open.
+    pub(crate) const fn parent(&self) -> Option<F::Pointer> {
+        self.parent
+    }
+
+    /// Wrap the node `id` and all of its subsequent siblings in a new node
+    /// with the identifier `new_id`, returning the parent of the new node.
+    ///
+    /// If `span` is `None`, the span of the new node covers all the wrapped
+    /// nodes.
+    fn wrap(
+        &mut self,
+        id: F::Pointer,
+        new_id: F::Pointer,
+        data: T,
+        span: Option<Span<F::Index>>,
+    ) -> BuildResult<Option<F::Pointer>, F> {
+        // A checkpoint taken for the next node to be inserted would otherwise
+        // refer to the wrapping node, so it's moved past it. Only the most
+        // recent checkpoint can refer to the next node. If it was taken inside
+        // of a node which has since been closed it can't be closed any longer,
+        // so it's only forgotten to avoid handing it out again.
+        if let Some(c) = &self.checkpoint {
+            let (node, parent) = c.get();
+
+            if node == new_id {
+                if parent == self.parent {
+                    let next = F::Pointer::new(new_id.get() + 1).ok_or(Error::Overflow)?;
+                    c.set(next, parent);
+                } else {
+                    self.checkpoint = None;
+                }
             }
+        }
 
-            self.sibling = Some(new_id);
-            return Ok(new_id);
-        };
+        let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
 
         let parent = links.parent.replace(new_id);
         let prev = links.prev.take();
@@ -762,20 +2509,22 @@ where
         // Restructuring is necessary to calculate the full span of the newly
         // inserted node and update parent references to point to the newly
         // inserted node.
-        let (last, span) = if let Some(next) = links.next {
-            let span = links.span;
-            let (last, end) = restructure_close_at(&mut self.tree, new_id, next)?;
-            (last, Span::new(span.start, end))
+        let (last, wrapped) = if let Some(next) = links.next {
+            let start = links.span.start;
+            let (last, end) = self.restructure_close_at(new_id, next)?;
+            (last, Span::new(start, end))
         } else {
             (id, links.span)
         };
 
+        let span = span.unwrap_or(wrapped);
+
         if let Some(parent) = parent.and_then(|id| self.tree.get_mut(id)) {
             if parent.first == Some(id) {
                 parent.first = Some(new_id);
             }
 
-            if parent.last == Some(id) {
+            if parent.last == Some(last) {
                 parent.last = Some(new_id);
             }
         }
@@ -785,206 +2534,117 @@ where
         }
 
         // If we're replacing the first node of the tree, the newly inserted
-        // node should be set as the first node.
-        let (first, _) = self.tree.links_mut();
+        // node should be set as the first node. Similarly, wrapping top-level
+        // nodes always includes the last node of the tree.
+        let (first, tree_last) = self.tree.links_mut();
 
         if *first == Some(id) {
             *first = Some(new_id);
         }
 
+        if parent.is_none() {
+            *tree_last = Some(new_id);
+        }
+
+        let flags = self.wrapped_flags(id);
+
         // Do necessary accounting.
         self.tree.push(Links {
             data: Cell::new(data),
+            kind: Kind::Node,
             span,
             prev,
             parent,
             next: None,
             first: Some(id),
             last: Some(last),
+            flags,
         })?;
 
         self.sibling = Some(new_id);
-        c.set(new_id, parent);
-        Ok(new_id)
+        Ok(parent)
     }
 
-    /// Insert a node with a custom length.
-    ///
-    /// # Errors
-    ///
-    /// The checkpoint being closed *must* be a sibling. Otherwise a
-    /// [`Error::CloseAtError`] will be raised.
-    ///
-    /// This might also sporadically error with [`Error::MissingNode`], in case
-    /// a checkpoint is used that was constructed from another tree.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use syntree::Span;
-    ///
-    /// let mut tree = syntree::Builder::new();
-    ///
-    /// let c = tree.checkpoint()?;
-    /// tree.token_with("lit", Span::new(1, 3))?;
-    /// tree.close_at_with(&c, "custom", Span::new(0, 6))?;
-    /// tree.token_with("lit2", Span::new(6, 8))?;
-    ///
-    /// let tree = tree.build()?;
-    ///
-    /// let root = tree.first().ok_or("missing root")?;
-    /// assert_eq!(root.value(), "custom");
-    /// assert_eq!(root.span().start, 0);
-    /// assert_eq!(root.span().end, 6);
-    ///
-    /// # Ok::<_, Box<dyn core::error::Error>>(())
-    /// ```
-    pub fn close_at_with(
-        &mut self,
-        c: &Checkpoint<F::Pointer>,
-        data: T,
-        span: Span<F::Index>,
-    ) -> Result<F::Pointer, Error<F::Error>> {
-        let (id, parent) = c.get();
-
-        if parent != self.parent {
-            return Err(Error::CloseAtError);
-        }
-
-        let new_id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
-
-        let Some(links) = self.tree.get_mut(id) else {
-            let new_id = self.insert(data, span)?;
-
-            if new_id != id {
-                return Err(Error::MissingNode(new_id.get()));
-            }
-
-            self.sibling = Some(new_id);
-            return Ok(new_id);
-        };
-
-        let parent = links.parent.replace(new_id);
-        let prev = links.prev.take();
-
-        // Restructuring is necessary to calculate the full span of the newly
-        // inserted node and update parent references to point to the newly
-        // inserted node.
-        let last = if let Some(next) = links.next {
-            let (last, _) = restructure_close_at(&mut self.tree, new_id, next)?;
-            last
-        } else {
-            id
+    /// Calculate the flags propagated to a node which wraps `id` and all of its
+    /// subsequent siblings.
+    fn wrapped_flags(&self, id: F::Pointer) -> F::Flags {
+        let Some((from, to)) = self.propagate else {
+            return <F::Flags as Flags>::EMPTY;
         };
 
-        if let Some(parent) = parent.and_then(|id| self.tree.get_mut(id)) {
-            if parent.first == Some(id) {
-                parent.first = Some(new_id);
-            }
+        let mask = from.union(to);
+        let mut current = self.tree.get(id);
 
-            if parent.last == Some(id) {
-                parent.last = Some(new_id);
+        while let Some(node) = current {
+            if node.flags().intersects(mask) {
+                return to;
             }
-        }
 
-        if let Some(prev) = prev.and_then(|id| self.tree.get_mut(id)) {
-            prev.next = Some(new_id);
+            current = node.next();
         }
 
-        // If we're replacing the first node of the tree, the newly inserted
-        // node should be set as the first node.
-        let (first, _) = self.tree.links_mut();
+        <F::Flags as Flags>::EMPTY
+    }
 
-        if *first == Some(id) {
-            *first = Some(new_id);
+    /// Restructure the tree after inserting a node at a checkpoint, see
+    /// [`restructure_close_at`].
+    fn restructure_close_at(
+        &mut self,
+        parent_id: F::Pointer,
+        next: F::Pointer,
+    ) -> BuildResult<(F::Pointer, F::Index), F> {
+        #[cfg(feature = "instrument")]
+        let start = std::time::Instant::now();
+
+        let (last, end, _count) = restructure_close_at(&mut self.tree, parent_id, next)?;
+
+        #[cfg(feature = "instrument")]
+        {
+            self.metrics.restructurings += 1;
+            self.metrics.restructured_nodes += _count;
+            self.metrics.restructure_time += start.elapsed();
         }
 
-        // Do necessary accounting.
-        self.tree.push(Links {
-            data: Cell::new(data),
-            span,
-            prev,
-            parent,
-            next: None,
-            first: Some(id),
-            last: Some(last),
-        })?;
-
-        self.sibling = Some(new_id);
-        c.set(new_id, parent);
-        Ok(new_id)
+        Ok((last, end))
     }
 
-    /// Build a [Tree] from the current state of the builder.
-    ///
-    /// # Errors
-    ///
-    /// This requires the stack in the builder to be empty. Otherwise a
-    /// [`Error::BuildError`] will be raised.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// let mut tree = syntree::Builder::new();
-    ///
-    /// tree.open("child")?;
-    /// tree.token("number", 3)?;
-    /// tree.close()?;
-    /// tree.open("child")?;
-    /// tree.close()?;
-    ///
-    /// let tree = tree.build()?;
-    ///
-    /// let expected = syntree::tree! {
-    ///     "child" => {
-    ///         ("number", 3)
-    ///     },
-    ///     "child" => {},
-    /// };
-    ///
-    /// assert_eq!(tree, expected);
-    /// # Ok::<_, Box<dyn core::error::Error>>(())
-    /// ```
-    ///
-    /// If a tree is unbalanced during construction, building will fail with an error:
-    ///
-    /// ```
-    /// use syntree::{Error, Span, Builder};
-    ///
-    /// let mut tree = syntree::Builder::new();
-    ///
-    /// tree.open("number")?;
-    /// tree.token("lit", 3)?;
-    /// tree.close()?;
-    ///
-    /// tree.open("number")?;
-    ///
-    /// // "number" is left open.
-    /// assert!(matches!(tree.build(), Err(Error::BuildError)));
-    /// # Ok::<_, Box<dyn core::error::Error>>(())
-    /// ```
-    pub fn build(self) -> Result<Tree<T, F>, Error<F::Error>> {
-        if self.parent.is_some() {
-            return Err(Error::BuildError);
+    /// Get the cursor to derive a span from, unless spans must be specified
+    /// explicitly.
+    fn implicit_cursor(&self) -> BuildResult<F::Index, F> {
+        if self.explicit_spans {
+            return Err(Error::ImplicitSpan);
         }
 
-        Ok(self.tree)
+        Ok(self.cursor)
     }
 
     /// Insert a new node.
-    fn insert(&mut self, data: T, span: Span<F::Index>) -> Result<F::Pointer, Error<F::Error>> {
+    fn insert(&mut self, data: T, kind: Kind, span: Span<F::Index>) -> BuildResult<F::Pointer, F> {
         let new = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
 
+        if self.explicit_spans {
+            let is_empty = self.tree.is_empty();
+            let tree_span = self.tree.span_mut();
+
+            *tree_span = if is_empty {
+                span
+            } else {
+                tree_span.join(&span)
+            };
+        }
+
         let prev = self.sibling.take();
 
         self.tree.push(Links {
             data: Cell::new(data),
+            kind,
             span,
             parent: self.parent,
             prev,
             next: None,
             first: None,
             last: None,
+            flags: <F::Flags as Flags>::EMPTY,
         })?;
 
         if let Some(id) = self.parent {
@@ -994,7 +2654,10 @@ where
                 }
 
                 node.last = Some(new);
-                node.span.end = span.end;
+
+                if !self.explicit_spans {
+                    node.span.end = span.end;
+                }
             }
         } else {
             let (first, last) = self.tree.links_mut();
@@ -1014,20 +2677,66 @@ where
     }
 }
 
+impl<T, F> Builder<T, F>
+where
+    T: Copy,
+    F: Flavor<Width = usize>,
+{
+    /// Get a checkpoint corresponding to the current position in the tree,
+    /// without the possibility of an error.
+    ///
+    /// This is only available for flavors with a `usize` width such as
+    /// [`FlavorDefault`], since their identifiers can refer to any node which
+    /// fits in memory. It's otherwise identical to [`Builder::checkpoint`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let c = tree.checkpoint_infallible();
+    /// tree.token("lit", 3)?;
+    /// tree.close_at(&c, "root")?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn checkpoint_infallible(&mut self) -> Checkpoint<F::Pointer> {
+        // SAFETY: A `usize` pointer can represent any value except
+        // `usize::MAX`, and storage can't hold that many nodes since every
+        // node takes up more than a byte.
+        let node = unsafe { F::Pointer::new_unchecked(self.tree.len()) };
+        self.checkpoint_at(node)
+    }
+}
+
 impl<T, F> Clone for Builder<T, F>
 where
     T: Copy,
     F: Flavor<Indexes: Clone, Width: Width<Pointer: Clone>>,
-    F::Storage<Links<T, F::Index, F::Pointer>>: Clone,
+    F::Storage<LinksOf<T, F>>: Clone,
 {
     #[inline]
     fn clone(&self) -> Self {
         Self {
             tree: self.tree.clone(),
             parent: self.parent,
-            checkpoint: self.checkpoint.clone(),
+            checkpoint: self.checkpoint.as_ref().map(Checkpoint::detach),
             sibling: self.sibling,
             cursor: self.cursor,
+            propagate: self.propagate,
+            explicit_spans: self.explicit_spans,
+            #[cfg(feature = "instrument")]
+            metrics: self.metrics,
         }
     }
 }
@@ -1043,30 +2752,45 @@ where
     }
 }
 
+/// Convert `len` into the length of the flavor `F`.
+#[inline]
+fn into_length<F, L>(len: L) -> BuildResult<F::Length, F>
+where
+    F: Flavor,
+    L: IntoLength<F::Length>,
+{
+    if let Some(len) = len.into_length() {
+        return Ok(len);
+    }
+
+    // Every length which can be constructed from a `usize` can represent an
+    // empty length, so if that fails no `usize` is supported.
+    if F::Length::from_usize(0).is_none() {
+        return Err(Error::UnsupportedLength);
+    }
+
+    Err(Error::Overflow)
+}
+
 // Adjust span to encapsulate all children and check that we just inserted the
 // checkpointed node in the right location which should be the tail sibling of
 // the replaced node.
-#[allow(clippy::type_complexity)]
 fn restructure_close_at<T, F>(
     tree: &mut Tree<T, F>,
     parent_id: F::Pointer,
     next: F::Pointer,
-) -> Result<(F::Pointer, F::Index), Error<F::Error>>
+) -> BuildResult<(F::Pointer, F::Index, usize), F>
 where
     T: Copy,
     F: Flavor,
 {
-    let mut links = tree
-        .get_mut(next)
-        .ok_or_else(|| Error::MissingNode(next.get()))?;
-    let mut last = (next, links.span.end);
+    let mut links = tree.get_mut(next).ok_or_else(|| Error::MissingNode(next))?;
+    let mut last = (next, links.span.end, 1);
     links.parent = Some(parent_id);
 
     while let Some(next) = links.next {
-        links = tree
-            .get_mut(next)
-            .ok_or_else(|| Error::MissingNode(next.get()))?;
-        last = (next, links.span.end);
+        links = tree.get_mut(next).ok_or_else(|| Error::MissingNode(next))?;
+        last = (next, links.span.end, last.2 + 1);
         links.parent = Some(parent_id);
     }
 
@@ -8,6 +8,11 @@ use alloc::rc::Rc;
 /// This can be used as a checkpoint in [`Builder::close_at`], and a checkpoint
 /// can be fetched up front from [`Builder::checkpoint`].
 ///
+/// Any number of checkpoints can be held at the same time, and they remain
+/// valid as the tree is restructured. A checkpoint refers to the outermost node
+/// which has wrapped the node it was taken for, regardless of whether it was
+/// wrapped through the same checkpoint or by other means.
+///
 /// [`Builder::close_at`]: crate::Builder::close_at
 /// [`Builder::checkpoint`]: crate::Builder::checkpoint
 #[derive(Debug, Clone)]
@@ -32,6 +37,13 @@ where
         self.0.get().node
     }
 
+    /// Construct a new checkpoint at the same position which doesn't share
+    /// its state with this one.
+    pub(crate) fn detach(&self) -> Self {
+        let Inner { node, parent } = self.0.get();
+        Self::new(node, parent)
+    }
+
     pub(crate) fn get(&self) -> (P, Option<P>) {
         let Inner { node, parent } = self.0.get();
         (node, parent)
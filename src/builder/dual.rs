@@ -0,0 +1,278 @@
+use crate::error::{BuildResult, Error};
+use crate::flavor::{Flavor, FlavorDefault};
+use crate::index::IntoLength;
+use crate::node::Node;
+use crate::tree::Tree;
+
+use super::{Builder, Checkpoint, TreeSink};
+
+/// The lossless and projected trees produced by a [`DualBuilder`].
+type Trees<T, F> = (Tree<T, F>, Tree<T, F>);
+
+/// A builder which constructs a lossless tree together with a projection of
+/// it in a single pass.
+///
+/// Every node and token is added to the lossless tree, while only those whose
+/// value matches the predicate are added to the projection. Nodes in the
+/// projection have the same spans as the corresponding nodes in the lossless
+/// tree, and anything which is filtered out is hoisted into its closest
+/// projected ancestor.
+///
+/// The predicate is consulted both when a node is opened and when it's
+/// closed, so it must always give the same answer for the same value.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::DualBuilder;
+///
+/// let mut tree = DualBuilder::new(|value: &str| value != "ws" && value != "paren");
+///
+/// tree.open("call")?;
+/// tree.token("ident", 3)?;
+/// tree.open("paren")?;
+/// tree.token("open", 1)?;
+/// tree.token("ws", 1)?;
+/// tree.token("number", 2)?;
+/// tree.token("close", 1)?;
+/// tree.close()?;
+/// tree.close()?;
+///
+/// let (lossless, ast) = tree.build()?;
+///
+/// let expected = syntree::tree! {
+///     "call" => {
+///         ("ident", 3),
+///         "paren" => {
+///             ("open", 1),
+///             ("ws", 1),
+///             ("number", 2),
+///             ("close", 1),
+///         }
+///     }
+/// };
+///
+/// assert_eq!(lossless, expected);
+///
+/// let expected = syntree::tree! {
+///     ("call", (0, 8)) => {
+///         ("ident", (0, 3)),
+///         ("open", (3, 4)),
+///         ("number", (5, 7)),
+///         ("close", (7, 8)),
+///     }
+/// };
+///
+/// assert_eq!(ast, expected);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct DualBuilder<T, P, F = FlavorDefault>
+where
+    T: Copy,
+    F: Flavor,
+{
+    full: Builder<T, F>,
+    ast: Builder<T, F>,
+    predicate: P,
+}
+
+impl<T, P> DualBuilder<T, P, FlavorDefault>
+where
+    T: Copy,
+    P: FnMut(T) -> bool,
+{
+    /// Construct a new dual builder using the default flavor and the given
+    /// projection `predicate`.
+    ///
+    /// For a constructor that can use custom flavors, use
+    /// [`DualBuilder::new_with`].
+    #[must_use]
+    pub const fn new(predicate: P) -> Self {
+        Self::new_with(predicate)
+    }
+}
+
+impl<T, P, F> DualBuilder<T, P, F>
+where
+    T: Copy,
+    P: FnMut(T) -> bool,
+    F: Flavor,
+{
+    /// Construct a new dual builder with a custom flavor and the given
+    /// projection `predicate`.
+    #[must_use]
+    pub const fn new_with(predicate: P) -> Self {
+        Self {
+            full: Builder::new_with(),
+            ast: Builder::new_with(),
+            predicate,
+        }
+    }
+
+    /// Start a node with the given `value`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    pub fn open(&mut self, value: T) -> BuildResult<(), F> {
+        self.full.open(value)?;
+
+        if (self.predicate)(value) {
+            self.ast.open(value)?;
+        }
+
+        Ok(())
+    }
+
+    /// End a node being built.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::CloseError`] if there is no node being built.
+    pub fn close(&mut self) -> BuildResult<(), F> {
+        let id = self.full.parent().ok_or(Error::CloseError)?;
+        self.full.close()?;
+
+        if self.projected(id)? {
+            let ast = self.ast.parent().ok_or(Error::CloseError)?;
+            self.copy_span(id, ast)?;
+            self.ast.close()?;
+        }
+
+        Ok(())
+    }
+
+    /// Declare a token with the given `value` and `len`.
+    ///
+    /// See [`Builder::token`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    pub fn token<L>(&mut self, value: T, len: L) -> BuildResult<(), F>
+    where
+        L: IntoLength<F::Length>,
+    {
+        let id = self.full.token(value, len)?;
+
+        if (self.predicate)(value) {
+            let span = *self.get(id)?.span();
+            self.ast.token_with(value, span)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get a checkpoint corresponding to the current position in both trees.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers.
+    pub fn checkpoint(&mut self) -> BuildResult<DualCheckpoint<F::Pointer>, F> {
+        Ok(DualCheckpoint {
+            full: self.full.checkpoint()?,
+            ast: self.ast.checkpoint()?,
+        })
+    }
+
+    /// Wrap everything since the checkpoint `c` in a node with the given
+    /// `value`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::CloseAtError`] if the checkpoint is not a sibling
+    /// of the current position.
+    pub fn close_at(&mut self, c: &DualCheckpoint<F::Pointer>, value: T) -> BuildResult<(), F> {
+        let id = self.full.close_at(&c.full, value)?;
+
+        if (self.predicate)(value) {
+            let ast = self.ast.close_at(&c.ast, value)?;
+            self.copy_span(id, ast)?;
+        }
+
+        Ok(())
+    }
+
+    /// Build the lossless tree and its projection.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::BuildError`] if either tree is not balanced.
+    pub fn build(self) -> BuildResult<Trees<T, F>, F> {
+        let full = self.full.build()?;
+        let mut ast = self.ast.build()?;
+        *ast.span_mut() = *full.span();
+        Ok((full, ast))
+    }
+
+    fn projected(&mut self, id: F::Pointer) -> BuildResult<bool, F> {
+        let value = self.get(id)?.value();
+        Ok((self.predicate)(value))
+    }
+
+    fn get(&self, id: F::Pointer) -> BuildResult<Node<'_, T, F>, F> {
+        self.full.tree().get(id).ok_or(Error::MissingNode(id))
+    }
+
+    /// Copy the span of the node `from` in the lossless tree to the node `to`
+    /// in the projection.
+    fn copy_span(&mut self, from: F::Pointer, to: F::Pointer) -> BuildResult<(), F> {
+        let span = *self.get(from)?.span();
+        let links = self
+            .ast
+            .tree_mut()
+            .get_mut(to)
+            .ok_or(Error::MissingNode(to))?;
+        links.span = span;
+        Ok(())
+    }
+}
+
+impl<T, P, F> TreeSink<T> for DualBuilder<T, P, F>
+where
+    T: Copy,
+    P: FnMut(T) -> bool,
+    F: Flavor,
+{
+    type Error = Error<F::Error, F::Pointer>;
+    type Length = F::Length;
+    type Checkpoint = DualCheckpoint<F::Pointer>;
+
+    #[inline]
+    fn open(&mut self, value: T) -> Result<(), Self::Error> {
+        DualBuilder::open(self, value)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Self::Error> {
+        DualBuilder::close(self)
+    }
+
+    #[inline]
+    fn token(&mut self, value: T, len: Self::Length) -> Result<(), Self::Error> {
+        DualBuilder::token(self, value, len)
+    }
+
+    #[inline]
+    fn checkpoint(&mut self) -> Result<Self::Checkpoint, Self::Error> {
+        DualBuilder::checkpoint(self)
+    }
+
+    #[inline]
+    fn close_at(&mut self, c: &Self::Checkpoint, value: T) -> Result<(), Self::Error> {
+        DualBuilder::close_at(self, c, value)
+    }
+}
+
+/// A checkpoint produced by [`DualBuilder::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct DualCheckpoint<P>
+where
+    P: Copy,
+{
+    full: Checkpoint<P>,
+    ast: Checkpoint<P>,
+}
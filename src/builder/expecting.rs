@@ -0,0 +1,281 @@
+use core::fmt;
+
+use crate::error::Error;
+use crate::flavor::{Flavor, FlavorDefault};
+use crate::index::IntoLength;
+use crate::tree::Tree;
+
+use super::{Builder, Checkpoint, FixedCheckpoint};
+
+/// A wrapper around a [`Builder`] which panics instead of returning errors.
+///
+/// This is intended for prototypes, tests and examples where a failure to
+/// build a tree is a bug, and error handling only gets in the way. Every
+/// method corresponds to a method on [`Builder`], and panics with the error
+/// that method would have returned. Prefer the fallible [`Builder`] API
+/// anywhere malformed input can reach the builder.
+///
+/// This is constructed through [`Expecting::new`] or [`Builder::expecting`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Expecting;
+///
+/// let mut tree = Expecting::new();
+///
+/// let c = tree.checkpoint();
+/// tree.open("number");
+/// tree.token("lit", 3);
+/// tree.close();
+/// tree.token("ws", 1);
+/// tree.close_at(&c, "root");
+///
+/// let tree = tree.build();
+///
+/// let expected = syntree::tree! {
+///     "root" => {
+///         "number" => {
+///             ("lit", 3)
+///         },
+///         ("ws", 1)
+///     }
+/// };
+///
+/// assert_eq!(tree, expected);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// Unbalanced calls panic:
+///
+/// ```should_panic
+/// let mut tree = syntree::Expecting::<&str>::new();
+/// tree.close();
+/// ```
+pub struct Expecting<T, F = FlavorDefault>
+where
+    T: Copy,
+    F: Flavor,
+{
+    builder: Builder<T, F>,
+}
+
+impl<T> Expecting<T, FlavorDefault>
+where
+    T: Copy,
+{
+    /// Construct a new builder using the default flavor.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            builder: Builder::new(),
+        }
+    }
+}
+
+impl<T, F> Expecting<T, F>
+where
+    T: Copy,
+    F: Flavor<Error: fmt::Display>,
+{
+    /// Access the wrapped builder.
+    #[inline]
+    pub fn builder(&mut self) -> &mut Builder<T, F> {
+        &mut self.builder
+    }
+
+    /// Unwrap into the wrapped builder.
+    #[inline]
+    pub fn into_builder(self) -> Builder<T, F> {
+        self.builder
+    }
+
+    /// See [`Builder::open`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::open`] errors.
+    #[track_caller]
+    pub fn open(&mut self, data: T) -> F::Pointer {
+        expect(self.builder.open(data))
+    }
+
+    /// See [`Builder::close`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::close`] errors.
+    #[track_caller]
+    pub fn close(&mut self) {
+        expect(self.builder.close());
+    }
+
+    /// See [`Builder::node`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::node`] errors.
+    #[track_caller]
+    pub fn node<O>(&mut self, data: T, f: impl FnOnce(&mut Self) -> O) -> O {
+        self.open(data);
+        let output = f(self);
+        self.close();
+        output
+    }
+
+    /// See [`Builder::token`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::token`] errors.
+    #[track_caller]
+    pub fn token<L>(&mut self, value: T, len: L) -> F::Pointer
+    where
+        L: IntoLength<F::Length>,
+    {
+        expect(self.builder.token(value, len))
+    }
+
+    /// See [`Builder::token_str`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::token_str`] errors.
+    #[track_caller]
+    pub fn token_str(&mut self, value: T, text: &str) -> F::Pointer {
+        expect(self.builder.token_str(value, text))
+    }
+
+    /// See [`Builder::token_empty`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::token_empty`] errors.
+    #[track_caller]
+    pub fn token_empty(&mut self, value: T) -> F::Pointer {
+        expect(self.builder.token_empty(value))
+    }
+
+    /// See [`Builder::token_unspanned`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::token_unspanned`] errors.
+    #[track_caller]
+    pub fn token_unspanned(&mut self, value: T) -> F::Pointer {
+        expect(self.builder.token_unspanned(value))
+    }
+
+    /// See [`Builder::token_usize`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::token_usize`] errors.
+    #[track_caller]
+    pub fn token_usize(&mut self, value: T, len: usize) -> F::Pointer {
+        expect(self.builder.token_usize(value, len))
+    }
+
+    /// See [`Builder::glue_last`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::glue_last`] errors.
+    #[track_caller]
+    pub fn glue_last(&mut self, n: usize, value: T) -> F::Pointer {
+        expect(self.builder.glue_last(n, value))
+    }
+
+    /// See [`Builder::checkpoint`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::checkpoint`] errors.
+    #[track_caller]
+    pub fn checkpoint(&mut self) -> Checkpoint<F::Pointer> {
+        expect(self.builder.checkpoint())
+    }
+
+    /// See [`Builder::close_at`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::close_at`] errors.
+    #[track_caller]
+    pub fn close_at(&mut self, c: &Checkpoint<F::Pointer>, data: T) -> F::Pointer {
+        expect(self.builder.close_at(c, data))
+    }
+
+    /// See [`Builder::checkpoint_fixed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::checkpoint_fixed`] errors.
+    #[track_caller]
+    pub fn checkpoint_fixed(&self) -> FixedCheckpoint<F::Pointer> {
+        expect(self.builder.checkpoint_fixed())
+    }
+
+    /// See [`Builder::close_at_fixed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::close_at_fixed`] errors.
+    #[track_caller]
+    pub fn close_at_fixed(&mut self, c: FixedCheckpoint<F::Pointer>, data: T) -> F::Pointer {
+        expect(self.builder.close_at_fixed(c, data))
+    }
+
+    /// See [`Builder::open_above`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::open_above`] errors.
+    #[track_caller]
+    pub fn open_above(&mut self, id: F::Pointer, data: T) -> F::Pointer {
+        expect(self.builder.open_above(id, data))
+    }
+
+    /// See [`Builder::build`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::build`] errors.
+    #[track_caller]
+    pub fn build(self) -> Tree<T, F> {
+        expect(self.builder.build())
+    }
+}
+
+impl<T> Default for Expecting<T, FlavorDefault>
+where
+    T: Copy,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, F> From<Builder<T, F>> for Expecting<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn from(builder: Builder<T, F>) -> Self {
+        Self { builder }
+    }
+}
+
+#[track_caller]
+fn expect<O, E, P>(result: Result<O, Error<E, P>>) -> O
+where
+    E: fmt::Display,
+    P: fmt::Debug,
+{
+    match result {
+        Ok(output) => output,
+        Err(error) => panic!("{error}"),
+    }
+}
@@ -0,0 +1,43 @@
+/// A checkpoint without interior mutability, as returned by
+/// [`Builder::checkpoint_fixed`].
+///
+/// Unlike [`Checkpoint`], this is a plain value which is [`Copy`] and is
+/// [`Send`] and [`Sync`] whenever the pointer is. This makes it suitable for
+/// storing in parser state which is shared across threads.
+///
+/// Since it isn't updated when it's closed, it instead records the sibling
+/// which precedes its position. When it's passed to
+/// [`Builder::close_at_fixed`], the builder wraps whatever follows the
+/// outermost node which has since wrapped that sibling. So nesting checkpoints
+/// taken at the same position works just like with [`Checkpoint`], and
+/// checkpoints are unaffected by nodes being wrapped before them.
+///
+/// Like other checkpoints, it no longer refers to the same position if the
+/// sibling preceding it is glued with [`Builder::glue_last`].
+///
+/// [`Builder::checkpoint_fixed`]: crate::Builder::checkpoint_fixed
+/// [`Builder::close_at_fixed`]: crate::Builder::close_at_fixed
+/// [`Builder::glue_last`]: crate::Builder::glue_last
+/// [`Checkpoint`]: crate::Checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedCheckpoint<P> {
+    // The sibling preceding the checkpoint, if any.
+    prev: Option<P>,
+    // The parent node of the context being checkpointed.
+    parent: Option<P>,
+}
+
+impl<P> FixedCheckpoint<P>
+where
+    P: Copy,
+{
+    #[inline]
+    pub(super) fn new(prev: Option<P>, parent: Option<P>) -> Self {
+        Self { prev, parent }
+    }
+
+    #[inline]
+    pub(super) fn get(&self) -> (Option<P>, Option<P>) {
+        (self.prev, self.parent)
+    }
+}
@@ -0,0 +1,150 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::error::BuildResult;
+use crate::flavor::Flavor;
+
+use super::Builder;
+
+/// A guard for a node which has been opened with [`Builder::open_scoped`].
+///
+/// The guard dereferences to the [`Builder`] it was opened from, so that
+/// children of the node can be added through it. When the guard is dropped
+/// the node is closed, together with any nodes opened inside of it which
+/// haven't been closed. Use [`NodeGuard::close`] to close the node and handle
+/// any errors explicitly.
+///
+/// # Examples
+///
+/// ```
+/// let mut tree = syntree::Builder::new();
+///
+/// {
+///     let mut root = tree.open_scoped("root")?;
+///     root.token("lit", 3)?;
+///
+///     let mut child = root.open_scoped("child")?;
+///     child.token("lit", 2)?;
+///     // Dropping the guard closes the child.
+/// }
+///
+/// let tree = tree.build()?;
+///
+/// let expected = syntree::tree! {
+///     "root" => {
+///         ("lit", 3),
+///         "child" => {
+///             ("lit", 2)
+///         }
+///     }
+/// };
+///
+/// assert_eq!(tree, expected);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct NodeGuard<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    builder: &'a mut Builder<T, F>,
+    id: F::Pointer,
+    /// Whether the node still needs to be closed.
+    open: bool,
+}
+
+impl<'a, T, F> NodeGuard<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(super) fn new(builder: &'a mut Builder<T, F>, id: F::Pointer) -> Self {
+        Self {
+            builder,
+            id,
+            open: true,
+        }
+    }
+
+    /// Get the identifier of the guarded node.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> F::Pointer {
+        self.id
+    }
+
+    /// Close the guarded node, together with any nodes opened inside of it
+    /// which haven't been closed.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::CloseError`][crate::Error::CloseError] if the node has already been closed
+    /// through the builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// let mut root = tree.open_scoped("root")?;
+    /// root.token("lit", 3)?;
+    /// root.close()?;
+    ///
+    /// tree.token("eof", 0)?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     },
+    ///     ("eof", 0)
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn close(mut self) -> BuildResult<(), F> {
+        self.open = false;
+        self.builder.close_through(self.id)
+    }
+}
+
+impl<T, F> Deref for NodeGuard<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Target = Builder<T, F>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.builder
+    }
+}
+
+impl<T, F> DerefMut for NodeGuard<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.builder
+    }
+}
+
+impl<T, F> Drop for NodeGuard<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn drop(&mut self) {
+        if self.open {
+            // Errors can't be reported from a destructor, use `close` to
+            // handle them.
+            let _ = self.builder.close_through(self.id);
+        }
+    }
+}
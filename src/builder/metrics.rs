@@ -0,0 +1,53 @@
+use core::time::Duration;
+
+/// Metrics collected by a [Builder][crate::Builder] when the `instrument`
+/// feature is enabled.
+///
+/// See [`Builder::metrics`][crate::Builder::metrics].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Metrics {
+    /// The number of nodes opened through [`Builder::open`] or
+    /// [`Builder::open_with`].
+    ///
+    /// [`Builder::open`]: crate::Builder::open
+    /// [`Builder::open_with`]: crate::Builder::open_with
+    pub opens: usize,
+    /// The number of nodes closed through [`Builder::close`].
+    ///
+    /// [`Builder::close`]: crate::Builder::close
+    pub closes: usize,
+    /// The number of tokens inserted.
+    pub tokens: usize,
+    /// The number of calls to [`Builder::checkpoint`].
+    ///
+    /// [`Builder::checkpoint`]: crate::Builder::checkpoint
+    pub checkpoints: usize,
+    /// The number of nodes inserted through [`Builder::close_at`] or
+    /// [`Builder::close_at_with`].
+    ///
+    /// [`Builder::close_at`]: crate::Builder::close_at
+    /// [`Builder::close_at_with`]: crate::Builder::close_at_with
+    pub close_ats: usize,
+    /// The number of times the tree had to be restructured as part of a
+    /// call to close at a checkpoint, which happens when the checkpointed
+    /// range covers more than one sibling.
+    pub restructurings: usize,
+    /// The number of siblings which were visited while restructuring.
+    pub restructured_nodes: usize,
+    /// The total time spent restructuring.
+    pub restructure_time: Duration,
+}
+
+impl Metrics {
+    pub(crate) const EMPTY: Self = Self {
+        opens: 0,
+        closes: 0,
+        tokens: 0,
+        checkpoints: 0,
+        close_ats: 0,
+        restructurings: 0,
+        restructured_nodes: 0,
+        restructure_time: Duration::ZERO,
+    };
+}
@@ -0,0 +1,30 @@
+/// A node whose value hasn't been decided yet, as returned by
+/// [`Builder::open_deferred`].
+///
+/// The node must be closed with [`Builder::close_with`], which supplies its
+/// value.
+///
+/// [`Builder::open_deferred`]: crate::Builder::open_deferred
+/// [`Builder::close_with`]: crate::Builder::close_with
+#[derive(Debug)]
+#[must_use = "a pending node must be closed with `Builder::close_with`"]
+pub struct PendingNode<P> {
+    id: P,
+}
+
+impl<P> PendingNode<P>
+where
+    P: Copy,
+{
+    #[inline]
+    pub(super) fn new(id: P) -> Self {
+        Self { id }
+    }
+
+    /// Get the identifier of the pending node.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> P {
+        self.id
+    }
+}
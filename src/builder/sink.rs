@@ -0,0 +1,137 @@
+use crate::error::Error;
+use crate::flavor::Flavor;
+
+use super::{Builder, Checkpoint};
+
+/// A destination for the events produced while parsing.
+///
+/// This abstracts over the operations of a [Builder], so that a parser can be
+/// written once and used to construct either a single tree or, through a
+/// [`DualBuilder`], a lossless tree together with a compact projection of it.
+///
+/// [`DualBuilder`]: crate::DualBuilder
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Builder, TreeSink};
+///
+/// fn parse_number<S>(sink: &mut S) -> Result<(), S::Error>
+/// where
+///     S: TreeSink<&'static str, Length = usize>,
+/// {
+///     let c = sink.checkpoint()?;
+///     sink.token("lit", 3)?;
+///     sink.token("ws", 1)?;
+///     sink.close_at(&c, "number")?;
+///     Ok(())
+/// }
+///
+/// let mut tree = Builder::new();
+/// parse_number(&mut tree)?;
+/// let tree = tree.build()?;
+///
+/// let expected = syntree::tree! {
+///     "number" => {
+///         ("lit", 3),
+///         ("ws", 1),
+///     }
+/// };
+///
+/// assert_eq!(tree, expected);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait TreeSink<T> {
+    /// The error raised by the sink.
+    type Error;
+
+    /// The length of a token.
+    type Length;
+
+    /// A checkpoint which can be used with [`TreeSink::close_at`].
+    type Checkpoint;
+
+    /// Start a node with the given `value`.
+    ///
+    /// See [`Builder::open`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the node could not be opened.
+    fn open(&mut self, value: T) -> Result<(), Self::Error>;
+
+    /// End the node being built.
+    ///
+    /// See [`Builder::close`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if there is no node being built.
+    fn close(&mut self) -> Result<(), Self::Error>;
+
+    /// Declare a token with the given `value` and `len`.
+    ///
+    /// See [`Builder::token`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the token could not be added.
+    fn token(&mut self, value: T, len: Self::Length) -> Result<(), Self::Error>;
+
+    /// Get a checkpoint corresponding to the current position.
+    ///
+    /// See [`Builder::checkpoint`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the checkpoint could not be constructed.
+    fn checkpoint(&mut self) -> Result<Self::Checkpoint, Self::Error>;
+
+    /// Wrap everything since the checkpoint `c` in a node with the given
+    /// `value`.
+    ///
+    /// See [`Builder::close_at`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the checkpoint is not a sibling of the current position.
+    fn close_at(&mut self, c: &Self::Checkpoint, value: T) -> Result<(), Self::Error>;
+}
+
+impl<T, F> TreeSink<T> for Builder<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Error = Error<F::Error, F::Pointer>;
+    type Length = F::Length;
+    type Checkpoint = Checkpoint<F::Pointer>;
+
+    #[inline]
+    fn open(&mut self, value: T) -> Result<(), Self::Error> {
+        Builder::open(self, value)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Self::Error> {
+        Builder::close(self)
+    }
+
+    #[inline]
+    fn token(&mut self, value: T, len: Self::Length) -> Result<(), Self::Error> {
+        Builder::token(self, value, len)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn checkpoint(&mut self) -> Result<Self::Checkpoint, Self::Error> {
+        Builder::checkpoint(self)
+    }
+
+    #[inline]
+    fn close_at(&mut self, c: &Self::Checkpoint, value: T) -> Result<(), Self::Error> {
+        Builder::close_at(self, c, value)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use core::fmt;
+
+use crate::index::{Index, IntoLength, Length};
+
+/// An [Index] which tracks two offsets at the same time, such as a byte offset
+/// and a character offset.
+///
+/// Each token is given a length for both components, and the builder advances
+/// both of them. This makes it possible to get both offsets of any node
+/// directly from its span, instead of repeatedly building a line or character
+/// index downstream.
+///
+/// Offsets are ordered by their `primary` component first, which matches the
+/// ordering of the `secondary` component in any tree built from monotonically
+/// increasing offsets.
+///
+/// Since a single `usize` can't describe both offsets, [`Index::from_usize`]
+/// and [`Length::from_usize`] always return `None` for this type. This means
+/// that queries by plain range such as [`Tree::node_with_range`] never match,
+/// use [`Tree::node_with_span`] instead. Similarly, [`Builder::token_usize`]
+/// and [`Builder::token_str`] error with [`Error::UnsupportedLength`], so
+/// tokens have to be inserted with [`Builder::token`] and a length for both
+/// components.
+///
+/// [`Tree::node_with_range`]: crate::Tree::node_with_range
+/// [`Tree::node_with_span`]: crate::Tree::node_with_span
+/// [`Builder::token`]: crate::Builder::token
+/// [`Builder::token_usize`]: crate::Builder::token_usize
+/// [`Builder::token_str`]: crate::Builder::token_str
+/// [`Error::UnsupportedLength`]: crate::Error::UnsupportedLength
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Dual, Span, Unit};
+///
+/// syntree::flavor! {
+///     struct FlavorDual {
+///         type Index = Dual<u32, u32>;
+///     }
+/// }
+///
+/// let source = "ö + 1";
+///
+/// let mut tree = syntree::Builder::<_, FlavorDual>::new_with();
+///
+/// tree.open("add")?;
+///
+/// for (value, text) in [("ident", "ö"), ("ws", " "), ("plus", "+"), ("ws", " "), ("number", "1")] {
+///     tree.token(value, Dual::new(Unit::Bytes.len(text), Unit::Chars.len(text)))?;
+/// }
+///
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// let plus = tree.first().and_then(|n| n.children().nth(2)).ok_or("missing plus")?;
+///
+/// assert_eq!(plus.span(), Span::new(Dual::new(3, 2), Dual::new(4, 3)));
+/// assert_eq!(plus.range(), 3..4);
+/// assert_eq!(&source[plus.range()], "+");
+///
+/// let span = Span::new(Dual::new(0, 0), Dual::new(4, 3));
+/// assert_eq!(tree.node_with_span(span).map(|n| n.value()), Some("add"));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dual<P, S> {
+    /// The primary offset, which is used when the index is coerced into a
+    /// `usize` such as through [`Span::range`].
+    ///
+    /// [`Span::range`]: crate::Span::range
+    pub primary: P,
+    /// The secondary offset.
+    pub secondary: S,
+}
+
+impl<P, S> Dual<P, S> {
+    /// Construct a new dual offset or length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Dual;
+    ///
+    /// let dual = Dual::new(4u32, 2u32);
+    /// assert_eq!(dual.primary, 4);
+    /// assert_eq!(dual.secondary, 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new(primary: P, secondary: S) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl<P, S> fmt::Display for Dual<P, S>
+where
+    P: fmt::Display,
+    S: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.primary, self.secondary)
+    }
+}
+
+impl<P, S> Index for Dual<P, S>
+where
+    P: Index,
+    S: Index,
+{
+    const EMPTY: Self = Dual::new(P::EMPTY, S::EMPTY);
+
+    type Length = Dual<P::Length, S::Length>;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.primary.is_empty() && self.secondary.is_empty()
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        self.primary.as_usize()
+    }
+
+    #[inline]
+    fn checked_add_len(self, other: Self::Length) -> Option<Self> {
+        Some(Dual::new(
+            self.primary.checked_add_len(other.primary)?,
+            self.secondary.checked_add_len(other.secondary)?,
+        ))
+    }
+
+    #[inline]
+    fn saturating_add_len(self, other: Self::Length) -> Self {
+        Dual::new(
+            self.primary.saturating_add_len(other.primary),
+            self.secondary.saturating_add_len(other.secondary),
+        )
+    }
+
+    #[inline]
+    fn len_to(self, other: Self) -> Self::Length {
+        Dual::new(
+            self.primary.len_to(other.primary),
+            self.secondary.len_to(other.secondary),
+        )
+    }
+
+    #[inline]
+    fn from_usize(_: usize) -> Option<Self> {
+        None
+    }
+}
+
+impl<P, S> IntoLength<Dual<P, S>> for Dual<P, S> {
+    #[inline]
+    fn into_length(self) -> Option<Self> {
+        Some(self)
+    }
+}
+
+impl<P, S> Length for Dual<P, S>
+where
+    P: Length,
+    S: Length,
+{
+    const EMPTY: Self = Dual::new(P::EMPTY, S::EMPTY);
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.primary.is_empty() && self.secondary.is_empty()
+    }
+
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(Dual::new(
+            self.primary.checked_add(other.primary)?,
+            self.secondary.checked_add(other.secondary)?,
+        ))
+    }
+
+    #[inline]
+    fn saturating_sub(self, other: Self) -> Self {
+        Dual::new(
+            self.primary.saturating_sub(other.primary),
+            self.secondary.saturating_sub(other.secondary),
+        )
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        self.primary.as_usize()
+    }
+
+    #[inline]
+    fn from_usize(_: usize) -> Option<Self> {
+        None
+    }
+}
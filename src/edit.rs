@@ -1,24 +1,36 @@
 //! Types associated with performing immutable editing of a tree.
+//!
+//! Narrow edits which don't change the structure of a tree can instead be
+//! performed in place, which is much cheaper. See [`Tree::replace_value`] and
+//! [`Tree::set_token_len`].
 
-use core::cell::Cell;
+use core::ops::RangeInclusive;
 
 use alloc::vec::Vec;
 
 use std::collections::HashMap;
 
-use crate::error::Error;
-use crate::flavor::{Flavor, Storage};
-use crate::index::{Index, TreeIndex};
-use crate::links::Links;
-use crate::node::Node;
+use crate::builder::Builder;
+use crate::error::{BuildResult, Error};
+use crate::flavor::Flavor;
+use crate::kind::Kind;
+#[cfg(feature = "alloc")]
 use crate::pointer::Pointer;
-use crate::span::Span;
+#[cfg(feature = "alloc")]
+use crate::tree::NodeMap;
 use crate::tree::Tree;
 
+/// A modified tree together with the new identifiers of its surviving nodes.
+#[cfg(feature = "alloc")]
+type Remapped<T, F> = (Tree<T, F>, NodeMap<F, <F as Flavor>::Pointer>);
+
 #[derive(Debug)]
-pub(crate) enum Change {
+pub(crate) enum Change<P> {
     /// Delete the given node.
     Delete,
+    /// Replace the given node and its siblings up until and including `end`
+    /// with the tree at index `tree`.
+    Replace { end: P, tree: usize },
 }
 
 /// A recorded set of tree modifications.
@@ -78,9 +90,11 @@ where
     T: Copy,
     F: Flavor,
 {
-    changes: HashMap<F::Pointer, Change>,
-    #[allow(unused)]
+    changes: HashMap<F::Pointer, Change<F::Pointer>>,
     trees: Vec<Tree<T, F>>,
+    /// Scratch buffer for the stack of open nodes used while modifying a
+    /// tree, which is kept so that it can be reused by subsequent calls.
+    parents: Vec<Option<F::Pointer>>,
 }
 
 impl<T, F> ChangeSet<T, F>
@@ -137,13 +151,72 @@ where
         self.changes.insert(id, Change::Delete);
     }
 
+    /// Register the replacement of a contiguous range of siblings in the
+    /// changeset, starting at `range.start()` and ending at and including
+    /// `range.end()`.
+    ///
+    /// Every top-level node in `replacement` is inserted where the range used
+    /// to be. Only one kind of modification for a given node will be preserved,
+    /// and a replacement is keyed by the first node in the range.
+    ///
+    /// If the end of the range is not a later sibling of its start,
+    /// [`ChangeSet::modify`] will error with [`Error::MissingNode`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::edit::ChangeSet;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "block" => {
+    ///         "stmt" => { ("a", 1) },
+    ///         "stmt" => { ("b", 1) },
+    ///         "stmt" => { ("c", 1) },
+    ///         "stmt" => { ("d", 1) },
+    ///     }
+    /// };
+    ///
+    /// let block = tree.first().ok_or("missing block")?;
+    /// let b = block.children().nth(1).ok_or("missing b")?;
+    /// let c = block.children().nth(2).ok_or("missing c")?;
+    ///
+    /// let replacement = syntree::tree! {
+    ///     "stmt" => { ("bc", 2) }
+    /// };
+    ///
+    /// let mut change_set = ChangeSet::new();
+    /// change_set.replace_range(b.id()..=c.id(), replacement);
+    ///
+    /// assert_eq!(
+    ///     change_set.modify(&tree)?,
+    ///     syntree::tree! {
+    ///         "block" => {
+    ///             "stmt" => { ("a", 1) },
+    ///             "stmt" => { ("bc", 2) },
+    ///             "stmt" => { ("d", 1) },
+    ///         }
+    ///     }
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn replace_range(&mut self, range: RangeInclusive<F::Pointer>, replacement: Tree<T, F>) {
+        let (start, end) = range.into_inner();
+        let tree = self.trees.len();
+        self.trees.push(replacement);
+        self.changes.insert(start, Change::Replace { end, tree });
+    }
+
     /// Construct a modified tree where the recorded modifications have been
     /// applied.
     ///
+    /// The buffers used while walking the tree are retained by the change set,
+    /// so calling this repeatedly doesn't need to allocate them again.
+    ///
     /// # Errors
     ///
     /// Errors with [`Error::Overflow`] in case we run out of node
-    /// identifiers.
+    /// identifiers, or with [`Error::MissingNode`] if a range registered with
+    /// [`ChangeSet::replace_range`] doesn't end at a later sibling.
     ///
     /// # Examples
     ///
@@ -174,173 +247,222 @@ where
     /// );
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn modify(&mut self, tree: &Tree<T, F>) -> Result<Tree<T, F>, Error<F::Error>> {
-        let mut output = Tree::<T, F>::with_capacity(tree.capacity())?;
-
-        let mut refactor = RefactorWalk {
-            parents: Vec::new(),
-            prev: None,
-        };
+    pub fn modify(&mut self, tree: &Tree<T, F>) -> BuildResult<Tree<T, F>, F> {
+        self.modify_inner(tree, None, |_, value| value)
+    }
 
-        let mut cursor = F::Index::EMPTY;
+    /// Construct a modified tree like [`ChangeSet::modify`], while mapping the
+    /// value of every node copied from the original tree through `map`, which
+    /// is called with the identifier of the node in the original tree and its
+    /// value.
+    ///
+    /// This allows a single pass to both restructure a tree and rewrite its
+    /// values. Nodes inserted through [`ChangeSet::replace_range`] are not
+    /// mapped, since they don't belong to the original tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors under the same conditions as [`ChangeSet::modify`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::edit::ChangeSet;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("lit", 1),
+    ///         },
+    ///         ("whitespace", 3),
+    ///     }
+    /// };
+    ///
+    /// let ws = tree.first().and_then(|n| n.last()).ok_or("missing whitespace")?;
+    /// let mut renamed = 0;
+    ///
+    /// let mut change_set = ChangeSet::new();
+    /// change_set.remove(ws.id());
+    ///
+    /// let modified = change_set.modify_with(&tree, |_, value| match value {
+    ///     "lit" => {
+    ///         renamed += 1;
+    ///         "number"
+    ///     }
+    ///     value => value,
+    /// })?;
+    ///
+    /// assert_eq!(
+    ///     modified,
+    ///     syntree::tree! {
+    ///         "root" => {
+    ///             "child" => {
+    ///                 ("number", 1),
+    ///             },
+    ///         }
+    ///     }
+    /// );
+    ///
+    /// assert_eq!(renamed, 1);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn modify_with<M>(&mut self, tree: &Tree<T, F>, map: M) -> BuildResult<Tree<T, F>, F>
+    where
+        M: FnMut(F::Pointer, T) -> T,
+    {
+        self.modify_inner(tree, None, map)
+    }
 
-        // The specified sub-tree depth is being deleted.
-        let mut current = tree.first().map(|node| (node, false));
+    /// Construct a modified tree like [`ChangeSet::modify`], while also
+    /// returning a map from the identifiers of nodes in the original tree to
+    /// their identifiers in the modified tree.
+    ///
+    /// Only nodes which survived the modification are present in the map,
+    /// which allows side tables keyed by identifier to be carried over to the
+    /// modified tree instead of being recomputed.
+    ///
+    /// # Errors
+    ///
+    /// Errors under the same conditions as [`ChangeSet::modify`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::edit::ChangeSet;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("ws", 1),
+    ///         "number" => {
+    ///             ("lit", 2),
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let ws = root.first().ok_or("missing ws")?;
+    /// let number = root.last().ok_or("missing number")?;
+    ///
+    /// let mut change_set = ChangeSet::new();
+    /// change_set.remove(ws.id());
+    ///
+    /// let (modified, ids) = change_set.modify_with_id_map(&tree)?;
+    ///
+    /// assert!(!ids.contains(ws.id()));
+    ///
+    /// let new_number = ids.get(number.id()).and_then(|&id| modified.get(id));
+    /// assert_eq!(new_number.map(|n| n.value()), Some("number"));
+    /// assert_eq!(ids.len(), 3);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn modify_with_id_map(&mut self, tree: &Tree<T, F>) -> BuildResult<Remapped<T, F>, F> {
+        let mut ids = Vec::new();
+        let output = self.modify_inner(tree, Some(&mut ids), |_, value| value)?;
+        ids.sort_by_key(|(old, _): &(F::Pointer, F::Pointer)| old.get());
+        Ok((output, NodeMap::from_sorted(ids)))
+    }
 
-        while let Some((mut node, mut first)) = current.take() {
-            let node_id = F::Pointer::new(output.len()).ok_or(Error::Overflow)?;
+    fn modify_inner<M>(
+        &mut self,
+        tree: &Tree<T, F>,
+        mut ids: Option<&mut Vec<(F::Pointer, F::Pointer)>>,
+        mut map: M,
+    ) -> BuildResult<Tree<T, F>, F>
+    where
+        M: FnMut(F::Pointer, T) -> T,
+    {
+        let mut output =
+            Builder::<T, F>::try_with_capacity(tree.capacity(), tree.token_offsets().len())?;
+        output.set_index_empty_tokens(tree.index_empty());
+
+        // The next sibling to visit at each level of nodes which are
+        // currently open.
+        self.parents.clear();
+        let mut current = tree.first();
+
+        loop {
+            let Some(node) = current else {
+                let Some(next) = self.parents.pop() else {
+                    break;
+                };
+
+                output.close()?;
+                current = next.and_then(|id| tree.get(id));
+                continue;
+            };
 
-            if let Some(change) = self.changes.get(&node_id) {
-                match change {
-                    Change::Delete => {
-                        let Some(skipped) = refactor.skip_subtree(node, first) else {
-                            continue;
-                        };
+            match self.changes.get(&node.id()) {
+                Some(Change::Delete) => {
+                    current = node.next();
+                    continue;
+                }
+                Some(&Change::Replace { end, tree: index }) => {
+                    let mut last = node;
 
-                        node = skipped.node;
-                        first = skipped.first;
+                    while last.id() != end {
+                        last = last.next().ok_or(Error::MissingNode(end))?;
                     }
-                }
-            }
 
-            if refactor.parents.is_empty() {
-                let (first, last) = output.links_mut();
+                    if let Some(replacement) = self.trees.get(index) {
+                        copy(&mut output, replacement)?;
+                    }
 
-                if first.is_none() {
-                    *first = Some(node_id);
+                    current = last.next();
+                    continue;
                 }
-
-                *last = Some(node_id);
+                None => {}
             }
 
-            // Since we are the first node in the sequence we're obligated to
-            // set the first child of the parent.
-            let prev = if first {
-                None
-            } else {
-                let prev = refactor.prev.take();
+            let value = map(node.id(), node.value());
 
-                if let Some(prev) = prev.and_then(|id| output.get_mut(id)) {
-                    prev.next = Some(node_id);
+            let id = match node.kind() {
+                Kind::Token => {
+                    current = node.next();
+                    output.token(value, node.span().len())?
+                }
+                Kind::Node => {
+                    self.parents.push(node.next().map(|n| n.id()));
+                    current = node.first();
+                    output.open(value)?
                 }
-
-                prev
-            };
-
-            let span = if !node.has_children() && !node.span().is_empty() {
-                output.indexes_mut().push(TreeIndex {
-                    index: cursor,
-                    id: node_id,
-                })?;
-                let start = cursor;
-                cursor = cursor
-                    .checked_add_len(node.span().len())
-                    .ok_or(Error::Overflow)?;
-                Span::new(start, cursor)
-            } else {
-                Span::point(cursor)
             };
 
-            let parent = refactor.parents.last().map(|n| n.1);
+            output.set_flag(id, node.flags())?;
 
-            if let Some(parent) = parent.and_then(|id| output.get_mut(id)) {
-                if parent.first.is_none() {
-                    parent.first = Some(node_id);
-                }
-
-                parent.last = Some(node_id);
-                parent.span.end = span.end;
+            if let Some(ids) = &mut ids {
+                ids.push((node.id(), id));
             }
-
-            output.push(Links {
-                data: Cell::new(node.value()),
-                span,
-                parent,
-                prev,
-                next: None,
-                first: None,
-                last: None,
-            })?;
-
-            current = refactor.step(node, node_id);
         }
 
-        output.span_mut().end = cursor;
-        Ok(output)
+        output.build()
     }
 }
 
-impl<T, F> Default for ChangeSet<T, F>
+/// Copy every node in `tree` into `output`.
+fn copy<T, F>(output: &mut Builder<T, F>, tree: &Tree<T, F>) -> BuildResult<(), F>
 where
     T: Copy,
     F: Flavor,
 {
-    #[inline]
-    fn default() -> Self {
-        Self {
-            changes: HashMap::new(),
-            trees: Vec::new(),
-        }
+    for node in tree.children() {
+        tree.clone_subtree_into(node.id(), output)?;
     }
-}
-
-/// The state of the skipped subtree.
-struct Skipped<'a, T, F>
-where
-    T: Copy,
-    F: Flavor,
-{
-    node: Node<'a, T, F>,
-    first: bool,
-}
 
-struct RefactorWalk<'a, T, F>
-where
-    T: Copy,
-    F: Flavor,
-{
-    parents: Vec<(Node<'a, T, F>, F::Pointer)>,
-    prev: Option<F::Pointer>,
+    Ok(())
 }
 
-impl<'a, T, F> RefactorWalk<'a, T, F>
+impl<T, F> Default for ChangeSet<T, F>
 where
     T: Copy,
     F: Flavor,
 {
-    fn skip_subtree(&mut self, node: Node<'a, T, F>, first: bool) -> Option<Skipped<'a, T, F>> {
-        if let Some(next) = node.next() {
-            return Some(Skipped { node: next, first });
-        }
-
-        let (node, parent_id) = self.parents.pop()?;
-        self.prev = Some(parent_id);
-        Some(Skipped { node, first: false })
-    }
-
-    /// Advance the iteration.
-    fn step(
-        &mut self,
-        node: Node<'a, T, F>,
-        node_id: F::Pointer,
-    ) -> Option<(Node<'a, T, F>, bool)> {
-        if let Some(next) = node.first() {
-            self.parents.push((node, node_id));
-            return Some((next, true));
-        }
-
-        if let Some(next) = node.next() {
-            self.prev = Some(node_id);
-            return Some((next, false));
-        }
-
-        while let Some((parent, prev_id)) = self.parents.pop() {
-            if let Some(next) = parent.next() {
-                self.prev = Some(prev_id);
-                return Some((next, false));
-            }
+    #[inline]
+    fn default() -> Self {
+        Self {
+            changes: HashMap::new(),
+            trees: Vec::new(),
+            parents: Vec::new(),
         }
-
-        None
     }
 }
@@ -3,7 +3,7 @@ use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 
 use crate::flavor::Storage;
-use crate::index::{Index, Length};
+use crate::index::{Index, IntoLength, Length};
 
 /// The empty [Index] implementation.
 ///
@@ -48,6 +48,11 @@ impl Index for Empty {
         Some(Empty)
     }
 
+    #[inline]
+    fn saturating_add_len(self, _: Self::Length) -> Self {
+        Empty
+    }
+
     #[inline]
     fn len_to(self, _: Self) -> Self {
         Empty
@@ -73,6 +78,33 @@ impl Length for Empty {
     fn is_empty(&self) -> bool {
         true
     }
+
+    #[inline]
+    fn checked_add(self, _: Self) -> Option<Self> {
+        Some(Empty)
+    }
+
+    #[inline]
+    fn saturating_sub(self, _: Self) -> Self {
+        Empty
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn from_usize(_: usize) -> Option<Self> {
+        Some(Empty)
+    }
+}
+
+impl IntoLength<Empty> for Empty {
+    #[inline]
+    fn into_length(self) -> Option<Empty> {
+        Some(self)
+    }
 }
 
 /// An empty vector.
@@ -103,6 +135,9 @@ impl<T> Storage<T> for EmptyVec<T> {
     fn capacity(&self) -> usize {
         0
     }
+
+    #[inline]
+    fn truncate(&mut self, _: usize) {}
 }
 
 impl<T> Deref for EmptyVec<T> {
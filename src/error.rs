@@ -1,10 +1,21 @@
 use core::convert::Infallible;
 use core::fmt;
 
+use crate::builder::Builder;
+use crate::flavor::Flavor;
+use crate::pointer::PointerUsize;
+use crate::span::Span;
+use crate::tree::ValidationError;
+
 /// Errors raised while building a tree.
+///
+/// The `P` parameter is the [Pointer] type of the tree being built, which is
+/// used to identify nodes referenced by the error.
+///
+/// [Pointer]: crate::pointer::Pointer
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
-pub enum Error<E = Infallible> {
+pub enum Error<E = Infallible, P = PointerUsize> {
     /// Error raised by [Builder::close][crate::Builder::close] if there
     /// currently is no node being built.
     ///
@@ -63,6 +74,72 @@ pub enum Error<E = Infallible> {
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
     CloseAtError,
+    /// Error raised by [Builder::glue_last][crate::Builder::glue_last] if the
+    /// most recently inserted elements are not sibling tokens at the current
+    /// level of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Error};
+    ///
+    /// let mut tree = Builder::new();
+    ///
+    /// tree.token(">", 1)?;
+    /// tree.open("child")?;
+    /// tree.close()?;
+    ///
+    /// assert_eq!(tree.glue_last(2, ">>"), Err(Error::GlueError));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    GlueError,
+    /// Error raised if the span of a node would be derived from the cursor
+    /// while the builder requires spans to be specified explicitly, see
+    /// [Builder::set_explicit_spans][crate::Builder::set_explicit_spans].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Error, Span};
+    ///
+    /// let mut tree = Builder::new();
+    /// tree.set_explicit_spans(true);
+    ///
+    /// assert_eq!(tree.open("root"), Err(Error::ImplicitSpan));
+    /// assert_eq!(tree.token("lit", 3), Err(Error::ImplicitSpan));
+    ///
+    /// tree.open_with("root", Span::new(0, 3))?;
+    /// tree.token_with("lit", Span::new(0, 3))?;
+    /// tree.close()?;
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ImplicitSpan,
+    /// Error raised by [Builder::token][crate::Builder::token] and similar
+    /// methods if they are given an integer length, but the length of the
+    /// flavor can't be constructed from one at all, such as with a
+    /// [Dual][crate::Dual] index which tracks more than one offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Dual, Error};
+    ///
+    /// syntree::flavor! {
+    ///     struct FlavorDual {
+    ///         type Index = Dual<u32, u32>;
+    ///     }
+    /// }
+    ///
+    /// let mut tree = Builder::<_, FlavorDual>::new_with();
+    ///
+    /// assert_eq!(tree.token("lit", 3), Err(Error::UnsupportedLength));
+    /// assert_eq!(tree.token_usize("lit", 3), Err(Error::UnsupportedLength));
+    /// assert_eq!(tree.token_str("lit", "foo"), Err(Error::UnsupportedLength));
+    ///
+    /// tree.token("lit", Dual::new(3, 3))?;
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    UnsupportedLength,
     /// Numerical overflow.
     ///
     /// This only happens under extreme circumstances or if a feature is enabled
@@ -102,6 +179,7 @@ pub enum Error<E = Infallible> {
     /// two compatible trees:
     ///
     /// ```
+    /// use syntree::pointer::PointerUsize;
     /// use syntree::{Builder, Error};
     ///
     /// let mut a = Builder::new();
@@ -112,26 +190,149 @@ pub enum Error<E = Infallible> {
     ///
     /// let c = b.checkpoint()?;
     ///
-    /// assert_eq!(a.close_at(&c, "operation"), Err(Error::MissingNode(0)));
+    /// let id = PointerUsize::new(0).ok_or("bad id")?;
+    /// assert_eq!(a.close_at(&c, "operation"), Err(Error::MissingNode(id)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    MissingNode(P),
+    /// The node of the given id is not a token, but the operation being
+    /// performed only applies to tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Error;
+    ///
+    /// let mut tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?.id();
+    /// assert_eq!(tree.set_token_len(root, 2), Err(Error::NotToken(root)));
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    MissingNode(usize),
+    NotToken(P),
+    /// Error raised by [try_tree!][crate::try_tree] if the node of the given
+    /// id doesn't have the span it is annotated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Error;
+    ///
+    /// let tree = syntree::try_tree! {
+    ///     ("number", expect = (0, 2)) => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// assert!(matches!(tree, Err(Error::UnexpectedSpan(..))));
+    /// ```
+    UnexpectedSpan(P),
+    /// Error raised by [TreeRaw::build][crate::TreeRaw::build] if the raw
+    /// nodes do not form a valid tree.
+    Invalid(ValidationError<P>),
     /// An error raised by the particular [Flavor] in use.
     ///
     /// [Flavor]: crate::Flavor
     Flavor(E),
 }
 
-impl<E> From<E> for Error<E> {
+/// The result of an operation on a tree or builder of the flavor `F`.
+pub(crate) type BuildResult<T, F> = Result<T, Error<<F as Flavor>::Error, <F as Flavor>::Pointer>>;
+
+impl<E, P> Error<E, P> {
+    /// Resolve the span in the input where this error happened, using the
+    /// state of the [Builder] which raised it.
+    ///
+    /// * [`Error::MissingNode`] and [`Error::UnexpectedSpan`] resolve to the
+    ///   span of the referenced node if it exists in the builder.
+    /// * [`Error::CloseAtError`] resolves to the span of the innermost node
+    ///   which is currently open.
+    ///
+    /// In all other cases, this resolves to an empty span at the current
+    /// [cursor] of the builder.
+    ///
+    /// [cursor]: crate::Builder::cursor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Error, Span};
+    ///
+    /// let mut tree = Builder::new();
+    ///
+    /// tree.token("ws", 2)?;
+    /// tree.open("number")?;
+    /// tree.token("lit", 3)?;
+    ///
+    /// let c = tree.checkpoint()?;
+    /// tree.open("inner")?;
+    /// tree.token("lit", 1)?;
+    ///
+    /// let error = tree.close_at(&c, "outer").unwrap_err();
+    /// assert_eq!(error, Error::CloseAtError);
+    /// assert_eq!(error.span(&tree), Span::new(5, 6));
+    ///
+    /// tree.close()?;
+    /// tree.close()?;
+    ///
+    /// let error = tree.close().unwrap_err();
+    /// assert_eq!(error, Error::CloseError);
+    /// assert_eq!(error.span(&tree), Span::point(6));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn span<T, F>(&self, builder: &Builder<T, F>) -> Span<F::Index>
+    where
+        T: Copy,
+        F: Flavor<Pointer = P>,
+        P: Copy,
+    {
+        let id = match self {
+            Error::MissingNode(id) | Error::UnexpectedSpan(id) => Some(*id),
+            Error::CloseAtError => builder.parent(),
+            _ => None,
+        };
+
+        match id.and_then(|id| builder.tree().get(id)) {
+            Some(node) => *node.span(),
+            None => Span::point(*builder.cursor()),
+        }
+    }
+
+    /// Map the error raised by the flavor, if any.
+    pub(crate) fn map_flavor<U>(self, f: impl FnOnce(E) -> U) -> Error<U, P> {
+        match self {
+            Error::CloseError => Error::CloseError,
+            Error::BuildError => Error::BuildError,
+            Error::CloseAtError => Error::CloseAtError,
+            Error::GlueError => Error::GlueError,
+            Error::ImplicitSpan => Error::ImplicitSpan,
+            Error::UnsupportedLength => Error::UnsupportedLength,
+            Error::Overflow => Error::Overflow,
+            Error::MissingNode(p) => Error::MissingNode(p),
+            Error::NotToken(p) => Error::NotToken(p),
+            Error::UnexpectedSpan(p) => Error::UnexpectedSpan(p),
+            Error::Invalid(error) => Error::Invalid(error),
+            Error::Flavor(error) => Error::Flavor(f(error)),
+        }
+    }
+}
+
+impl<E, P> From<E> for Error<E, P> {
     #[inline]
     fn from(error: E) -> Self {
         Error::Flavor(error)
     }
 }
 
-impl<E> core::error::Error for Error<E>
+impl<E, P> core::error::Error for Error<E, P>
 where
     E: 'static + core::error::Error,
+    P: fmt::Debug,
 {
     #[inline]
     fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
@@ -142,9 +343,10 @@ where
     }
 }
 
-impl<E> fmt::Display for Error<E>
+impl<E, P> fmt::Display for Error<E, P>
 where
     E: fmt::Display,
+    P: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -160,12 +362,31 @@ where
                     "trying to close a node which is not a sibling of the checkpoint being closed"
                 )
             }
+            Error::GlueError => {
+                write!(
+                    f,
+                    "trying to glue elements which are not trailing sibling tokens"
+                )
+            }
+            Error::ImplicitSpan => {
+                write!(f, "span must be specified explicitly")
+            }
+            Error::UnsupportedLength => {
+                write!(f, "length can't be constructed from an integer")
+            }
             Error::Overflow => {
                 write!(f, "numerical overflow")
             }
             Error::MissingNode(p) => {
-                write!(f, "missing node with id `{p}`")
+                write!(f, "missing node with id `{p:?}`")
+            }
+            Error::NotToken(p) => {
+                write!(f, "node with id `{p:?}` is not a token")
+            }
+            Error::UnexpectedSpan(p) => {
+                write!(f, "node with id `{p:?}` doesn't have the expected span")
             }
+            Error::Invalid(error) => error.fmt(f),
             Error::Flavor(error) => error.fmt(f),
         }
     }
@@ -0,0 +1,140 @@
+use core::fmt;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A small set of flags which is stored in every node of a tree.
+///
+/// The flags type of a tree is declared with `type Flags` in the
+/// [`flavor!`][crate::flavor!] macro and defaults to `()`, which can't store
+/// any flags and takes up no space. Unsigned integers such as `u8` can store
+/// one flag per bit, which makes it possible to mark nodes as containing
+/// errors or being synthesized without consulting side tables.
+///
+/// Flags are set with [`Builder::set_flag`][crate::Builder::set_flag] and
+/// queried with [`Node::has_flag`][crate::Node::has_flag].
+///
+/// # Examples
+///
+/// ```
+/// syntree::flavor! {
+///     struct FlavorFlags {
+///         type Index = u32;
+///         type Flags = u8;
+///     }
+/// }
+///
+/// const ERROR: u8 = 1 << 0;
+/// const SYNTHESIZED: u8 = 1 << 1;
+///
+/// let mut tree = syntree::Builder::<_, FlavorFlags>::new_with();
+///
+/// let root = tree.open("root")?;
+/// tree.token("lit", 3)?;
+/// let missing = tree.token_empty("semi")?;
+/// tree.close()?;
+///
+/// tree.set_flag(missing, SYNTHESIZED | ERROR)?;
+/// tree.set_flag(root, ERROR)?;
+///
+/// let tree = tree.build()?;
+///
+/// let with_errors = tree.walk().filter(|n| n.has_flag(ERROR)).map(|n| n.value());
+/// assert!(with_errors.eq(["root", "semi"]));
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// assert!(!root.has_flag(SYNTHESIZED | ERROR));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait Flags: Sized + Copy + Default + Eq + fmt::Debug + self::sealed::Sealed {
+    /// The set of flags which is stored in nodes by default.
+    const EMPTY: Self;
+
+    /// Construct the union of this and another set of flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Flags;
+    ///
+    /// assert_eq!(Flags::union(0b01u8, 0b10), 0b11);
+    /// assert_eq!(Flags::union((), ()), ());
+    /// ```
+    #[must_use]
+    fn union(self, other: Self) -> Self;
+
+    /// Test if this set of flags contains every flag in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Flags;
+    ///
+    /// assert!(Flags::contains(0b11u8, 0b01));
+    /// assert!(!Flags::contains(0b01u8, 0b11));
+    /// assert!(Flags::contains((), ()));
+    /// ```
+    fn contains(self, other: Self) -> bool;
+
+    /// Test if this set of flags contains any flag in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Flags;
+    ///
+    /// assert!(Flags::intersects(0b11u8, 0b01));
+    /// assert!(!Flags::intersects(0b01u8, 0b10));
+    /// assert!(!Flags::intersects((), ()));
+    /// ```
+    fn intersects(self, other: Self) -> bool;
+}
+
+impl self::sealed::Sealed for () {}
+
+impl Flags for () {
+    const EMPTY: Self = ();
+
+    #[inline]
+    fn union(self, _: Self) -> Self {}
+
+    #[inline]
+    fn contains(self, _: Self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn intersects(self, _: Self) -> bool {
+        false
+    }
+}
+
+macro_rules! implement {
+    ($($ty:ty),*) => {
+        $(
+            impl self::sealed::Sealed for $ty {}
+
+            impl Flags for $ty {
+                const EMPTY: Self = 0;
+
+                #[inline]
+                fn union(self, other: Self) -> Self {
+                    self | other
+                }
+
+                #[inline]
+                fn contains(self, other: Self) -> bool {
+                    self & other == other
+                }
+
+                #[inline]
+                fn intersects(self, other: Self) -> bool {
+                    self & other != 0
+                }
+            }
+        )*
+    };
+}
+
+implement!(u8, u16, u32, u64);
@@ -2,10 +2,20 @@
 
 use core::ops::DerefMut;
 
-use crate::index::{Index, Length, TreeIndex};
+use crate::flags::Flags;
+use crate::index::{Index, Indexes, Length};
 use crate::pointer::{Pointer, Width};
+use crate::unit::Unit;
 
 /// Storage being used in a tree.
+///
+/// Storage must dereference into a contiguous slice of elements, since nodes
+/// borrow directly into it. This means that a structure-of-arrays layout where
+/// values, spans and links are stored in separate arrays can not be expressed
+/// as a storage. For scans which only touch values or spans, prefer
+/// [`Tree::flat`] which avoids following links entirely.
+///
+/// [`Tree::flat`]: crate::Tree::flat
 pub trait Storage<T>
 where
     Self: Sized + DerefMut<Target = [T]>,
@@ -24,6 +34,11 @@ where
 
     /// Push an item into storage.
     fn push(&mut self, item: T) -> Result<(), Self::Error>;
+
+    /// Shorten the storage to `len` items, dropping the rest.
+    ///
+    /// This has no effect if the storage is already shorter than `len`.
+    fn truncate(&mut self, len: usize);
 }
 
 #[cfg(feature = "alloc")]
@@ -47,6 +62,11 @@ impl<T> Storage<T> for alloc::vec::Vec<T> {
         alloc::vec::Vec::push(self, item);
         Ok(())
     }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        alloc::vec::Vec::truncate(self, len);
+    }
 }
 
 /// Declare a new flavor.
@@ -54,6 +74,22 @@ impl<T> Storage<T> for alloc::vec::Vec<T> {
 /// The available type parameters are:
 /// * `type Index` which declares the index to use.
 /// * `type Width` which declares the width to use, defaults to `usize`.
+/// * `type Indexes` which declares the [`Indexes`] used for span lookups,
+///   defaults to a vector of [`TreeIndex`] entries.
+/// * `type Flags` which declares the [`Flags`] stored in every node, defaults
+///   to `()` which doesn't store any flags.
+/// * `const UNIT` which declares the [`Unit`] spans are measured in, defaults
+///   to [`Unit::Bytes`].
+/// * `const SATURATING` which declares whether the cursor of a
+///   [`Builder`][crate::Builder] saturates at the largest index instead of
+///   erroring with [`Error::Overflow`][crate::Error::Overflow], defaults to
+///   `false`.
+///
+/// [`Indexes`]: crate::Indexes
+/// [`Flags`]: crate::Flags
+/// [`TreeIndex`]: crate::TreeIndex
+/// [`Unit`]: crate::Unit
+/// [`Unit::Bytes`]: crate::Unit::Bytes
 ///
 /// # Examples
 ///
@@ -73,6 +109,42 @@ impl<T> Storage<T> for alloc::vec::Vec<T> {
 ///         type Width = u32;
 ///     }
 /// }
+///
+/// syntree::flavor! {
+///     struct FlavorUtf16 {
+///         type Index = u32;
+///         const UNIT = syntree::Unit::Utf16;
+///     }
+/// }
+/// ```
+///
+/// A saturating flavor clamps spans at the largest index, which is useful for
+/// tolerant tooling which would rather produce a tree with clamped spans than
+/// fail on extremely long inputs:
+///
+/// ```
+/// use syntree::{Builder, Span};
+///
+/// syntree::flavor! {
+///     struct FlavorSaturating {
+///         type Index = u32;
+///         const SATURATING = true;
+///     }
+/// }
+///
+/// let mut tree = Builder::<_, FlavorSaturating>::new_with();
+///
+/// tree.open("root")?;
+/// tree.token("large", u32::MAX as usize - 1)?;
+/// tree.token("lit", 5)?;
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// let lit = tree.find(|n| n.value() == "lit").ok_or("missing lit")?;
+/// assert_eq!(lit.span(), Span::new(u32::MAX - 1, u32::MAX));
+/// assert_eq!(tree.span(), Span::new(0, u32::MAX));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
 /// ```
 #[macro_export]
 macro_rules! flavor {
@@ -83,6 +155,9 @@ macro_rules! flavor {
             $(type Width = $width:ty;)?
             $(type Storage = $storage:ty;)?
             $(type Indexes = $indexes:ty;)?
+            $(type Flags = $flags:ty;)?
+            $(const UNIT = $unit:expr;)?
+            $(const SATURATING = $saturating:expr;)?
         }
     ) => {
         $(#[doc = $doc])*
@@ -97,6 +172,9 @@ macro_rules! flavor {
             type Pointer = $crate::flavor!(@pointer $($width)*);
             type Storage<T> = $crate::macro_support::Vec<T>;
             type Indexes = $crate::flavor!(@indexes $($indexes)*);
+            type Flags = $crate::flavor!(@flags $($flags)*);
+            const UNIT: $crate::Unit = $crate::flavor!(@unit $($unit)*);
+            const SATURATING: bool = $crate::flavor!(@saturating $($saturating)*);
         }
     };
 
@@ -106,6 +184,12 @@ macro_rules! flavor {
     (@pointer) => { <usize as $crate::pointer::Width>::Pointer };
     (@indexes $ty:ty) => { $ty };
     (@indexes) => { $crate::macro_support::DefaultIndexes<Self> };
+    (@flags $ty:ty) => { $ty };
+    (@flags) => { () };
+    (@unit $unit:expr) => { $unit };
+    (@unit) => { $crate::Unit::Bytes };
+    (@saturating $saturating:expr) => { $saturating };
+    (@saturating) => { false };
 }
 
 flavor! {
@@ -129,7 +213,70 @@ flavor! {
 /// through the [Width] trait, this decides how many elements that can be stored
 /// in the tree.
 ///
+/// Spans are always stored as absolute offsets alongside each node. This is
+/// what allows [`Node::span`] to hand out a reference and queries such as
+/// [`Tree::node_with_span`] to binary search the token index. Storing only
+/// lengths and resolving offsets by walking the tree is therefore not a
+/// strategy a flavor can select. If spans are not needed, use an
+/// [`Empty`] index and keep whatever lengths are necessary in the values of
+/// the tree instead.
+///
+/// Similarly, every node always stores links to its parent and previous
+/// sibling, even in trees which are only ever walked top-down. The
+/// [Builder][crate::Builder] follows parent links to find the node to return
+/// to when closing a node, and walks such as [`Tree::walk`] follow them to
+/// climb out of a subtree without keeping a stack. A flavor can therefore not
+/// omit them. To reduce the size of the links, use a smaller `Width` such as
+/// `u32` or `u16`, which shrinks every link in proportion. For scans which
+/// don't need any links at all, see [`Tree::flat`].
+///
+/// # Memory usage
+///
+/// Every node in a tree stores its value, its [Kind], two indexes for its span
+/// and five links: parent, previous and next sibling, and first and last
+/// child. Links don't take up any extra space for being optional, so a node
+/// takes up roughly:
+///
+/// ```text
+/// size_of::<T>() + size_of::<Kind>() + 2 * size_of::<Index>() + 5 * size_of::<Width>()
+/// ```
+///
+/// Rounded up to the alignment of its largest field. With a `u32` index and
+/// a `u32` width, which fits most source files, the links account for 20
+/// bytes per node. Narrowing the width from the default `usize` is the most
+/// effective way to shrink a tree, followed by using an [`Empty`] index if
+/// spans are not needed. Declaring `type Flags` adds the size of the flags to
+/// every node, which is nothing for the default of `()`.
+///
+/// The last child link is what makes [`Node::last`] constant time, and what
+/// allows iterating over children in reverse without first walking to the
+/// end. Omitting it would save a fifth of the links at the cost of making
+/// those operations proportional to the number of children, which is why it's
+/// always present.
+///
+/// Runs of identical tokens, such as the values of a large data file, are not
+/// compressed either. Every token is a node with an identifier of its own
+/// which can be looked up in constant time with [`Tree::get`], and which other
+/// nodes link to as a sibling or child. Storing a run as a single entry would
+/// leave the elements of the run without identifiers or links to step
+/// between them. If a tree doesn't need to tell the elements of a run apart,
+/// gluing them into a single token with [`Builder::glue_last`] as they are
+/// parsed has the same effect.
+///
+/// When comparing flavors, the `comparisons` benchmark in the `benches`
+/// directory of the repository is a good starting point, since it builds and
+/// walks the same trees with different flavors.
+///
 /// [spans]: crate::Span
+/// [Kind]: crate::Kind
+/// [`Node::last`]: crate::Node::last
+/// [`Node::span`]: crate::Node::span
+/// [`Tree::node_with_span`]: crate::Tree::node_with_span
+/// [`Tree::walk`]: crate::Tree::walk
+/// [`Tree::flat`]: crate::Tree::flat
+/// [`Tree::get`]: crate::Tree::get
+/// [`Builder::glue_last`]: crate::Builder::glue_last
+/// [`Empty`]: crate::Empty
 pub trait Flavor {
     /// The error raised by the type of the tree.
     type Error;
@@ -144,5 +291,21 @@ pub trait Flavor {
     /// The storage type used in the tree.
     type Storage<T>: Storage<T, Error = Self::Error>;
     /// How indexes are stored in the tree.
-    type Indexes: Storage<TreeIndex<Self>, Error = Self::Error>;
+    ///
+    /// See [Indexes] for documentation.
+    type Indexes: Indexes<Self, Error = Self::Error>;
+    /// The flags stored in every node of the tree.
+    ///
+    /// See [Flags] for documentation.
+    type Flags: Flags;
+    /// The unit which spans in the tree are measured in.
+    ///
+    /// See [Unit] for documentation.
+    const UNIT: Unit = Unit::Bytes;
+    /// Whether the cursor of a [Builder][crate::Builder] saturates at the
+    /// largest index instead of erroring with
+    /// [`Error::Overflow`][crate::Error::Overflow] when tokens are added.
+    ///
+    /// See [`Index::saturating_add_len`].
+    const SATURATING: bool = false;
 }
@@ -1,54 +1,306 @@
 //! Types that can be used to refer to indexes in a [Span][crate::Span].
 
 use core::cmp;
+use core::fmt;
 
-use crate::flavor::Flavor;
-
-mod sealed {
-    pub trait Sealed {}
-
-    impl Sealed for u32 {}
-    impl Sealed for usize {}
-    impl Sealed for crate::empty::Empty {}
-}
+use crate::flavor::{Flavor, Storage};
+use crate::pointer::Pointer;
 
 /// A type that can be used when referring to an index in a tree.
 ///
 /// An index is a valid single component of a [Span][crate::Span], valid indexes
-/// are types such as `u32` and `usize`, but also [`Empty`][crate::Empty] in
-/// case indexing is not required.
+/// are types such as `u32`, `u64` and `usize`, but also
+/// [`Empty`][crate::Empty] in case indexing is not required.
 ///
 /// See [Builder::new_with][crate::Builder::new_with].
-pub trait Index: Sized + Copy + cmp::Ord + cmp::Eq + self::sealed::Sealed {
-    #[doc(hidden)]
+///
+/// # Large inputs
+///
+/// Using `u64` as an index allows for inputs which are larger than what can be
+/// addressed with a `u32` or a `usize` on 32-bit targets.
+///
+/// ```
+/// use syntree::Span;
+///
+/// syntree::flavor! {
+///     struct FlavorU64 {
+///         type Index = u64;
+///     }
+/// }
+///
+/// let mut tree = syntree::Builder::<_, FlavorU64>::new_with();
+///
+/// tree.open("root")?;
+/// tree.token("large", 1u64 << 33)?;
+/// tree.token("lit", 2)?;
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// let lit = tree.find(|n| n.value() == "lit").ok_or("missing lit")?;
+/// assert_eq!(lit.span(), Span::new(1 << 33, (1 << 33) + 2));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// # Implementing a custom index
+///
+/// Indexes can be implemented for custom types, such as a newtype which
+/// ensures that byte offsets are not confused with other numbers.
+///
+/// ```
+/// use syntree::{Index, Span};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// struct ByteOffset(u32);
+///
+/// impl Index for ByteOffset {
+///     const EMPTY: Self = ByteOffset(0);
+///
+///     type Length = u32;
+///
+///     fn is_empty(&self) -> bool {
+///         self.0 == 0
+///     }
+///
+///     fn as_usize(self) -> usize {
+///         self.0 as usize
+///     }
+///
+///     fn checked_add_len(self, other: u32) -> Option<Self> {
+///         Some(ByteOffset(self.0.checked_add(other)?))
+///     }
+///
+///     fn len_to(self, other: Self) -> u32 {
+///         other.0.saturating_sub(self.0)
+///     }
+///
+///     fn from_usize(value: usize) -> Option<Self> {
+///         Some(ByteOffset(u32::try_from(value).ok()?))
+///     }
+/// }
+///
+/// syntree::flavor! {
+///     struct FlavorByteOffset {
+///         type Index = ByteOffset;
+///     }
+/// }
+///
+/// let mut tree = syntree::Builder::<_, FlavorByteOffset>::new_with();
+///
+/// tree.open("root")?;
+/// tree.token("lit", 4)?;
+/// tree.token("ws", 1)?;
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// assert_eq!(tree.span(), Span::new(ByteOffset(0), ByteOffset(5)));
+/// assert_eq!(tree.range(), 0..5);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait Index: Sized + Copy + cmp::Ord + cmp::Eq {
+    /// The index at the start of the input.
     const EMPTY: Self;
 
-    #[doc(hidden)]
+    /// The length of a span between two indexes.
     type Length: Length;
 
-    #[doc(hidden)]
+    /// Test if the index is at the start of the input.
     fn is_empty(&self) -> bool;
 
-    #[doc(hidden)]
+    /// Coerce the index into a `usize`, saturating if it doesn't fit.
     fn as_usize(self) -> usize;
 
-    #[doc(hidden)]
+    /// Add a length to the index, returning `None` on overflow.
     fn checked_add_len(self, other: Self::Length) -> Option<Self>;
 
-    #[doc(hidden)]
+    /// Add a length to the index, saturating at the largest representable
+    /// index on overflow.
+    ///
+    /// This is used instead of [`Index::checked_add_len`] by flavors which
+    /// declare `const SATURATING = true`, see [`flavor!`][crate::flavor!].
+    ///
+    /// The default implementation doesn't know what the largest index is, so
+    /// it leaves the index unchanged on overflow. Custom indexes which are used
+    /// with saturating flavors should override it.
+    #[inline]
+    #[must_use]
+    fn saturating_add_len(self, other: Self::Length) -> Self {
+        self.checked_add_len(other).unwrap_or(self)
+    }
+
+    /// Get the length from this index to `other`, saturating at zero if
+    /// `other` comes before this index.
     fn len_to(self, other: Self) -> Self::Length;
 
-    #[doc(hidden)]
+    /// Construct an index from a `usize`, returning `None` if it doesn't fit.
     fn from_usize(value: usize) -> Option<Self>;
 }
 
-#[doc(hidden)]
-pub trait Length: Copy + self::sealed::Sealed {
-    #[doc(hidden)]
+/// The length of a [Span][crate::Span], as returned by
+/// [`Span::len`][crate::Span::len].
+///
+/// This is implemented for `usize`, which is used as the length of `u32` and
+/// `usize` indexes, `u64` for `u64` indexes, and [`Empty`][crate::Empty] for
+/// trees which do not store spans. It's also implemented for `u32`, which can
+/// be used as the length of custom indexes. It provides the arithmetic
+/// necessary to write code which is generic over the [Flavor] of a tree.
+///
+/// Code which is generic over the flavor and has a `usize` length at hand,
+/// such as the length of a string, can convert it with
+/// [`Length::from_usize`]. Methods such as [`Builder::token`] accept any
+/// [`IntoLength`], so plain integers can be passed to them directly.
+///
+/// [`Builder::token`]: crate::Builder::token
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Flavor, Length, Span, Tree};
+///
+/// fn total_len<T, F>(tree: &Tree<T, F>) -> Option<F::Length>
+/// where
+///     T: Copy,
+///     F: Flavor,
+/// {
+///     let mut total = F::Length::EMPTY;
+///
+///     for node in tree.children() {
+///         total = total.checked_add(node.span().len())?;
+///     }
+///
+///     Some(total)
+/// }
+///
+/// let tree = syntree::tree! {
+///     "a" => {
+///         ("lit", 3)
+///     },
+///     ("ws", 1),
+///     "b" => {
+///         ("lit", 2)
+///     },
+/// };
+///
+/// assert_eq!(total_len(&tree), Some(6));
+///
+/// let len = Span::new(2u32, 7u32).len();
+/// assert_eq!(len.saturating_sub(3), 2);
+/// assert_eq!(len.as_usize(), 5);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait Length: Copy + cmp::Ord + cmp::Eq + fmt::Debug + IntoLength<Self> {
+    /// The empty length.
     const EMPTY: Self;
 
-    #[doc(hidden)]
+    /// Test if the length is empty.
     fn is_empty(&self) -> bool;
+
+    /// Add two lengths, returning `None` on overflow.
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// Subtract `other` from this length, saturating at zero.
+    #[must_use]
+    fn saturating_sub(self, other: Self) -> Self;
+
+    /// Coerce the length into a `usize`, saturating if it doesn't fit.
+    fn as_usize(self) -> usize;
+
+    /// Construct a length from a `usize`, returning `None` if it doesn't fit.
+    ///
+    /// Lengths which can't be described by a single `usize`, such as
+    /// [`Dual`][crate::Dual], return `None` for every value including zero.
+    fn from_usize(value: usize) -> Option<Self>;
+
+    /// Construct a length from a `u64`, returning `None` if it doesn't fit.
+    ///
+    /// The default implementation goes through [`Length::from_usize`], which
+    /// lengths that are wider than `usize` on some targets should override.
+    #[inline]
+    fn from_u64(value: u64) -> Option<Self> {
+        Self::from_usize(usize::try_from(value).ok()?)
+    }
+}
+
+/// A value which can be converted into the [Length] `L`, as accepted by
+/// [`Builder::token`].
+///
+/// Every length can be converted into itself, and plain integers can be
+/// converted into any length through [`Length::from_usize`]. This allows
+/// passing integer literals as lengths regardless of the flavor in use, where
+/// the conversion is checked when the token is inserted.
+///
+/// This is also implemented for `i32`, since that is the type integer literals
+/// fall back to when their type can't be inferred. Negative values can't be
+/// converted, and literals which don't fit in an `i32` need a suffix such as
+/// `1u64 << 33`.
+///
+/// [`Builder::token`]: crate::Builder::token
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Builder, Flavor, FlavorDefault, Span, Tree};
+///
+/// syntree::flavor! {
+///     struct FlavorU64 {
+///         type Index = u64;
+///     }
+/// }
+///
+/// fn build<F>() -> Result<Tree<&'static str, F>, Box<dyn core::error::Error>>
+/// where
+///     F: Flavor<Error: core::error::Error + 'static, Pointer: 'static>,
+/// {
+///     let mut tree = Builder::<_, F>::new_with();
+///
+///     tree.open("root")?;
+///     tree.token("lit", 3)?;
+///     tree.token("ws", 1u32)?;
+///     tree.close()?;
+///
+///     Ok(tree.build()?)
+/// }
+///
+/// let tree = build::<FlavorDefault>()?;
+/// assert_eq!(tree.span(), Span::new(0, 4));
+///
+/// let tree = build::<FlavorU64>()?;
+/// assert_eq!(tree.span(), Span::new(0, 4));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait IntoLength<L> {
+    /// Convert into a length, returning `None` if the value can't be
+    /// represented by it.
+    fn into_length(self) -> Option<L>;
+}
+
+macro_rules! into_length {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<L> IntoLength<L> for $ty
+            where
+                L: Length,
+            {
+                #[inline]
+                fn into_length(self) -> Option<L> {
+                    L::from_usize(usize::try_from(self).ok()?)
+                }
+            }
+        )*
+    };
+}
+
+into_length!(u8, u16, u32, usize, i32);
+
+impl<L> IntoLength<L> for u64
+where
+    L: Length,
+{
+    #[inline]
+    fn into_length(self) -> Option<L> {
+        L::from_u64(self)
+    }
 }
 
 impl Length for usize {
@@ -58,6 +310,89 @@ impl Length for usize {
     fn is_empty(&self) -> bool {
         *self == 0
     }
+
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        usize::checked_add(self, other)
+    }
+
+    #[inline]
+    fn saturating_sub(self, other: Self) -> Self {
+        usize::saturating_sub(self, other)
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        self
+    }
+
+    #[inline]
+    fn from_usize(value: usize) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl Length for u32 {
+    const EMPTY: Self = 0;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        *self == 0
+    }
+
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        u32::checked_add(self, other)
+    }
+
+    #[inline]
+    fn saturating_sub(self, other: Self) -> Self {
+        u32::saturating_sub(self, other)
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        self as usize
+    }
+
+    #[inline]
+    fn from_usize(value: usize) -> Option<Self> {
+        u32::try_from(value).ok()
+    }
+}
+
+impl Length for u64 {
+    const EMPTY: Self = 0;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        *self == 0
+    }
+
+    #[inline]
+    fn checked_add(self, other: Self) -> Option<Self> {
+        u64::checked_add(self, other)
+    }
+
+    #[inline]
+    fn saturating_sub(self, other: Self) -> Self {
+        u64::saturating_sub(self, other)
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        usize::try_from(self).unwrap_or(usize::MAX)
+    }
+
+    #[inline]
+    fn from_usize(value: usize) -> Option<Self> {
+        u64::try_from(value).ok()
+    }
+
+    #[inline]
+    fn from_u64(value: u64) -> Option<Self> {
+        Some(value)
+    }
 }
 
 /// Ensure u32 is smaller or equal to usize.
@@ -83,6 +418,11 @@ impl Index for u32 {
         u32::checked_add(self, u32::try_from(other).ok()?)
     }
 
+    #[inline]
+    fn saturating_add_len(self, other: Self::Length) -> Self {
+        u32::saturating_add(self, u32::try_from(other).unwrap_or(u32::MAX))
+    }
+
     #[inline]
     fn len_to(self, other: Self) -> Self::Length {
         other.saturating_sub(self) as usize
@@ -114,6 +454,11 @@ impl Index for usize {
         usize::checked_add(self, other)
     }
 
+    #[inline]
+    fn saturating_add_len(self, other: Self::Length) -> Self {
+        usize::saturating_add(self, other)
+    }
+
     #[inline]
     fn len_to(self, other: Self) -> Self::Length {
         other.saturating_sub(self)
@@ -125,8 +470,44 @@ impl Index for usize {
     }
 }
 
+impl Index for u64 {
+    const EMPTY: Self = 0;
+
+    type Length = u64;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        *self == 0
+    }
+
+    #[inline]
+    fn as_usize(self) -> usize {
+        usize::try_from(self).unwrap_or(usize::MAX)
+    }
+
+    #[inline]
+    fn checked_add_len(self, other: Self::Length) -> Option<Self> {
+        u64::checked_add(self, other)
+    }
+
+    #[inline]
+    fn saturating_add_len(self, other: Self::Length) -> Self {
+        u64::saturating_add(self, other)
+    }
+
+    #[inline]
+    fn len_to(self, other: Self) -> Self::Length {
+        other.saturating_sub(self)
+    }
+
+    #[inline]
+    fn from_usize(value: usize) -> Option<Self> {
+        u64::try_from(value).ok()
+    }
+}
+
 /// A single span index entry for the given [Flavor] `F`.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 #[doc(hidden)]
 pub struct TreeIndex<F>
 where
@@ -135,3 +516,240 @@ where
     pub(crate) index: F::Index,
     pub(crate) id: F::Pointer,
 }
+
+// Implemented manually, since flavors are marker types which don't implement
+// Clone themselves.
+impl<F> Clone for TreeIndex<F>
+where
+    F: ?Sized + Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for TreeIndex<F> where F: ?Sized + Flavor {}
+
+/// A position index used by a tree to look up nodes by their span.
+///
+/// Every time a token with a non-empty span is added to a tree, the offset at
+/// which it ends is pushed to the index together with the identifier of the
+/// token. Queries such as [`Tree::node_with_span`] then use [`Indexes::find`]
+/// to locate the first token which covers a given position.
+///
+/// This is implemented for any [`Storage`] of [`TreeIndex`] entries such as
+/// `Vec<TreeIndex<F>>`, in which case lookups are performed through binary
+/// search. A custom implementation can be plugged into a flavor through the
+/// `type Indexes` declaration of the [`flavor!`] macro.
+///
+/// [`Tree::node_with_span`]: crate::Tree::node_with_span
+/// [`flavor!`]: crate::flavor!
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use std::convert::Infallible;
+///
+/// use syntree::{Flavor, Indexes, Span};
+///
+/// syntree::flavor! {
+///     struct FlavorBTree {
+///         type Index = u32;
+///         type Indexes = BTreeIndexes;
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct BTreeIndexes {
+///     entries: Vec<(u32, <FlavorBTree as Flavor>::Pointer)>,
+///     by_end: BTreeMap<u32, <FlavorBTree as Flavor>::Pointer>,
+/// }
+///
+/// impl Indexes<FlavorBTree> for BTreeIndexes {
+///     const EMPTY: Self = Self {
+///         entries: Vec::new(),
+///         by_end: BTreeMap::new(),
+///     };
+///
+///     type Error = Infallible;
+///
+///     fn push(&mut self, index: u32, id: <FlavorBTree as Flavor>::Pointer) -> Result<(), Infallible> {
+///         self.entries.push((index, id));
+///         self.by_end.insert(index, id);
+///         Ok(())
+///     }
+///
+///     fn find(&self, index: u32) -> Option<<FlavorBTree as Flavor>::Pointer> {
+///         let (_, id) = self.by_end.range(index.saturating_add(1)..).next()?;
+///         Some(*id)
+///     }
+///
+///     fn len(&self) -> usize {
+///         self.entries.len()
+///     }
+///
+///     fn get(&self, n: usize) -> Option<(u32, <FlavorBTree as Flavor>::Pointer)> {
+///         self.entries.get(n).copied()
+///     }
+///
+///     fn truncate(&mut self, len: usize) {
+///         for (index, id) in self.entries.drain(len.min(self.entries.len())..) {
+///             if self.by_end.get(&index) == Some(&id) {
+///                 self.by_end.remove(&index);
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut tree = syntree::Builder::<_, FlavorBTree>::new_with();
+///
+/// tree.open("root")?;
+/// tree.open("number")?;
+/// tree.token("lit", 3)?;
+/// tree.close()?;
+/// tree.open("ident")?;
+/// tree.token("lit", 2)?;
+/// tree.close()?;
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// let node = tree.node_with_span(Span::new(3, 4)).ok_or("missing ident")?;
+/// assert_eq!(node.value(), "ident");
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub trait Indexes<F>: Sized
+where
+    F: ?Sized + Flavor,
+{
+    /// An empty index.
+    const EMPTY: Self;
+
+    /// The error raised when the index cannot be grown.
+    type Error: 'static;
+
+    /// Construct an index with capacity for at least `capacity` entries.
+    ///
+    /// The default implementation ignores the capacity and returns
+    /// [`Indexes::EMPTY`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the capacity cannot be allocated.
+    #[inline]
+    fn with_capacity(capacity: usize) -> Result<Self, Self::Error> {
+        let _ = capacity;
+        Ok(Self::EMPTY)
+    }
+
+    /// Push the end `index` of the token identified by `id`.
+    ///
+    /// Entries are always pushed in the order in which tokens are inserted
+    /// into the tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the index cannot be grown.
+    fn push(&mut self, index: F::Index, id: F::Pointer) -> Result<(), Self::Error>;
+
+    /// Find the first token which ends after the given `index`.
+    fn find(&self, index: F::Index) -> Option<F::Pointer>;
+
+    /// Find the position of the first entry which ends at or after the given
+    /// `index`, or [`Indexes::len`] if there is no such entry.
+    ///
+    /// The default implementation scans the entries in insertion order
+    /// through [`Indexes::get`].
+    #[inline]
+    fn lower_bound(&self, index: F::Index) -> usize {
+        let len = self.len();
+
+        (0..len)
+            .find(|&n| matches!(self.get(n), Some((at, _)) if at >= index))
+            .unwrap_or(len)
+    }
+
+    /// The number of entries in the index.
+    fn len(&self) -> usize;
+
+    /// Test if the index is empty.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the `n`th entry in the index in insertion order.
+    fn get(&self, n: usize) -> Option<(F::Index, F::Pointer)>;
+
+    /// Shorten the index to its first `len` entries in insertion order.
+    ///
+    /// This has no effect if the index already has fewer than `len` entries.
+    fn truncate(&mut self, len: usize);
+
+    /// Sort the entries by the offset at which they end, and then by
+    /// identifier.
+    ///
+    /// This is used when tokens might have been inserted out of order, such as
+    /// with [`Builder::set_explicit_spans`]. The default implementation does
+    /// nothing.
+    ///
+    /// [`Builder::set_explicit_spans`]: crate::Builder::set_explicit_spans
+    #[inline]
+    fn sort(&mut self) {}
+}
+
+impl<F, S> Indexes<F> for S
+where
+    F: ?Sized + Flavor,
+    S: Storage<TreeIndex<F>>,
+{
+    const EMPTY: Self = <S as Storage<TreeIndex<F>>>::EMPTY;
+
+    type Error = <S as Storage<TreeIndex<F>>>::Error;
+
+    #[inline]
+    fn with_capacity(capacity: usize) -> Result<Self, Self::Error> {
+        <S as Storage<TreeIndex<F>>>::with_capacity(capacity)
+    }
+
+    #[inline]
+    fn push(&mut self, index: F::Index, id: F::Pointer) -> Result<(), Self::Error> {
+        Storage::push(self, TreeIndex { index, id })
+    }
+
+    #[inline]
+    fn find(&self, index: F::Index) -> Option<F::Pointer> {
+        // Empty tokens might share their offset with the token before them,
+        // so the first entry past all of them is used.
+        let n = self.partition_point(|f| f.index <= index);
+        Some(<[TreeIndex<F>]>::get(self, n)?.id)
+    }
+
+    #[inline]
+    fn lower_bound(&self, index: F::Index) -> usize {
+        self.partition_point(|f| f.index < index)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        <[TreeIndex<F>]>::len(self)
+    }
+
+    #[inline]
+    fn get(&self, n: usize) -> Option<(F::Index, F::Pointer)> {
+        let index = <[TreeIndex<F>]>::get(self, n)?;
+        Some((index.index, index.id))
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Storage::truncate(self, len);
+    }
+
+    #[inline]
+    fn sort(&mut self) {
+        self.sort_unstable_by_key(|f| (f.index, f.id.get()));
+    }
+}
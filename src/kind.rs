@@ -0,0 +1,38 @@
+/// The kind of a node in the [Tree][crate::Tree].
+///
+/// This is recorded when the node is inserted, so that nodes which happen to
+/// not have any children can be distinguished from tokens.
+///
+/// See [`Node::kind`][crate::Node::kind].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Kind;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "empty" => {},
+///         "token"
+///     }
+/// };
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// let empty = root.first().ok_or("missing empty")?;
+/// let token = root.last().ok_or("missing token")?;
+///
+/// assert_eq!(root.kind(), Kind::Node);
+/// assert_eq!(empty.kind(), Kind::Node);
+/// assert_eq!(token.kind(), Kind::Token);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// A node, as constructed through for example
+    /// [`Builder::open`][crate::Builder::open] or
+    /// [`Builder::close_at`][crate::Builder::close_at].
+    Node,
+    /// A token, as constructed through for example
+    /// [`Builder::token`][crate::Builder::token].
+    Token,
+}
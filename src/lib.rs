@@ -363,41 +363,86 @@ extern crate std;
 
 #[macro_use]
 mod macros;
+
+#[cfg(feature = "bench-support")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bench-support")))]
+pub mod bench_support;
+
 mod builder;
 
 #[cfg(feature = "std")]
 pub mod edit;
 
+mod dual;
 mod empty;
 mod error;
+mod flags;
 #[macro_use]
 mod flavor;
 mod index;
+mod kind;
 mod links;
+#[cfg(feature = "alloc")]
+pub mod merge;
 pub mod node;
+pub mod parse;
 pub mod pointer;
 pub mod print;
+#[cfg(feature = "alloc")]
+pub mod rewrite;
 mod span;
 mod tree;
+mod unit;
 
+#[cfg(feature = "instrument")]
+#[cfg_attr(docsrs, doc(cfg(feature = "instrument")))]
+#[doc(inline)]
+pub use self::builder::Metrics;
 #[doc(inline)]
-pub use self::builder::{Builder, Checkpoint};
+pub use self::builder::{
+    Builder, Checkpoint, DualBuilder, DualCheckpoint, Expecting, FixedCheckpoint, NodeGuard,
+    PendingNode, TreeSink,
+};
+#[doc(inline)]
+pub use self::dual::Dual;
 #[doc(inline)]
 pub use self::empty::{Empty, EmptyVec};
 #[doc(inline)]
 pub use self::error::Error;
 #[doc(inline)]
+pub use self::flags::Flags;
+#[doc(inline)]
 pub use self::flavor::{Flavor, FlavorDefault, Storage};
 #[doc(inline)]
-pub use self::index::{Index, Length, TreeIndex};
+pub use self::index::{Index, Indexes, IntoLength, Length, TreeIndex};
+#[doc(inline)]
+pub use self::kind::Kind;
+#[cfg(feature = "alloc")]
+#[doc(inline)]
+pub use self::merge::merge;
 #[doc(inline)]
 pub use self::node::node_impl::Node;
 #[doc(inline)]
 pub use self::pointer::{Pointer, Width};
 #[doc(inline)]
 pub use self::span::Span;
+#[cfg(feature = "alloc")]
+#[doc(inline)]
+pub use self::tree::{
+    EulerTour, IndexBy, IndexByIter, NodeMap, NodeMapIter, QueryCtx, SortedBySpan, Trivia,
+};
+#[doc(inline)]
+pub use self::tree::{
+    FindAll, Flat, IntoIter, LinkInfo, NodeId, PathTo, RawNode, SourceMismatch, TokenOffsets, Tree,
+    TreeRaw, ValidationError,
+};
+#[doc(inline)]
+pub use self::unit::Unit;
+
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
 #[doc(inline)]
-pub use self::tree::Tree;
+pub use syntree_macros::Syntax;
 
 #[doc(hidden)]
 pub mod macro_support {
@@ -410,4 +455,85 @@ pub mod macro_support {
     pub type Vec<T> = crate::empty::EmptyVec<T>;
 
     pub type DefaultIndexes<F> = crate::macro_support::Vec<TreeIndex<F>>;
+
+    /// Build a tree using the builder `b` by calling `f`.
+    ///
+    /// Constructing the tree through a function like this means that the
+    /// errors raised inside of `f` can be inferred from the builder.
+    pub fn build<T, F, C>(
+        mut b: crate::Builder<T, F>,
+        f: C,
+    ) -> Result<crate::Tree<T, F>, crate::Error<F::Error, F::Pointer>>
+    where
+        T: Copy,
+        F: crate::Flavor,
+        C: FnOnce(&mut crate::Builder<T, F>) -> Result<(), crate::Error<F::Error, F::Pointer>>,
+    {
+        f(&mut b)?;
+        b.build()
+    }
+
+    /// Unwrap the result of building a tree, panicking with the error if it
+    /// failed.
+    #[track_caller]
+    pub fn unwrap<T, F>(
+        result: Result<crate::Tree<T, F>, crate::Error<F::Error, F::Pointer>>,
+    ) -> crate::Tree<T, F>
+    where
+        T: Copy,
+        F: crate::Flavor,
+    {
+        match result {
+            Ok(tree) => tree,
+            Err(crate::Error::Flavor(..)) => panic!("failed to build tree: flavor error"),
+            Err(error) => panic!("failed to build tree: {}", error.map_flavor(|_| "")),
+        }
+    }
+
+    /// Assert that the node `id` which has been built by `b` has the
+    /// `expected` span, panicking if it doesn't.
+    #[track_caller]
+    pub fn expect_span<T, F>(
+        b: &crate::Builder<T, F>,
+        id: F::Pointer,
+        expected: crate::Span<F::Index>,
+    ) -> Result<(), crate::Error<F::Error, F::Pointer>>
+    where
+        T: Copy,
+        F: crate::Flavor<Index: core::fmt::Display>,
+    {
+        let Some(node) = b.tree().get(id) else {
+            panic!("expected a node with the span {expected}, but it is missing");
+        };
+
+        let actual = node.span();
+
+        if *actual != expected {
+            panic!("expected a node with the span {expected}, but it has the span {actual}");
+        }
+
+        Ok(())
+    }
+
+    /// Check that the node `id` which has been built by `b` has the
+    /// `expected` span, erroring if it doesn't.
+    pub fn try_expect_span<T, F>(
+        b: &crate::Builder<T, F>,
+        id: F::Pointer,
+        expected: crate::Span<F::Index>,
+    ) -> Result<(), crate::Error<F::Error, F::Pointer>>
+    where
+        T: Copy,
+        F: crate::Flavor,
+    {
+        let Some(node) = b.tree().get(id) else {
+            return Err(crate::Error::MissingNode(id));
+        };
+
+        if *node.span() != expected {
+            return Err(crate::Error::UnexpectedSpan(id));
+        }
+
+        Ok(())
+    }
 }
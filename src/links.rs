@@ -2,15 +2,23 @@
 
 use core::cell::Cell;
 
+use crate::flavor::Flavor;
+use crate::kind::Kind;
 use crate::span::Span;
 
+/// The links stored for every node in a tree with the flavor `F`.
+pub(crate) type LinksOf<T, F> =
+    Links<T, <F as Flavor>::Index, <F as Flavor>::Pointer, <F as Flavor>::Flags>;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct Links<T, I, P>
+pub(crate) struct Links<T, I, P, G = ()>
 where
     T: Copy,
 {
     /// The data in the node.
     pub(crate) data: Cell<T>,
+    /// The kind of the node.
+    pub(crate) kind: Kind,
     /// Span of the node.
     pub(crate) span: Span<I>,
     /// Parent node. These exists because they are needed when performing range
@@ -24,25 +32,39 @@ where
     pub(crate) first: Option<P>,
     /// Last child node.
     pub(crate) last: Option<P>,
+    /// Flags set on the node.
+    pub(crate) flags: G,
 }
 
 // These tests might not always pass, due to alignment. But it's nice to ensure
 #[test]
 fn test_size() {
     macro_rules! test {
-        ($data:ty, $index:ty, $width:ty, $max_align:expr) => {
-            assert!(
-                (
-                    std::mem::size_of::<Links<$data, $index, <$width as crate::pointer::Width>::Pointer>>() as isize
-                    -
-                    (std::mem::size_of::<$data>() as isize + ((<$index>::BITS * 2) / 8) as isize + ((<$width>::BITS * 5) / 8) as isize)
-                ).abs() <= $max_align
-            );
-        }
+        ($data:ty, $index:ty, $width:ty) => {
+            let size = std::mem::size_of::<
+                Links<$data, $index, <$width as crate::pointer::Width>::Pointer>,
+            >();
+            let align = std::mem::align_of::<
+                Links<$data, $index, <$width as crate::pointer::Width>::Pointer>,
+            >();
+
+            let fields = std::mem::size_of::<$data>()
+                + std::mem::size_of::<Kind>()
+                + ((<$index>::BITS * 2) / 8) as usize
+                + ((<$width>::BITS * 5) / 8) as usize;
+
+            assert_eq!(size, fields.next_multiple_of(align));
+        };
     }
 
-    test!([u8; 8], u32, u16, 4);
-    test!([u8; 8], u32, u32, 4);
-    test!([u8; 8], u32, u64, 4);
-    test!([u8; 8], u32, u128, 4);
+    assert_eq!(std::mem::size_of::<Kind>(), 1);
+
+    // NB: The kind of a node is stored in a byte of its own. With data that
+    // fills up its alignment, such as the 8 bytes used here, this grows every
+    // node by a full alignment unit, which is 4 bytes with a `u32` width and 8
+    // bytes with a `u64` width.
+    test!([u8; 8], u32, u16);
+    test!([u8; 8], u32, u32);
+    test!([u8; 8], u32, u64);
+    test!([u8; 8], u32, u128);
 }
@@ -1,5 +1,9 @@
 /// Helper macro for building a tree in place.
 ///
+/// This panics if the tree can't be built, so it can be used outside of
+/// functions which return a `Result`. To handle the error instead, use
+/// [`try_tree!`][crate::try_tree].
+///
 /// # Examples
 ///
 /// ```
@@ -46,72 +50,161 @@
 /// assert_eq!(expected, tree);
 /// # Ok::<_,  Box<dyn core::error::Error>>(())
 /// ```
+///
+/// Nodes can be annotated with the span they are expected to cover using
+/// `(value, expect = (start, end))`. This is checked once the node has been
+/// closed, and construction panics with a message describing both spans if
+/// they differ. With [`try_tree!`][crate::try_tree] the mismatch is instead
+/// returned as [`Error::UnexpectedSpan`][crate::Error::UnexpectedSpan]:
+///
+/// ```
+/// let tree = syntree::tree! {
+///     ("root", expect = (0, 4)) => {
+///         ("number", expect = (0, 3)) => {
+///             ("lit", 3)
+///         },
+///         ("ws", 1),
+///     }
+/// };
+///
+/// assert_eq!(tree.span(), syntree::Span::new(0, 4));
+/// # Ok::<_,  Box<dyn core::error::Error>>(())
+/// ```
+///
+/// ```should_panic
+/// let tree = syntree::tree! {
+///     ("number", expect = (0, 2)) => {
+///         ("lit", 3)
+///     }
+/// };
+/// # Ok::<_,  Box<dyn core::error::Error>>(())
+/// ```
 #[macro_export]
 macro_rules! tree {
-    (@o $b:ident,) => {};
+    (@o $m:ident $b:ident,) => {};
 
-    (@o $b:ident, ($expr:expr, ($start:expr, $end:expr)) $(,)?) => {{
+    (@o $m:ident $b:ident, ($expr:expr, ($start:expr, $end:expr)) $(,)?) => {{
         $b.token_with($expr, $crate::Span::new($start, $end))?;
     }};
 
-    (@o $b:ident, ($expr:expr, $len:expr) $(,)?) => {{
+    (@o $m:ident $b:ident, ($expr:expr, $len:expr) $(,)?) => {{
         $b.token($expr, $len)?;
     }};
 
-    (@o $b:ident, ($expr:expr, ($start:expr, $end:expr)), $($rest:tt)*) => {{
+    (@o $m:ident $b:ident, ($expr:expr, ($start:expr, $end:expr)), $($rest:tt)*) => {{
         $b.token_with($expr, $crate::Span::new($start, $end))?;
-        $crate::tree!(@o $b, $($rest)*);
+        $crate::tree!(@o $m $b, $($rest)*);
     }};
 
-    (@o $b:ident, ($expr:expr, $len:expr), $($rest:tt)*) => {{
+    (@o $m:ident $b:ident, ($expr:expr, $len:expr), $($rest:tt)*) => {{
         $b.token($expr, $len)?;
-        $crate::tree!(@o $b, $($rest)*);
+        $crate::tree!(@o $m $b, $($rest)*);
     }};
 
-    (@o $b:ident, $expr:expr $(,)?) => {{
+    (@o $m:ident $b:ident, $expr:expr $(,)?) => {{
         $b.token_empty($expr)?;
     }};
 
-    (@o $b:ident, $expr:expr, $($rest:tt)*) => {{
+    (@o $m:ident $b:ident, $expr:expr, $($rest:tt)*) => {{
         $b.token_empty($expr)?;
-        $crate::tree!(@o $b, $($rest)*);
+        $crate::tree!(@o $m $b, $($rest)*);
     }};
 
-    (@o $b:ident, ($expr:expr, ($start:expr, $end:expr)) => { $($tt:tt)* } $(,)?) => {{
+    (@o $m:ident $b:ident, ($expr:expr, expect = ($start:expr, $end:expr)) => { $($tt:tt)* } $(,)?) => {{
+        let id = $b.open($expr)?;
+        $crate::tree!(@o $m $b, $($tt)*);
+        $b.close()?;
+        $crate::macro_support::$m($b, id, $crate::Span::new($start, $end))?;
+    }};
+
+    (@o $m:ident $b:ident, ($expr:expr, expect = ($start:expr, $end:expr)) => { $($tt:tt)* }, $($rest:tt)*) => {{
+        let id = $b.open($expr)?;
+        $crate::tree!(@o $m $b, $($tt)*);
+        $b.close()?;
+        $crate::macro_support::$m($b, id, $crate::Span::new($start, $end))?;
+        $crate::tree!(@o $m $b, $($rest)*);
+    }};
+
+    (@o $m:ident $b:ident, ($expr:expr, ($start:expr, $end:expr)) => { $($tt:tt)* } $(,)?) => {{
         $b.open_with($expr, $crate::Span::new($start, $end))?;
-        $crate::tree!(@o $b, $($tt)*);
+        $crate::tree!(@o $m $b, $($tt)*);
         $b.close()?;
     }};
 
-    (@o $b:ident, $expr:expr => { $($tt:tt)* } $(,)?) => {{
+    (@o $m:ident $b:ident, $expr:expr => { $($tt:tt)* } $(,)?) => {{
         $b.open($expr)?;
-        $crate::tree!(@o $b, $($tt)*);
+        $crate::tree!(@o $m $b, $($tt)*);
         $b.close()?;
     }};
 
-    (@o $b:ident, ($expr:expr, ($start:expr, $end:expr)) => { $($tt:tt)* }, $($rest:tt)*) => {{
+    (@o $m:ident $b:ident, ($expr:expr, ($start:expr, $end:expr)) => { $($tt:tt)* }, $($rest:tt)*) => {{
         $b.open_with($expr, $crate::Span::new($start, $end))?;
-        $crate::tree!(@o $b, $($tt)*);
+        $crate::tree!(@o $m $b, $($tt)*);
         $b.close()?;
-        $crate::tree!(@o $b, $($rest)*);
+        $crate::tree!(@o $m $b, $($rest)*);
     }};
 
-    (@o $b:ident, $expr:expr => { $($tt:tt)* }, $($rest:tt)*) => {{
+    (@o $m:ident $b:ident, $expr:expr => { $($tt:tt)* }, $($rest:tt)*) => {{
         $b.open($expr)?;
-        $crate::tree!(@o $b, $($tt)*);
+        $crate::tree!(@o $m $b, $($tt)*);
         $b.close()?;
-        $crate::tree!(@o $b, $($rest)*);
+        $crate::tree!(@o $m $b, $($rest)*);
     }};
 
-    ($($tt:tt)*) => {{
-        let mut b = $crate::Builder::new();
-        $crate::tree!(@o b, $($tt)*);
-        b.build()?
-    }};
+    ($($tt:tt)*) => {
+        $crate::macro_support::unwrap($crate::macro_support::build($crate::Builder::new(), |b| {
+            $crate::tree!(@o expect_span b, $($tt)*);
+            Ok(())
+        }))
+    };
+}
+
+/// Helper macro for building a tree in place, returning a `Result` instead of
+/// panicking if the tree can't be built.
+///
+/// This uses the same syntax as [`tree!`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Span;
+///
+/// let tree = syntree::try_tree! {
+///     "root" => {
+///         ("number", 3),
+///     }
+/// };
+///
+/// assert_eq!(tree.map(|t| *t.span()), Ok(Span::new(0, 3)));
+/// ```
+///
+/// Errors raised while building the tree are returned:
+///
+/// ```
+/// use syntree::Error;
+///
+/// let tree = syntree::try_tree! {
+///     ("lit", u32::MAX as usize),
+///     ("lit", 1),
+/// };
+///
+/// assert_eq!(tree, Err(Error::Overflow));
+/// ```
+#[macro_export]
+macro_rules! try_tree {
+    ($($tt:tt)*) => {
+        $crate::macro_support::build($crate::Builder::new(), |b| {
+            $crate::tree!(@o try_expect_span b, $($tt)*);
+            Ok(())
+        })
+    };
 }
 
 /// Helper macro for building a tree in place with a custom span.
 ///
+/// Like [`tree!`], this panics if the tree can't be built. To handle the error
+/// instead, use [`try_tree_with!`][crate::try_tree_with].
+///
 /// # Examples
 ///
 /// ```
@@ -147,9 +240,205 @@ macro_rules! tree {
 /// ```
 #[macro_export]
 macro_rules! tree_with {
-    ($($tt:tt)*) => {{
-        let mut b = $crate::Builder::new_with();
-        $crate::tree!(@o b, $($tt)*);
-        b.build()?
+    ($($tt:tt)*) => {
+        $crate::macro_support::unwrap($crate::macro_support::build($crate::Builder::new_with(), |b| {
+            $crate::tree!(@o expect_span b, $($tt)*);
+            Ok(())
+        }))
+    };
+}
+
+/// Helper macro for building a tree in place with a custom span, returning a
+/// `Result` instead of panicking if the tree can't be built.
+///
+/// This uses the same syntax as [`tree!`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Empty, EmptyVec, Tree, TreeIndex};
+///
+/// syntree::flavor! {
+///     struct FlavorEmpty {
+///         type Index = Empty;
+///         type Indexes = EmptyVec<TreeIndex<Self>>;
+///     }
+/// };
+///
+/// let tree: Result<Tree<_, FlavorEmpty>, _> = syntree::try_tree_with! {
+///     "root" => {
+///         "child" => {
+///             "token"
+///         },
+///     }
+/// };
+///
+/// assert!(tree.is_ok());
+/// ```
+#[macro_export]
+macro_rules! try_tree_with {
+    ($($tt:tt)*) => {
+        $crate::macro_support::build($crate::Builder::new_with(), |b| {
+            $crate::tree!(@o try_expect_span b, $($tt)*);
+            Ok(())
+        })
+    };
+}
+
+/// Helper macro for matching a node against the shape of a subtree.
+///
+/// The pattern uses a syntax similar to [`tree!`], where each value is a
+/// pattern matched against the value of the corresponding node:
+///
+/// * `Value` matches a node whose value matches the pattern `Value`,
+///   regardless of its children.
+/// * `Value => { .. }` additionally matches each child in order against the
+///   patterns inside of the braces. All children must be matched unless the
+///   list ends with `..`.
+/// * `name @ Value` binds the matched [Node][crate::Node] to `name`.
+///
+/// If the node matches, the expression following the pattern is evaluated
+/// with all bindings in scope and its result is returned as `Some`.
+/// Otherwise this evaluates to `None`.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Syntax {
+///     Operation,
+///     Operator,
+///     Plus,
+///     Minus,
+///     Number,
+/// }
+///
+/// use Syntax::*;
+///
+/// let source = "12+3";
+///
+/// let tree = syntree::tree! {
+///     Operation => {
+///         (Number, 2),
+///         Operator => {
+///             (Plus, 1)
+///         },
+///         (Number, 1),
+///     }
+/// };
+///
+/// let node = tree.first().ok_or("missing operation")?;
+///
+/// let result = syntree::match_tree! {
+///     node,
+///     Operation => { lhs @ Number, Operator => { op @ (Plus | Minus) }, rhs @ Number } => {
+///         let lhs = source[lhs.range()].parse::<i64>()?;
+///         let rhs = source[rhs.range()].parse::<i64>()?;
+///
+///         match op.value() {
+///             Plus => lhs + rhs,
+///             _ => lhs - rhs,
+///         }
+///     }
+/// };
+///
+/// assert_eq!(result, Some(15));
+///
+/// let number = syntree::match_tree!(node, Operation => { n @ Number, .. } => n.range());
+/// assert_eq!(number, Some(0..2));
+///
+/// assert!(syntree::match_tree!(node, Operation => { Number } => ()).is_none());
+/// assert!(syntree::match_tree!(node, Number => ()).is_none());
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+#[macro_export]
+macro_rules! match_tree {
+    (@check $l:lifetime, $n:ident, $value:pat) => {
+        if !::core::matches!($n.value(), $value) {
+            break $l ::core::option::Option::None;
+        }
+    };
+
+    (@check $l:lifetime, $n:ident, $value:pat, [$($children:tt)*]) => {
+        $crate::match_tree!(@check $l, $n, $value);
+        let _cursor = $n.first();
+        $crate::match_tree!(@children $l, _cursor, [$($children)*]);
+    };
+
+    (@children $l:lifetime, $c:ident, []) => {
+        if $c.is_some() {
+            break $l ::core::option::Option::None;
+        }
+    };
+
+    (@children $l:lifetime, $c:ident, [.. $(,)?]) => {};
+
+    (@children $l:lifetime, $c:ident, [$name:ident @ $value:pat => { $($children:tt)* } $(, $($rest:tt)*)?]) => {
+        let ::core::option::Option::Some($name) = $c else {
+            break $l ::core::option::Option::None;
+        };
+
+        $crate::match_tree!(@check $l, $name, $value, [$($children)*]);
+        let $c = $name.next();
+        $crate::match_tree!(@children $l, $c, [$($($rest)*)?]);
+    };
+
+    (@children $l:lifetime, $c:ident, [$name:ident @ $value:pat $(, $($rest:tt)*)?]) => {
+        let ::core::option::Option::Some($name) = $c else {
+            break $l ::core::option::Option::None;
+        };
+
+        $crate::match_tree!(@check $l, $name, $value);
+        let $c = $name.next();
+        $crate::match_tree!(@children $l, $c, [$($($rest)*)?]);
+    };
+
+    (@children $l:lifetime, $c:ident, [$value:pat => { $($children:tt)* } $(, $($rest:tt)*)?]) => {
+        let ::core::option::Option::Some(node) = $c else {
+            break $l ::core::option::Option::None;
+        };
+
+        $crate::match_tree!(@check $l, node, $value, [$($children)*]);
+        let $c = node.next();
+        $crate::match_tree!(@children $l, $c, [$($($rest)*)?]);
+    };
+
+    (@children $l:lifetime, $c:ident, [$value:pat $(, $($rest:tt)*)?]) => {
+        let ::core::option::Option::Some(node) = $c else {
+            break $l ::core::option::Option::None;
+        };
+
+        $crate::match_tree!(@check $l, node, $value);
+        let $c = node.next();
+        $crate::match_tree!(@children $l, $c, [$($($rest)*)?]);
+    };
+
+    (@top $l:lifetime, $n:ident, $name:ident @ $value:pat => { $($children:tt)* } => $body:expr) => {{
+        let $name = $n;
+        $crate::match_tree!(@check $l, $n, $value, [$($children)*]);
+        ::core::option::Option::Some($body)
     }};
+
+    (@top $l:lifetime, $n:ident, $name:ident @ $value:pat => $body:expr) => {{
+        let $name = $n;
+        $crate::match_tree!(@check $l, $n, $value);
+        ::core::option::Option::Some($body)
+    }};
+
+    (@top $l:lifetime, $n:ident, $value:pat => { $($children:tt)* } => $body:expr) => {{
+        $crate::match_tree!(@check $l, $n, $value, [$($children)*]);
+        ::core::option::Option::Some($body)
+    }};
+
+    (@top $l:lifetime, $n:ident, $value:pat => $body:expr) => {{
+        $crate::match_tree!(@check $l, $n, $value);
+        ::core::option::Option::Some($body)
+    }};
+
+    ($node:expr, $($pattern:tt)*) => {
+        'match_tree: {
+            let node = $node;
+            $crate::match_tree!(@top 'match_tree, node, $($pattern)*)
+        }
+    };
 }
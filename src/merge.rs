@@ -0,0 +1,463 @@
+//! Merging trees which were built over the same source.
+//!
+//! Separate passes over the same source, such as a parser and a pass which
+//! marks up comments or macro invocations, each produce a tree of their own.
+//! [`merge`] combines two such trees into one by nesting nodes by the spans
+//! they cover, while preserving the relative order and nesting of the nodes in
+//! each tree.
+//!
+//! Nodes from the two trees which can't be nested in each other, such as
+//! nodes which partially overlap or tokens which cover the same text, are
+//! reported as a [`Conflict`] to a resolver which decides which of the two
+//! should be kept through a [`Choice`]. A node which isn't kept is removed
+//! while its children are kept in its place, and a token which isn't kept is
+//! removed.
+//!
+//! # Examples
+//!
+//! ```
+//! use syntree::merge::Choice;
+//!
+//! // A tree produced by a parser.
+//! let parsed = syntree::tree! {
+//!     "fn" => {
+//!         ("kw", 2),
+//!         ("ws", 1),
+//!         ("ident", 4),
+//!         ("ws", 1),
+//!         "block" => {
+//!             ("open", 1),
+//!             ("close", 1),
+//!         }
+//!     }
+//! };
+//!
+//! // A tree produced by a pass which highlights a region of the source.
+//! let highlights = syntree::tree! {
+//!     ("text", 3),
+//!     "highlight" => {
+//!         ("text", 4)
+//!     },
+//!     ("text", 3),
+//! };
+//!
+//! let mut conflicts = Vec::new();
+//!
+//! // The resolver keeps tokens from the parsed tree.
+//! let tree = syntree::merge(&parsed, &highlights, |c| {
+//!     conflicts.push((c.a().value(), c.b().value()));
+//!     Choice::A
+//! })?;
+//!
+//! let expected = syntree::tree! {
+//!     "fn" => {
+//!         ("kw", 2),
+//!         ("ws", 1),
+//!         "highlight" => {
+//!             ("ident", 4),
+//!         },
+//!         ("ws", 1),
+//!         "block" => {
+//!             ("open", 1),
+//!             ("close", 1),
+//!         }
+//!     }
+//! };
+//!
+//! assert_eq!(tree, expected);
+//! assert_eq!(conflicts, [("kw", "text"), ("ident", "text"), ("ws", "text")]);
+//! # Ok::<_, Box<dyn core::error::Error>>(())
+//! ```
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::builder::Builder;
+use crate::error::{BuildResult, Error};
+use crate::flavor::Flavor;
+use crate::kind::Kind;
+use crate::node::Node;
+use crate::tree::Tree;
+
+/// A pair of nodes from the trees being merged which can't both be kept.
+///
+/// See the [module level documentation][self].
+pub struct Conflict<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    a: Node<'a, T, F>,
+    b: Node<'a, T, F>,
+}
+
+impl<'a, T, F> Conflict<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// The conflicting node from the first tree.
+    #[must_use]
+    pub const fn a(&self) -> Node<'a, T, F> {
+        self.a
+    }
+
+    /// The conflicting node from the second tree.
+    #[must_use]
+    pub const fn b(&self) -> Node<'a, T, F> {
+        self.b
+    }
+}
+
+impl<T, F> Clone for Conflict<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for Conflict<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> fmt::Debug for Conflict<'_, T, F>
+where
+    T: Copy + fmt::Debug,
+    F: Flavor<Index: fmt::Debug>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Conflict")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
+
+/// Which node of a [`Conflict`] to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Choice {
+    /// Keep the node from the first tree.
+    A,
+    /// Keep the node from the second tree.
+    B,
+}
+
+/// Merge two trees built over the same source by nesting their nodes by the
+/// spans they cover, calling `resolve` for every pair of nodes which can't be
+/// nested in each other.
+///
+/// A node contains another if the other node is within its span. If two nodes
+/// cover the same span, the node from `a` contains the node from `b`. Tokens
+/// can't contain other nodes, so tokens from the two trees which overlap are
+/// always in conflict.
+///
+/// The spans of nodes are kept as they are in the trees being merged, and the
+/// merged tree has the [source id][Tree::source_id] of `a`.
+///
+/// See the [module level documentation][self] for an example.
+///
+/// # Errors
+///
+/// Errors with [`Error::Overflow`] in case we run out of node identifiers.
+pub fn merge<T, F, R>(a: &Tree<T, F>, b: &Tree<T, F>, mut resolve: R) -> BuildResult<Tree<T, F>, F>
+where
+    T: Copy,
+    F: Flavor,
+    R: FnMut(Conflict<'_, T, F>) -> Choice,
+{
+    let mut ops = Vec::new();
+    let mut frames = Vec::<Frame<'_, T, F>>::new();
+
+    let mut current = Frame {
+        container: None,
+        a: Cursor::new(a.first()),
+        b: Cursor::new(b.first()),
+    };
+
+    loop {
+        let mut x = current.a.head();
+        let mut y = current.b.head();
+
+        // Nodes on the other side of the container which are not inside of it
+        // are either after it, or overlap it.
+        if let Some(c) = current.container {
+            let (other, head) = match c.side {
+                Side::A => (&mut current.b, &mut y),
+                Side::B => (&mut current.a, &mut x),
+            };
+
+            if let Some(node) = head.filter(|node| !contains(c.node, *node)) {
+                if node.span().start >= c.node.span().end {
+                    *head = None;
+                } else {
+                    let keep = match c.side {
+                        Side::A => resolve(Conflict { a: c.node, b: node }),
+                        Side::B => resolve(Conflict { a: node, b: c.node }),
+                    };
+
+                    if matches!((keep, c.side), (Choice::A, Side::A) | (Choice::B, Side::B)) {
+                        other.unwrap();
+                    } else {
+                        // Remove the container, which leaves its children
+                        // where they are.
+                        ops[c.op] = None;
+                        let mut parent = frames.pop().ok_or(Error::CloseError)?;
+
+                        match c.side {
+                            Side::A => {
+                                parent.a.replace(current.a);
+                                parent.b = current.b;
+                            }
+                            Side::B => {
+                                parent.a = current.a;
+                                parent.b.replace(current.b);
+                            }
+                        }
+
+                        current = parent;
+                    }
+
+                    continue;
+                }
+            }
+        }
+
+        let (side, node) = match (x, y) {
+            (None, None) => {
+                let Some(mut parent) = frames.pop() else {
+                    break;
+                };
+
+                let c = current.container.ok_or(Error::CloseError)?;
+                ops.push(Some(Op::Close));
+
+                match c.side {
+                    Side::A => {
+                        parent.a.advance();
+                        parent.b = current.b;
+                    }
+                    Side::B => {
+                        parent.a = current.a;
+                        parent.b.advance();
+                    }
+                }
+
+                current = parent;
+                continue;
+            }
+            (Some(x), None) => (Side::A, x),
+            (None, Some(y)) => (Side::B, y),
+            (Some(x), Some(y)) => {
+                if contains(x, y) || x.span().end <= y.span().start {
+                    (Side::A, x)
+                } else if contains(y, x) || y.span().end <= x.span().start {
+                    (Side::B, y)
+                } else {
+                    match resolve(Conflict { a: x, b: y }) {
+                        Choice::A => current.b.unwrap(),
+                        Choice::B => current.a.unwrap(),
+                    }
+
+                    continue;
+                }
+            }
+        };
+
+        if node.kind() == Kind::Token {
+            ops.push(Some(Op::Token(node)));
+
+            match side {
+                Side::A => current.a.advance(),
+                Side::B => current.b.advance(),
+            }
+
+            continue;
+        }
+
+        let op = ops.len();
+        ops.push(Some(Op::Open(node)));
+
+        let children = Cursor::new(node.first());
+
+        let child = match side {
+            Side::A => Frame {
+                container: Some(Container { side, node, op }),
+                a: children,
+                b: core::mem::take(&mut current.b),
+            },
+            Side::B => Frame {
+                container: Some(Container { side, node, op }),
+                a: core::mem::take(&mut current.a),
+                b: children,
+            },
+        };
+
+        frames.push(core::mem::replace(&mut current, child));
+    }
+
+    let mut builder = Builder::<T, F>::new_with();
+    builder.set_explicit_spans(true);
+
+    if let Some(id) = a.source_id() {
+        builder.set_source_id(id);
+    }
+
+    for op in ops.into_iter().flatten() {
+        match op {
+            Op::Open(node) => {
+                let id = builder.open_with(node.value(), *node.span())?;
+                builder.set_flag(id, node.flags())?;
+            }
+            Op::Token(node) => {
+                let id = builder.token_with(node.value(), *node.span())?;
+                builder.set_flag(id, node.flags())?;
+            }
+            Op::Close => {
+                builder.close()?;
+            }
+        }
+    }
+
+    builder.build()
+}
+
+/// Test if `inner` can be nested inside of `outer`.
+fn contains<T, F>(outer: Node<'_, T, F>, inner: Node<'_, T, F>) -> bool
+where
+    T: Copy,
+    F: Flavor,
+{
+    outer.kind() == Kind::Node
+        && outer.span().start <= inner.span().start
+        && inner.span().end <= outer.span().end
+}
+
+/// An operation used to build the merged tree.
+enum Op<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    Open(Node<'a, T, F>),
+    Token(Node<'a, T, F>),
+    Close,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    A,
+    B,
+}
+
+/// A node which has been opened in the merged tree.
+struct Container<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    side: Side,
+    node: Node<'a, T, F>,
+    /// The operation which opened the node.
+    op: usize,
+}
+
+impl<T, F> Clone for Container<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for Container<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+/// The state of the merge inside of a node which has been opened.
+struct Frame<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    container: Option<Container<'a, T, F>>,
+    a: Cursor<'a, T, F>,
+    b: Cursor<'a, T, F>,
+}
+
+/// A cursor over the siblings of one of the trees being merged, where the
+/// children of removed nodes take their place.
+struct Cursor<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// The current node of every level which is being visited, the innermost
+    /// level being last.
+    stack: Vec<Node<'a, T, F>>,
+}
+
+impl<'a, T, F> Cursor<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    fn new(node: Option<Node<'a, T, F>>) -> Self {
+        Self {
+            stack: node.into_iter().collect(),
+        }
+    }
+
+    fn head(&self) -> Option<Node<'a, T, F>> {
+        self.stack.last().copied()
+    }
+
+    /// Move past the current node.
+    fn advance(&mut self) {
+        if let Some(next) = self.stack.pop().and_then(|n| n.next()) {
+            self.stack.push(next);
+        }
+    }
+
+    /// Remove the current node, visiting its children in its place.
+    fn unwrap(&mut self) {
+        let Some(node) = self.stack.pop() else {
+            return;
+        };
+
+        self.stack.extend(node.next());
+        self.stack.extend(node.first());
+    }
+
+    /// Replace the current node with the remaining nodes of `other`, which
+    /// visits the rest of its children in its place.
+    fn replace(&mut self, other: Self) {
+        self.advance();
+        self.stack.extend(other.stack);
+    }
+}
+
+impl<T, F> Default for Cursor<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self { stack: Vec::new() }
+    }
+}
@@ -2,16 +2,24 @@
 
 mod ancestors;
 mod children;
+mod children_by;
 pub(crate) mod node_impl;
 mod siblings;
+mod siblings_before;
 mod skip_tokens;
+mod span_events;
 mod walk;
 mod walk_events;
+mod walk_postorder;
 
 pub use self::ancestors::Ancestors;
 pub use self::children::Children;
+pub use self::children_by::ChildrenBy;
 pub(crate) use self::node_impl::Node;
 pub use self::siblings::Siblings;
+pub use self::siblings_before::SiblingsBefore;
 pub use self::skip_tokens::SkipTokens;
-pub use self::walk::{Walk, WithDepths};
+pub use self::span_events::{SpanEvent, SpanEvents};
+pub use self::walk::{Walk, WithDepths, WithParents};
 pub use self::walk_events::{Event, WalkEvents};
+pub use self::walk_postorder::WalkPostorder;
@@ -1,9 +1,10 @@
 use core::iter::FusedIterator;
 
 use crate::flavor::Flavor;
-use crate::links::Links;
+use crate::links::LinksOf;
 use crate::node::{Node, SkipTokens};
 use crate::pointer::Pointer;
+use crate::span::Span;
 
 /// An iterator that iterates over the [`Node::next`] elements of a node. This is
 /// typically used for iterating over the children of a tree.
@@ -74,7 +75,7 @@ where
     T: Copy,
     F: Flavor,
 {
-    tree: &'a [Links<T, F::Index, F::Pointer>],
+    tree: &'a [LinksOf<T, F>],
     first: Option<F::Pointer>,
     last: Option<F::Pointer>,
 }
@@ -87,7 +88,7 @@ where
     /// Construct a new child iterator.
     #[inline]
     pub(crate) const fn new(
-        tree: &'a [Links<T, F::Index, F::Pointer>],
+        tree: &'a [LinksOf<T, F>],
         first: Option<F::Pointer>,
         last: Option<F::Pointer>,
     ) -> Self {
@@ -147,6 +148,46 @@ where
     pub fn next_node(&mut self) -> Option<Node<'a, T, F>> {
         self.find(|n| n.has_children())
     }
+
+    /// Get the span covering the remaining nodes of the iterator, or `None`
+    /// if there are no remaining nodes.
+    ///
+    /// This only looks at the first and last remaining nodes, so it takes
+    /// constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         ("arg", 2),
+    ///         ("comma", 1),
+    ///         ("arg", 4),
+    ///     }
+    /// };
+    ///
+    /// let call = tree.first().ok_or("missing call")?;
+    ///
+    /// let mut args = call.children();
+    /// args.next();
+    /// args.next();
+    /// assert_eq!(args.span(), Some(Span::new(5, 10)));
+    ///
+    /// args.next_back();
+    /// args.next_back();
+    /// assert_eq!(args.span(), None);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Option<Span<F::Index>> {
+        let mut it = self.clone();
+        let first = it.next()?;
+        let last = it.next_back().unwrap_or(first);
+        Some(first.span().join(last.span()))
+    }
 }
 
 impl<'a, T, F> Iterator for Children<'a, T, F>
@@ -0,0 +1,145 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::links::LinksOf;
+use crate::node::Node;
+use crate::pointer::Pointer;
+
+/// An iterator over the children of a node whose values match a predicate.
+///
+/// Values are tested before a [`Node`] is constructed for them, so this avoids
+/// the overhead of constructing nodes which are filtered out.
+///
+/// See [`Node::children_by`].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "call" => {
+///         ("ident", 3),
+///         ("ws", 1),
+///         "arg" => {
+///             ("number", 1),
+///         },
+///         ("ws", 1),
+///         "arg" => {
+///             ("number", 2),
+///         },
+///     }
+/// };
+///
+/// let call = tree.first().ok_or("missing call")?;
+///
+/// let args = call.children_by(|v| *v == "arg").map(|n| n.span().range()).collect::<Vec<_>>();
+/// assert_eq!(args, [4..5, 6..8]);
+///
+/// let args = call.children_by(|v| *v == "arg").rev().map(|n| n.span().range()).collect::<Vec<_>>();
+/// assert_eq!(args, [6..8, 4..5]);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct ChildrenBy<'a, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: &'a [LinksOf<T, F>],
+    first: Option<F::Pointer>,
+    last: Option<F::Pointer>,
+    predicate: P,
+}
+
+impl<'a, T, F, P> ChildrenBy<'a, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// Construct a new filtered child iterator.
+    #[inline]
+    pub(crate) const fn new(
+        tree: &'a [LinksOf<T, F>],
+        first: Option<F::Pointer>,
+        last: Option<F::Pointer>,
+        predicate: P,
+    ) -> Self {
+        Self {
+            tree,
+            first,
+            last,
+            predicate,
+        }
+    }
+}
+
+impl<'a, T, F, P> Iterator for ChildrenBy<'a, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(&T) -> bool,
+{
+    type Item = Node<'a, T, F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let first = self.first.take()?;
+            let links = self.tree.get(first.get())?;
+
+            if first != self.last? {
+                self.first = links.next;
+            }
+
+            if (self.predicate)(&links.data.get()) {
+                return Some(Node::new(links, self.tree));
+            }
+        }
+    }
+}
+
+impl<T, F, P> DoubleEndedIterator for ChildrenBy<'_, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(&T) -> bool,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let last = self.last.take()?;
+            let links = self.tree.get(last.get())?;
+
+            if last != self.first? {
+                self.last = links.prev;
+            }
+
+            if (self.predicate)(&links.data.get()) {
+                return Some(Node::new(links, self.tree));
+            }
+        }
+    }
+}
+
+impl<T, F, P> FusedIterator for ChildrenBy<'_, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(&T) -> bool,
+{
+}
+
+impl<T, F, P> Clone for ChildrenBy<'_, T, F, P>
+where
+    T: Copy,
+    F: Flavor,
+    P: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            first: self.first,
+            last: self.last,
+            predicate: self.predicate.clone(),
+        }
+    }
+}
@@ -1,12 +1,20 @@
 use core::fmt;
 use core::mem::size_of;
 use core::ops::Range;
+use core::ptr;
 
+use crate::flags::Flags;
 use crate::flavor::Flavor;
-use crate::links::Links;
-use crate::node::{Ancestors, Children, Event, Siblings, Walk, WalkEvents};
+use crate::kind::Kind;
+use crate::links::LinksOf;
+use crate::node::{
+    Ancestors, Children, ChildrenBy, Event, Siblings, SiblingsBefore, Walk, WalkEvents,
+    WalkPostorder,
+};
 use crate::pointer::Pointer;
 use crate::span::Span;
+use crate::tree::Tree;
+use crate::unit::Unit;
 
 /// A node in the tree.
 ///
@@ -26,8 +34,8 @@ where
     T: Copy,
     F: Flavor,
 {
-    links: &'a Links<T, F::Index, F::Pointer>,
-    tree: &'a [Links<T, F::Index, F::Pointer>],
+    links: &'a LinksOf<T, F>,
+    tree: &'a [LinksOf<T, F>],
 }
 
 impl<'a, T, F> Node<'a, T, F>
@@ -35,10 +43,7 @@ where
     T: Copy,
     F: Flavor,
 {
-    pub(crate) const fn new(
-        links: &'a Links<T, F::Index, F::Pointer>,
-        tree: &'a [Links<T, F::Index, F::Pointer>],
-    ) -> Self {
+    pub(crate) const fn new(links: &'a LinksOf<T, F>, tree: &'a [LinksOf<T, F>]) -> Self {
         Self { links, tree }
     }
 
@@ -97,6 +102,83 @@ where
         self.links.data.replace(value)
     }
 
+    /// Get the [Kind] of the node, indicating whether it was constructed as a
+    /// node or as a token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Kind;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "empty" => {},
+    ///         ("number", 5),
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// assert_eq!(root.kind(), Kind::Node);
+    ///
+    /// let empty = root.first().ok_or("missing empty")?;
+    /// assert_eq!(empty.kind(), Kind::Node);
+    ///
+    /// let number = root.last().ok_or("missing number")?;
+    /// assert_eq!(number.kind(), Kind::Token);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub const fn kind(&self) -> Kind {
+        self.links.kind
+    }
+
+    /// Get the flags set on the node.
+    ///
+    /// See [Flags] for documentation.
+    #[must_use]
+    pub fn flags(&self) -> F::Flags {
+        self.links.flags
+    }
+
+    /// Test if every flag in `flag` is set on the node.
+    ///
+    /// See [Flags] for documentation.
+    #[must_use]
+    pub fn has_flag(&self, flag: F::Flags) -> bool {
+        self.links.flags.contains(flag)
+    }
+
+    /// Check if the current node is a token.
+    ///
+    /// Unlike [`Node::has_children`], this distinguishes between tokens and
+    /// nodes which do not have any children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "empty" => {},
+    ///         ("number", 5),
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// assert!(!root.is_token());
+    ///
+    /// let empty = root.first().ok_or("missing empty")?;
+    /// assert!(!empty.has_children());
+    /// assert!(!empty.is_token());
+    ///
+    /// let number = root.last().ok_or("missing number")?;
+    /// assert!(number.is_token());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub const fn is_token(&self) -> bool {
+        matches!(self.links.kind, Kind::Token)
+    }
+
     /// Check if the current node has children or not.
     ///
     /// Nodes without children are also known as tokens.
@@ -188,6 +270,89 @@ where
         Ancestors::new(Some(*self))
     }
 
+    /// Test if this node is a proper ancestor of `other`, meaning that `other`
+    /// is somewhere inside of this node.
+    ///
+    /// A node is not an ancestor of itself, and nodes from different trees are
+    /// never related.
+    ///
+    /// This takes time proportional to the depth of `other`. For many checks
+    /// against the same tree, see [`Tree::euler_tour`].
+    ///
+    /// [`Tree::euler_tour`]: crate::Tree::euler_tour
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let number = root.first().ok_or("missing number")?;
+    /// let lit = number.first().ok_or("missing lit")?;
+    /// let ws = root.last().ok_or("missing ws")?;
+    ///
+    /// assert!(root.is_ancestor_of(&lit));
+    /// assert!(number.is_ancestor_of(&lit));
+    /// assert!(!number.is_ancestor_of(&ws));
+    /// assert!(!lit.is_ancestor_of(&lit));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn is_ancestor_of(&self, other: &Node<'_, T, F>) -> bool {
+        if !other.belongs_to(self.tree) {
+            return false;
+        }
+
+        let id = self.id();
+        let mut current = other.links.parent;
+
+        while let Some(parent) = current {
+            if parent == id {
+                return true;
+            }
+
+            current = self.tree.get(parent.get()).and_then(|links| links.parent);
+        }
+
+        false
+    }
+
+    /// Test if this node is a proper descendant of `other`, meaning that this
+    /// node is somewhere inside of `other`.
+    ///
+    /// This is the same as `other.is_ancestor_of(self)`, see
+    /// [`Node::is_ancestor_of`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let lit = root.first().and_then(|n| n.first()).ok_or("missing lit")?;
+    ///
+    /// assert!(lit.is_descendant_of(&root));
+    /// assert!(!root.is_descendant_of(&lit));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn is_descendant_of(&self, other: &Node<'_, T, F>) -> bool {
+        other.is_ancestor_of(self)
+    }
+
     /// Get an iterator over the siblings of this node, including itself.
     ///
     /// See [Siblings] for documentation.
@@ -196,6 +361,42 @@ where
         Siblings::new(self.tree, self.links)
     }
 
+    /// Get an iterator over the siblings following this node, excluding
+    /// itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit1", 1),
+    ///         ("lit2", 1),
+    ///         ("lit3", 1),
+    ///     }
+    /// };
+    ///
+    /// let lit1 = tree.first().and_then(|n| n.first()).ok_or("missing lit1")?;
+    ///
+    /// assert_eq!(
+    ///     lit1.siblings_after().map(|n| n.value()).collect::<Vec<_>>(),
+    ///     ["lit2", "lit3"]
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn siblings_after(&self) -> Siblings<'a, T, F> {
+        Siblings::after(self.tree, self.links)
+    }
+
+    /// Get an iterator over the siblings preceding this node, excluding
+    /// itself and starting with the closest one.
+    ///
+    /// See [SiblingsBefore] for documentation.
+    #[must_use]
+    pub fn siblings_before(&self) -> SiblingsBefore<'a, T, F> {
+        SiblingsBefore::new(self.tree, self.links)
+    }
+
     /// Get an iterator over the children of this node.
     ///
     /// See [Children] for documentation.
@@ -204,6 +405,67 @@ where
         Children::new(self.tree, self.links.first, self.links.last)
     }
 
+    /// Find the first immediate child of this node whose value matches
+    /// `predicate`.
+    ///
+    /// Only the children of this node are scanned, not their descendants. This
+    /// is the same as `children().find(..)` on the value of each node, but
+    /// avoids constructing nodes which don't match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "fn" => {
+    ///         ("fn", 2),
+    ///         ("ws", 1),
+    ///         ("ident", 4),
+    ///         "block" => {
+    ///             ("ident", 1),
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// let item = tree.first().ok_or("missing fn")?;
+    ///
+    /// let ident = item.child_by(|v| *v == "ident").ok_or("missing ident")?;
+    /// assert_eq!(ident.span().range(), 3..7);
+    ///
+    /// assert!(item.child_by(|v| *v == "number").is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn child_by<P>(&self, mut predicate: P) -> Option<Node<'a, T, F>>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut current = self.links.first;
+
+        while let Some(id) = current {
+            let links = self.tree.get(id.get())?;
+
+            if predicate(&links.data.get()) {
+                return Some(Node::new(links, self.tree));
+            }
+
+            current = links.next;
+        }
+
+        None
+    }
+
+    /// Get an iterator over the immediate children of this node whose values
+    /// match `predicate`.
+    ///
+    /// See [ChildrenBy] for documentation.
+    #[must_use]
+    pub fn children_by<P>(&self, predicate: P) -> ChildrenBy<'a, T, F, P>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        ChildrenBy::new(self.tree, self.links.first, self.links.last, predicate)
+    }
+
     /// Walk the subtree forward starting with the first child of the current
     /// node.
     ///
@@ -231,6 +493,17 @@ where
     pub fn walk_events(&self) -> WalkEvents<'a, T, F> {
         WalkEvents::new(self.tree, Some(self.id()), Event::Next)
     }
+
+    /// Walk the subtree of the current node in postorder, visiting every node
+    /// after its descendants and ending with the current node.
+    ///
+    /// Unlike [`Node::walk`], this doesn't continue past the current node.
+    ///
+    /// See [`WalkPostorder`] for documentation.
+    #[must_use]
+    pub fn walk_postorder(&self) -> WalkPostorder<'a, T, F> {
+        WalkPostorder::subtree(*self)
+    }
 }
 
 impl<'a, T, F> Node<'a, T, F>
@@ -336,6 +609,168 @@ where
         self.node_at(self.links.next?)
     }
 
+    /// Get the previous sibling which is not trivia, skipping over any
+    /// siblings whose value matches the `is_trivia` predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///         ("comment", 4),
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let is_trivia = |value| matches!(value, "ws" | "comment");
+    ///
+    /// let ident = tree.first().and_then(|n| n.last()).ok_or("missing ident")?;
+    /// assert_eq!(ident.prev().map(|n| n.value()), Some("comment"));
+    ///
+    /// let number = ident.prev_non_trivia(is_trivia).ok_or("missing number")?;
+    /// assert_eq!(number.value(), "number");
+    /// assert!(number.prev_non_trivia(is_trivia).is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn prev_non_trivia<P>(&self, mut is_trivia: P) -> Option<Node<'a, T, F>>
+    where
+        P: FnMut(T) -> bool,
+    {
+        let mut node = self.prev()?;
+
+        while is_trivia(node.value()) {
+            node = node.prev()?;
+        }
+
+        Some(node)
+    }
+
+    /// Get the next sibling which is not trivia, skipping over any siblings
+    /// whose value matches the `is_trivia` predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///         ("comment", 4),
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let is_trivia = |value| matches!(value, "ws" | "comment");
+    ///
+    /// let number = tree.first().and_then(|n| n.first()).ok_or("missing number")?;
+    /// assert_eq!(number.next().map(|n| n.value()), Some("ws"));
+    ///
+    /// let ident = number.next_non_trivia(is_trivia).ok_or("missing ident")?;
+    /// assert_eq!(ident.value(), "ident");
+    /// assert!(ident.next_non_trivia(is_trivia).is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn next_non_trivia<P>(&self, mut is_trivia: P) -> Option<Node<'a, T, F>>
+    where
+        P: FnMut(T) -> bool,
+    {
+        let mut node = self.next()?;
+
+        while is_trivia(node.value()) {
+            node = node.next()?;
+        }
+
+        Some(node)
+    }
+
+    /// Get the position of this node among its siblings, where the first child
+    /// of a node has position zero.
+    ///
+    /// This takes time proportional to the position of the node. To test if a
+    /// node is the first or last of its siblings, check if [`Node::prev`] or
+    /// [`Node::next`] returns `None` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "args" => {
+    ///         ("a", 1),
+    ///         ("comma", 1),
+    ///         ("b", 1),
+    ///     }
+    /// };
+    ///
+    /// let args = tree.first().ok_or("missing args")?;
+    /// let b = args.last().ok_or("missing b")?;
+    ///
+    /// assert_eq!(args.sibling_index(), 0);
+    /// assert_eq!(b.sibling_index(), 2);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn sibling_index(&self) -> usize {
+        let mut index = 0;
+        let mut current = self.prev();
+
+        while let Some(node) = current {
+            index += 1;
+            current = node.prev();
+        }
+
+        index
+    }
+
+    /// Get the sibling `n` steps away from this node, where a positive `n`
+    /// moves towards later siblings and a negative `n` towards earlier ones.
+    ///
+    /// `nth_sibling(0)` is the node itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "args" => {
+    ///         ("a", 1),
+    ///         ("comma", 1),
+    ///         ("b", 1),
+    ///     }
+    /// };
+    ///
+    /// let a = tree.first().and_then(|n| n.first()).ok_or("missing a")?;
+    ///
+    /// assert_eq!(a.nth_sibling(0).map(|n| n.value()), Some("a"));
+    /// assert_eq!(a.nth_sibling(2).map(|n| n.value()), Some("b"));
+    /// assert!(a.nth_sibling(3).is_none());
+    /// assert!(a.nth_sibling(-1).is_none());
+    ///
+    /// let b = a.nth_sibling(2).ok_or("missing b")?;
+    /// assert_eq!(b.nth_sibling(-1).map(|n| n.value()), Some("comma"));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn nth_sibling(&self, n: isize) -> Option<Node<'a, T, F>> {
+        let mut node = *self;
+
+        for _ in 0..n.unsigned_abs() {
+            node = if n < 0 { node.prev()? } else { node.next()? };
+        }
+
+        Some(node)
+    }
+
     /// Get the first child node.
     ///
     /// # Examples
@@ -466,6 +901,84 @@ where
         }
     }
 
+    /// Find the first descendant of this node which matches the given
+    /// predicate.
+    ///
+    /// Descendants are visited in depth-first order, not including the
+    /// current node, and the search stops as soon as a matching node is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     },
+    ///     "root2" => {
+    ///         "ident"
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    ///
+    /// let ident = root.find_descendant(|n| n.value() == "ident").ok_or("missing ident")?;
+    /// assert_eq!(ident.range(), 5..8);
+    ///
+    /// assert!(root.find_descendant(|n| n.value() == "root").is_none());
+    /// assert!(root.find_descendant(|n| n.value() == "root2").is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn find_descendant<P>(&self, mut predicate: P) -> Option<Node<'a, T, F>>
+    where
+        P: FnMut(Node<'a, T, F>) -> bool,
+    {
+        self.walk().inside().skip(1).find(|n| predicate(*n))
+    }
+
+    /// Apply the given function to the descendants of this node, returning
+    /// the first non-`None` result.
+    ///
+    /// Descendants are visited in depth-first order, not including the
+    /// current node, and the search stops as soon as the function returns a
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    ///
+    /// let range = root.find_map_descendant(|n| (n.value() == "ident").then(|| n.range()));
+    /// assert_eq!(range, Some(5..8));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn find_map_descendant<P, O>(&self, predicate: P) -> Option<O>
+    where
+        P: FnMut(Node<'a, T, F>) -> Option<O>,
+    {
+        self.walk().inside().skip(1).find_map(predicate)
+    }
+
+    /// Test if the node belongs to the given tree storage.
+    pub(crate) fn belongs_to(&self, tree: &[LinksOf<T, F>]) -> bool {
+        core::ptr::eq(self.tree.as_ptr(), tree.as_ptr())
+    }
+
     fn node_at(&self, id: F::Pointer) -> Option<Node<'a, T, F>> {
         let cur = self.tree.get(id.get())?;
 
@@ -477,8 +990,11 @@ where
 
     /// Get the identifier of the current node.
     ///
-    /// Note that an id might be re-used across different trees. This behavior
-    /// is never unsafe, but is not well-defined.
+    /// Identifiers are allocated deterministically in the order in which nodes
+    /// are inserted, so an id will be re-used across different trees. Using an
+    /// id with a tree it wasn't taken from is never unsafe, but it refers to an
+    /// unrelated node. See [`NodeId`][crate::NodeId] for an identifier which
+    /// catches such mistakes in debug builds.
     ///
     /// This can be used to register a change in a [`ChangeSet`] later.
     ///
@@ -510,12 +1026,64 @@ where
         // inside of the tree of links.
         let current = self.links as *const _ as usize;
         let base = self.tree.as_ptr() as usize;
-        let id = (current - base) / size_of::<Links<T, F::Index, F::Pointer>>();
+        let id = (current - base) / size_of::<LinksOf<T, F>>();
         debug_assert!(id < self.tree.len(), "identifier outside of tree length");
         // SAFETY: It's impossible to construct a node with an offset which is
         // not a legal `NonMax`.
         unsafe { F::Pointer::new_unchecked(id) }
     }
+
+    /// Find the token within this node which contains the offset `index`.
+    ///
+    /// A token contains an offset if it starts at or before it and ends after
+    /// it, so empty tokens are never returned. This returns `None` if `index`
+    /// is outside of the span of this node, or if it falls in a gap between
+    /// tokens. If this node is itself a token containing `index`, it is
+    /// returned.
+    ///
+    /// The token is looked up using the index of `tree`, which must be the
+    /// tree this node belongs to, and then checked to be inside of this node.
+    /// So this takes time proportional to the depth of the token below this
+    /// node rather than to the number of tokens in it. See
+    /// [`Tree::token_at`][crate::Tree::token_at].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("ident", 3),
+    ///         "number" => {
+    ///             ("lit", 2),
+    ///             ("suffix", 3),
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let number = root.last().ok_or("missing number")?;
+    ///
+    /// assert_eq!(root.token_at(&tree, 1).map(|n| n.value()), Some("ident"));
+    /// assert_eq!(root.token_at(&tree, 6).map(|n| n.value()), Some("suffix"));
+    /// assert_eq!(number.token_at(&tree, 4).map(|n| n.value()), Some("lit"));
+    /// assert!(number.token_at(&tree, 1).is_none());
+    /// assert!(root.token_at(&tree, 8).is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn token_at(&self, tree: &'a Tree<T, F>, index: F::Index) -> Option<Node<'a, T, F>> {
+        if !self.links.span.contains(&index) {
+            return None;
+        }
+
+        let token = tree.token_at(index)?;
+
+        if token.ancestors().any(|n| ptr::eq(n.links, self.links)) {
+            return Some(token);
+        }
+
+        None
+    }
 }
 
 impl<T, F> Node<'_, T, F>
@@ -554,6 +1122,295 @@ where
     pub fn range(&self) -> Range<usize> {
         self.links.span.range()
     }
+
+    /// Get the span of the current node measured in `unit`, converting from
+    /// the [`Flavor::UNIT`] of the tree using `source`.
+    ///
+    /// Returns `None` if the span doesn't fit in the source or doesn't fall on
+    /// character boundaries. See [`Unit::convert`] for details.
+    ///
+    /// [`Unit::convert`]: crate::Unit::convert
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Span, Unit};
+    ///
+    /// let source = "ö = \"😀\"";
+    ///
+    /// let tree = syntree::tree! {
+    ///     "assign" => {
+    ///         ("ident", 2),
+    ///         ("ws", 1),
+    ///         ("eq", 1),
+    ///         ("ws", 1),
+    ///         ("string", 6),
+    ///     }
+    /// };
+    ///
+    /// let string = tree.first().and_then(|n| n.last()).ok_or("missing string")?;
+    ///
+    /// assert_eq!(string.span(), Span::new(5, 11));
+    /// assert_eq!(string.span_in(Unit::Chars, source), Some(Span::new(4, 7)));
+    /// assert_eq!(string.span_in(Unit::Utf16, source), Some(Span::new(4, 8)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn span_in(&self, unit: Unit, source: &str) -> Option<Span<usize>> {
+        let range = self.range();
+        let start = F::UNIT.convert(unit, source, range.start)?;
+        let end = F::UNIT.convert(unit, source, range.end)?;
+        Some(Span::new(start, end))
+    }
+
+    /// Get the span of the current node without any leading or trailing
+    /// tokens whose value matches the `is_trivia` predicate.
+    ///
+    /// This is useful for diagnostics, which look better when they don't
+    /// underline surrounding whitespace and comments. Trivia is trimmed no
+    /// matter how deeply nested it is inside of the node.
+    ///
+    /// If the node doesn't contain any tokens which are not trivia, its span
+    /// is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "stmt" => {
+    ///         ("ws", 2),
+    ///         "call" => {
+    ///             ("comment", 5),
+    ///             ("ident", 3),
+    ///             ("ws", 1),
+    ///         },
+    ///         ("semi", 1),
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let is_trivia = |value| matches!(value, "ws" | "comment");
+    ///
+    /// let stmt = tree.first().ok_or("missing stmt")?;
+    /// assert_eq!(*stmt.span(), Span::new(0, 13));
+    /// assert_eq!(stmt.trimmed_span(is_trivia), Span::new(7, 12));
+    ///
+    /// let call = stmt.children().nth(1).ok_or("missing call")?;
+    /// assert_eq!(call.trimmed_span(is_trivia), Span::new(7, 10));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn trimmed_span<P>(&self, mut is_trivia: P) -> Span<F::Index>
+    where
+        P: FnMut(T) -> bool,
+    {
+        let mut trimmed = None::<Span<F::Index>>;
+
+        for node in self.walk().inside() {
+            if !node.is_token() || is_trivia(node.value()) {
+                continue;
+            }
+
+            let span = node.span();
+
+            trimmed = Some(match trimmed {
+                Some(trimmed) => Span::new(trimmed.start, span.end),
+                None => *span,
+            });
+        }
+
+        trimmed.unwrap_or(self.links.span)
+    }
+
+    /// Compare the structure and values of the subtree of this node with the
+    /// subtree of `other`, ignoring spans.
+    ///
+    /// The other node may be from a tree of a different flavor. This is useful
+    /// when only the shape of a tree matters, since it means that an expected
+    /// tree doesn't have to be constructed with exact token lengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Empty, EmptyVec, Tree, TreeIndex};
+    ///
+    /// syntree::flavor! {
+    ///     struct FlavorEmpty {
+    ///         type Index = Empty;
+    ///         type Width = u32;
+    ///         type Indexes = EmptyVec<TreeIndex<Self>>;
+    ///     }
+    /// }
+    ///
+    /// let tree = syntree::tree! {
+    ///     "expr" => {
+    ///         ("ident", 3),
+    ///         ("ws", 1),
+    ///         "args" => {
+    ///             ("number", 12),
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let expected: Tree<_, FlavorEmpty> = syntree::tree_with! {
+    ///     "expr" => {
+    ///         "ident",
+    ///         "ws",
+    ///         "args" => {
+    ///             "number",
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let a = tree.first().ok_or("missing expr")?;
+    /// let b = expected.first().ok_or("missing expr")?;
+    ///
+    /// assert!(a.eq_structure(&b));
+    ///
+    /// let args = a.last().ok_or("missing args")?;
+    /// assert!(!args.eq_structure(&b));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn eq_structure<A>(&self, other: &Node<'_, T, A>) -> bool
+    where
+        T: PartialEq,
+        A: Flavor,
+    {
+        if self.value() != other.value() || self.kind() != other.kind() {
+            return false;
+        }
+
+        let mut a = self.walk_events();
+        let mut b = other.walk_events();
+
+        // Skip over the nodes themselves, which were compared above.
+        a.next();
+        b.next();
+
+        loop {
+            // Events are inside of the subtree as long as they are below the
+            // node, or they are leaving it.
+            let a = a
+                .next_with_depth()
+                .filter(|&(e, depth, _)| depth > 0 || (depth == 0 && e == Event::Up));
+
+            let b = b
+                .next_with_depth()
+                .filter(|&(e, depth, _)| depth > 0 || (depth == 0 && e == Event::Up));
+
+            match (a, b) {
+                (Some((ea, da, a)), Some((eb, db, b))) => {
+                    if ea != eb || da != db || a.value() != b.value() || a.kind() != b.kind() {
+                        return false;
+                    }
+                }
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Compare the text covered by the tokens of this node with the text
+    /// covered by the tokens of `other`.
+    ///
+    /// The text of this node is looked up in `source`, and the text of `other`
+    /// in `other_source`. Both token sequences are walked in lockstep without
+    /// allocating, so differences in how the text is split into tokens do not
+    /// matter.
+    ///
+    /// If any token has a span which is out of bounds for its source, this
+    /// returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let a = "foo + bar";
+    /// let b = "bar+foo + bar";
+    ///
+    /// let tree_a = syntree::tree! {
+    ///     "expr" => {
+    ///         ("ident", 3),
+    ///         ("ws", 1),
+    ///         ("plus", 1),
+    ///         ("ws", 1),
+    ///         ("ident", 3),
+    ///     }
+    /// };
+    ///
+    /// let tree_b = syntree::tree! {
+    ///     "expr" => {
+    ///         ("ident", 3),
+    ///         ("plus", 1),
+    ///     },
+    ///     "expr" => {
+    ///         ("ident", 3),
+    ///         ("ws", 1),
+    ///         ("plus", 2),
+    ///         ("ident", 3),
+    ///     }
+    /// };
+    ///
+    /// let expr_a = tree_a.first().ok_or("missing expr")?;
+    /// let first_b = tree_b.first().ok_or("missing first")?;
+    /// let last_b = tree_b.last().ok_or("missing last")?;
+    ///
+    /// assert!(expr_a.text_eq(&last_b, a, b));
+    /// assert!(!expr_a.text_eq(&first_b, a, b));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn text_eq<A>(&self, other: &Node<'_, T, A>, source: &str, other_source: &str) -> bool
+    where
+        A: Flavor,
+    {
+        let mut a = self.walk().inside().filter(|n| !n.has_children());
+        let mut b = other.walk().inside().filter(|n| !n.has_children());
+
+        let mut left: &[u8] = &[];
+        let mut right: &[u8] = &[];
+
+        loop {
+            while left.is_empty() {
+                let Some(node) = a.next() else {
+                    break;
+                };
+
+                let Some(text) = source.as_bytes().get(node.range()) else {
+                    return false;
+                };
+
+                left = text;
+            }
+
+            while right.is_empty() {
+                let Some(node) = b.next() else {
+                    break;
+                };
+
+                let Some(text) = other_source.as_bytes().get(node.range()) else {
+                    return false;
+                };
+
+                right = text;
+            }
+
+            if left.is_empty() || right.is_empty() {
+                return left.is_empty() && right.is_empty();
+            }
+
+            let n = left.len().min(right.len());
+
+            if left[..n] != right[..n] {
+                return false;
+            }
+
+            left = &left[n..];
+            right = &right[n..];
+        }
+    }
 }
 
 impl<T, F> fmt::Debug for Node<'_, T, F>
@@ -1,9 +1,10 @@
 use core::iter::FusedIterator;
 
 use crate::flavor::Flavor;
-use crate::links::Links;
+use crate::links::LinksOf;
 use crate::node::{Node, SkipTokens};
 use crate::pointer::Pointer;
+use crate::span::Span;
 
 /// An iterator that iterates over the [`Node::next`] elements of a node. This is
 /// typically used for iterating over the children of a tree.
@@ -67,8 +68,8 @@ where
     T: Copy,
     F: Flavor,
 {
-    tree: &'a [Links<T, F::Index, F::Pointer>],
-    links: Option<&'a Links<T, F::Index, F::Pointer>>,
+    tree: &'a [LinksOf<T, F>],
+    links: Option<&'a LinksOf<T, F>>,
 }
 
 impl<'a, T, F> Siblings<'a, T, F>
@@ -78,16 +79,22 @@ where
 {
     /// Construct a new child iterator.
     #[inline]
-    pub(crate) const fn new(
-        tree: &'a [Links<T, F::Index, F::Pointer>],
-        links: &'a Links<T, F::Index, F::Pointer>,
-    ) -> Self {
+    pub(crate) const fn new(tree: &'a [LinksOf<T, F>], links: &'a LinksOf<T, F>) -> Self {
         Self {
             tree,
             links: Some(links),
         }
     }
 
+    /// Construct a new iterator over the siblings after `links`.
+    #[inline]
+    pub(crate) fn after(tree: &'a [LinksOf<T, F>], links: &'a LinksOf<T, F>) -> Self {
+        Self {
+            tree,
+            links: links.next.and_then(|id| tree.get(id.get())),
+        }
+    }
+
     /// Construct a [`SkipTokens`] iterator from the remainder of this iterator.
     /// This filters out childless nodes, also known as tokens.
     ///
@@ -143,6 +150,41 @@ where
     pub fn next_node(&mut self) -> Option<Node<'a, T, F>> {
         self.find(|n| n.has_children())
     }
+
+    /// Get the span covering the remaining nodes of the iterator, or `None`
+    /// if there are no remaining nodes.
+    ///
+    /// This follows the links to the last sibling, so it takes time
+    /// proportional to the number of remaining nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         ("arg", 2),
+    ///         ("comma", 1),
+    ///         ("arg", 4),
+    ///     }
+    /// };
+    ///
+    /// let arg = tree.first().and_then(|n| n.first()).and_then(|n| n.next()).ok_or("missing arg")?;
+    /// assert_eq!(arg.siblings().span(), Some(Span::new(3, 10)));
+    ///
+    /// let last = arg.siblings().last().ok_or("missing last")?;
+    /// assert_eq!(last.siblings().span(), Some(Span::new(6, 10)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Option<Span<F::Index>> {
+        let mut it = self.clone();
+        let first = it.next()?;
+        let last = it.last().unwrap_or(first);
+        Some(first.span().join(last.span()))
+    }
 }
 
 impl<'a, T, F> Iterator for Siblings<'a, T, F>
@@ -0,0 +1,120 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::links::LinksOf;
+use crate::node::{Node, SkipTokens};
+use crate::pointer::Pointer;
+
+/// An iterator that iterates over the [`Node::prev`] elements of a node,
+/// starting with the closest preceding sibling.
+///
+/// See [`Node::siblings_before`].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("lit1", 1),
+///         "child" => {
+///             ("lit2", 1)
+///         },
+///         ("lit3", 1),
+///     }
+/// };
+///
+/// let lit3 = tree.first().and_then(|n| n.last()).ok_or("missing lit3")?;
+///
+/// assert_eq!(
+///     lit3.siblings_before().map(|n| n.value()).collect::<Vec<_>>(),
+///     ["child", "lit1"]
+/// );
+///
+/// assert_eq!(
+///     lit3.siblings_before().skip_tokens().map(|n| n.value()).collect::<Vec<_>>(),
+///     ["child"]
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct SiblingsBefore<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: &'a [LinksOf<T, F>],
+    links: Option<&'a LinksOf<T, F>>,
+}
+
+impl<'a, T, F> SiblingsBefore<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// Construct a new iterator over the siblings before `links`.
+    #[inline]
+    pub(crate) fn new(tree: &'a [LinksOf<T, F>], links: &'a LinksOf<T, F>) -> Self {
+        Self {
+            tree,
+            links: links.prev.and_then(|id| tree.get(id.get())),
+        }
+    }
+
+    /// Construct a [`SkipTokens`] iterator from the remainder of this iterator.
+    /// This filters out childless nodes, also known as tokens.
+    ///
+    /// See [`SkipTokens`] for documentation.
+    #[must_use]
+    pub const fn skip_tokens(self) -> SkipTokens<Self> {
+        SkipTokens::new(self)
+    }
+}
+
+impl<'a, T, F> Iterator for SiblingsBefore<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = Node<'a, T, F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let links = self.links.take()?;
+        self.links = links.prev.and_then(|id| self.tree.get(id.get()));
+        Some(Node::new(links, self.tree))
+    }
+}
+
+impl<T, F> FusedIterator for SiblingsBefore<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for SiblingsBefore<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            links: self.links,
+        }
+    }
+}
+
+impl<T, F> Default for SiblingsBefore<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tree: &[],
+            links: None,
+        }
+    }
+}
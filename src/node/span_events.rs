@@ -0,0 +1,174 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::node::{Event, WalkEvents};
+use crate::span::Span;
+
+/// Whether a [`SpanEvents`] iterator is entering or exiting the span of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpanEvent {
+    /// Entering the span of a node.
+    Enter,
+    /// Exiting the span of a node, after all of its descendants have been
+    /// entered and exited.
+    Exit,
+}
+
+/// An iterator which converts the events of a [`WalkEvents`] iterator into
+/// balanced enter and exit events for the span of each node.
+///
+/// Every node produces an [`SpanEvent::Enter`] and a matching
+/// [`SpanEvent::Exit`], including tokens and nodes without children. This is
+/// suitable for maintaining a stack of intervals, such as for semantic
+/// highlighting.
+///
+/// If the walk starts in the middle of a tree, nodes which are returned to
+/// without having been entered, like the ancestors of the node the walk
+/// started at, are skipped.
+///
+/// See [`WalkEvents::spans`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Span;
+/// use syntree::node::SpanEvent::*;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "number" => {
+///             ("lit", 2)
+///         },
+///         ("ws", 1),
+///     }
+/// };
+///
+/// assert_eq!(
+///     tree.walk_events().spans().collect::<Vec<_>>(),
+///     [
+///         (Span::new(0, 3), Enter, "root"),
+///         (Span::new(0, 2), Enter, "number"),
+///         (Span::new(0, 2), Enter, "lit"),
+///         (Span::new(0, 2), Exit, "lit"),
+///         (Span::new(0, 2), Exit, "number"),
+///         (Span::new(2, 3), Enter, "ws"),
+///         (Span::new(2, 3), Exit, "ws"),
+///         (Span::new(0, 3), Exit, "root"),
+///     ]
+/// );
+///
+/// let number = tree.first().and_then(|n| n.first()).ok_or("missing number")?;
+///
+/// assert_eq!(
+///     number.walk_events().spans().map(|(_, e, v)| (e, v)).collect::<Vec<_>>(),
+///     [
+///         (Enter, "number"),
+///         (Enter, "lit"),
+///         (Exit, "lit"),
+///         (Exit, "number"),
+///         (Enter, "ws"),
+///         (Exit, "ws"),
+///     ]
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct SpanEvents<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    iter: WalkEvents<'a, T, F>,
+    // The exit event of a node without children, which is emitted right after
+    // entering it.
+    exit: Option<(Span<F::Index>, T)>,
+    // The number of nodes with children which have been entered but not
+    // exited.
+    open: usize,
+}
+
+impl<'a, T, F> SpanEvents<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) const fn new(iter: WalkEvents<'a, T, F>) -> Self {
+        Self {
+            iter,
+            exit: None,
+            open: 0,
+        }
+    }
+}
+
+impl<T, F> Iterator for SpanEvents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = (Span<F::Index>, SpanEvent, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((span, value)) = self.exit.take() {
+            return Some((span, SpanEvent::Exit, value));
+        }
+
+        loop {
+            let (event, node) = self.iter.next()?;
+            let span = *node.span();
+
+            match event {
+                Event::Next | Event::Down => {
+                    if node.has_children() {
+                        self.open += 1;
+                    } else {
+                        self.exit = Some((span, node.value()));
+                    }
+
+                    return Some((span, SpanEvent::Enter, node.value()));
+                }
+                Event::Up => {
+                    let Some(open) = self.open.checked_sub(1) else {
+                        continue;
+                    };
+
+                    self.open = open;
+                    return Some((span, SpanEvent::Exit, node.value()));
+                }
+            }
+        }
+    }
+}
+
+impl<T, F> FusedIterator for SpanEvents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for SpanEvents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            exit: self.exit,
+            open: self.open,
+        }
+    }
+}
+
+impl<T, F> Default for SpanEvents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new(WalkEvents::default())
+    }
+}
@@ -1,9 +1,10 @@
 use core::iter::FusedIterator;
 
 use crate::flavor::Flavor;
-use crate::links::Links;
+use crate::links::LinksOf;
 use crate::node::Node;
 use crate::node::{Event, SkipTokens, WalkEvents};
+use crate::span::Span;
 
 /// An iterator that walks over the entire tree, visiting every node exactly
 /// once.
@@ -79,6 +80,15 @@ use crate::node::{Event, SkipTokens, WalkEvents};
 ///     c5.walk_from().map(|n| n.value()).collect::<Vec<_>>(),
 ///     ["c6", "c7"],
 /// );
+///
+/// // Walk in reverse.
+/// assert_eq!(
+///     c1.walk().rev().map(|n| n.value()).collect::<Vec<_>>(),
+///     ["c7", "c6", "c5", "c4", "c3", "c2", "c1"],
+/// );
+///
+/// // Find the last non-token descendant.
+/// assert_eq!(tree.walk().skip_tokens().next_back().map(|n| n.value()), Some("c6"));
 /// # Ok::<_, Box<dyn core::error::Error>>(())
 /// ```
 pub struct Walk<'a, T, F>
@@ -96,11 +106,7 @@ where
 {
     /// Construct a new walk.
     #[inline]
-    pub(crate) fn new(
-        tree: &'a [Links<T, F::Index, F::Pointer>],
-        node: Option<F::Pointer>,
-        e: Event,
-    ) -> Self {
+    pub(crate) fn new(tree: &'a [LinksOf<T, F>], node: Option<F::Pointer>, e: Event) -> Self {
         Self {
             iter: WalkEvents::new(tree, node, e),
         }
@@ -172,6 +178,22 @@ where
         WithDepths { iter: self.iter }
     }
 
+    /// Convert this iterator into one which includes the parent of each node.
+    ///
+    /// The parent is tracked as the tree is walked, which avoids having to
+    /// look it up with [`Node::parent`] for every node.
+    ///
+    /// See [`WithParents`] for documentation.
+    #[inline]
+    #[must_use]
+    pub fn with_parents(self) -> WithParents<'a, T, F> {
+        WithParents {
+            iter: self.iter,
+            current: None,
+            parent: None,
+        }
+    }
+
     /// Construct a [`SkipTokens`] iterator from the remainder of this iterator.
     /// This filters out childless nodes, also known as tokens.
     ///
@@ -220,6 +242,49 @@ where
             }
         }
     }
+
+    /// Get the span covering the remaining nodes of the walk, or `None` if
+    /// there are no remaining nodes.
+    ///
+    /// This joins the spans of the next and the last node of the walk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("ident", 3),
+    ///         "args" => {
+    ///             ("number", 2),
+    ///             ("number", 4),
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// let mut walk = tree.walk();
+    /// assert_eq!(walk.span(), Some(Span::new(0, 9)));
+    ///
+    /// walk.next();
+    /// walk.next();
+    /// assert_eq!(walk.span(), Some(Span::new(3, 9)));
+    ///
+    /// // The args node is still remaining.
+    /// walk.next_back();
+    /// assert_eq!(walk.span(), Some(Span::new(3, 9)));
+    ///
+    /// walk.next();
+    /// assert_eq!(walk.span(), Some(Span::new(3, 5)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Option<Span<F::Index>> {
+        let mut it = self.clone();
+        let first = it.next()?;
+        let last = it.next_back().unwrap_or(first);
+        Some(first.span().join(last.span()))
+    }
 }
 
 impl<T, F> Clone for Walk<'_, T, F>
@@ -267,6 +332,23 @@ where
     }
 }
 
+impl<T, F> DoubleEndedIterator for Walk<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (event, node) = self.iter.next_back()?;
+
+            if !matches!(event, Event::Up) {
+                return Some(node);
+            }
+        }
+    }
+}
+
 impl<T, F> FusedIterator for Walk<'_, T, F>
 where
     T: Copy,
@@ -349,6 +431,23 @@ where
     }
 }
 
+impl<T, F> DoubleEndedIterator for WithDepths<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (event, depth, node) = self.iter.next_back_with_depth()?;
+
+            if !matches!(event, Event::Up) {
+                return Some((depth, node));
+            }
+        }
+    }
+}
+
 impl<T, F> FusedIterator for WithDepths<'_, T, F>
 where
     T: Copy,
@@ -382,6 +481,148 @@ where
     }
 }
 
+/// An iterator that walks over the entire tree, visiting every node exactly
+/// once together with its parent. This is constructed with
+/// [`Walk::with_parents`].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "c1" => {
+///             "c2" => {},
+///             "c3" => {},
+///         },
+///         "c4" => {}
+///     },
+///     "root2" => {}
+/// };
+///
+/// let values = tree
+///     .walk()
+///     .with_parents()
+///     .map(|(p, n)| (p.map(|p| p.value()), n.value()))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     values,
+///     [
+///         (None, "root"),
+///         (Some("root"), "c1"),
+///         (Some("c1"), "c2"),
+///         (Some("c1"), "c3"),
+///         (Some("root"), "c4"),
+///         (None, "root2"),
+///     ]
+/// );
+///
+/// let c2 = tree.first().and_then(|n| n.first()).and_then(|n| n.first()).ok_or("missing c2")?;
+///
+/// let values = c2
+///     .walk()
+///     .with_parents()
+///     .map(|(p, n)| (p.map(|p| p.value()), n.value()))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     values,
+///     [
+///         (Some("c1"), "c2"),
+///         (Some("c1"), "c3"),
+///         (Some("root"), "c4"),
+///         (None, "root2"),
+///     ]
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct WithParents<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    iter: WalkEvents<'a, T, F>,
+    // The node most recently visited by the walk.
+    current: Option<Node<'a, T, F>>,
+    // The parent of the node most recently visited by the walk.
+    parent: Option<Node<'a, T, F>>,
+}
+
+impl<'a, T, F> Iterator for WithParents<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = (Option<Node<'a, T, F>>, Node<'a, T, F>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (event, node) = self.iter.next()?;
+
+            match event {
+                // Descending means that the previously visited node is the
+                // new parent.
+                Event::Down => {
+                    self.parent = self.current;
+                }
+                // Only the first node of the walk needs to look up its parent,
+                // siblings share it.
+                Event::Next => {
+                    if self.current.is_none() {
+                        self.parent = node.parent();
+                    }
+                }
+                Event::Up => {
+                    self.current = Some(node);
+                    self.parent = node.parent();
+                    continue;
+                }
+            }
+
+            self.current = Some(node);
+            return Some((self.parent, node));
+        }
+    }
+}
+
+impl<T, F> FusedIterator for WithParents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for WithParents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            current: self.current,
+            parent: self.parent,
+        }
+    }
+}
+
+impl<T, F> Default for WithParents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            iter: WalkEvents::default(),
+            current: None,
+            parent: None,
+        }
+    }
+}
+
 /// An iterator that limits the walk to inside the current node, visiting every
 /// node exactly once. This is constructed with [`Walk::inside`].
 ///
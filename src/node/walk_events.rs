@@ -1,8 +1,8 @@
 use core::iter::FusedIterator;
 
 use crate::flavor::Flavor;
-use crate::links::Links;
-use crate::node::Node;
+use crate::links::{Links, LinksOf};
+use crate::node::{Node, SpanEvents};
 use crate::pointer::Pointer;
 
 /// An event indicating how a tree is being walked with [`WalkEvents`].
@@ -22,6 +22,10 @@ pub enum Event {
 /// A low-level iterator which walks the tree while emitting [Event] instances
 /// indicating *how* the structure is being navigated.
 ///
+/// The walk is driven by the links stored in every node of the tree, so it
+/// doesn't keep a stack of parents and never allocates. Creating many short
+/// walks in a tight loop is as cheap as advancing a single one.
+///
 /// See [`Tree::walk_events`][crate::Tree::walk_events] or
 /// [`Node::walk_events`][crate::Node::walk_events].
 ///
@@ -136,17 +140,58 @@ pub enum Event {
 /// );
 /// # Ok::<_, Box<dyn core::error::Error>>(())
 /// ```
+///
+/// The same depths are available directly through
+/// [`WalkEvents::next_with_depth`].
+///
+/// Events can also be walked in reverse, which produces the exact reverse
+/// sequence of events:
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "c1" => {
+///             "c2" => {},
+///             "c3" => {},
+///         },
+///         "c4" => {}
+///     },
+///     "root2" => {}
+/// };
+///
+/// let forward = tree.walk_events().map(|(e, n)| (e, n.value())).collect::<Vec<_>>();
+/// let mut backward = tree.walk_events().rev().map(|(e, n)| (e, n.value())).collect::<Vec<_>>();
+/// backward.reverse();
+///
+/// assert_eq!(forward, backward);
+///
+/// let mut it = tree.walk_events().map(|(e, n)| (e, n.value()));
+/// let mut mixed = Vec::new();
+///
+/// while let (Some(a), b) = (it.next(), it.next_back()) {
+///     mixed.push(a);
+///     mixed.extend(b);
+/// }
+///
+/// assert_eq!(mixed.len(), forward.len());
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
 pub struct WalkEvents<'a, T, F>
 where
     T: Copy,
     F: Flavor,
 {
     /// The tree being iterated over.
-    tree: &'a [Links<T, F::Index, F::Pointer>],
+    tree: &'a [LinksOf<T, F>],
     // The current node.
     node: Option<(F::Pointer, Event)>,
     // Current depth being walked.
     depth: isize,
+    // The node at the back of the iterator, which is lazily initialized the
+    // first time the iterator is advanced from the back.
+    back: Option<(F::Pointer, Event)>,
+    // Depth of the node at the back of the iterator.
+    back_depth: isize,
 }
 
 impl<'a, T, F> WalkEvents<'a, T, F>
@@ -156,15 +201,13 @@ where
 {
     /// Construct a new events walker.
     #[inline]
-    pub(crate) fn new(
-        tree: &'a [Links<T, F::Index, F::Pointer>],
-        node: Option<F::Pointer>,
-        e: Event,
-    ) -> Self {
+    pub(crate) fn new(tree: &'a [LinksOf<T, F>], node: Option<F::Pointer>, e: Event) -> Self {
         Self {
             tree,
             node: node.map(|n| (n, e)),
             depth: 0,
+            back: None,
+            back_depth: 0,
         }
     }
 
@@ -174,11 +217,182 @@ where
         self.depth
     }
 
-    fn step(
-        &mut self,
-        links: &Links<T, F::Index, F::Pointer>,
-        event: Event,
-    ) -> Option<(F::Pointer, Event)> {
+    /// Convert this iterator into one which emits balanced enter and exit
+    /// events for the span and value of every node.
+    ///
+    /// See [`SpanEvents`] for documentation.
+    #[must_use]
+    pub const fn spans(self) -> SpanEvents<'a, T, F> {
+        SpanEvents::new(self)
+    }
+
+    /// Get the next event together with the depth of the node it refers to.
+    ///
+    /// The depth is relative to the node that the walk started at, so it is
+    /// negative for any ancestors visited after leaving it. Note that for
+    /// [`Event::Up`] the depth is that of the node being returned to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::node::Event::*;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "c1" => {
+    ///             "c2" => {},
+    ///         },
+    ///         "c3" => {}
+    ///     }
+    /// };
+    ///
+    /// let c1 = tree.first().and_then(|n| n.first()).ok_or("missing c1")?;
+    ///
+    /// let mut it = c1.walk_events();
+    /// let mut events = Vec::new();
+    ///
+    /// while let Some((e, depth, n)) = it.next_with_depth() {
+    ///     events.push((e, depth, n.value()));
+    /// }
+    ///
+    /// assert_eq!(
+    ///     events,
+    ///     [
+    ///         (Next, 0, "c1"),
+    ///         (Down, 1, "c2"),
+    ///         (Up, 0, "c1"),
+    ///         (Next, 0, "c3"),
+    ///         (Up, -1, "root"),
+    ///     ]
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn next_with_depth(&mut self) -> Option<(Event, isize, Node<'a, T, F>)> {
+        let (node, event) = self.node.take()?;
+        let links = self.tree.get(node.get())?;
+        let depth = self.depth;
+
+        if self
+            .back
+            .is_some_and(|back| same(links, back, (node, event)))
+        {
+            self.back = None;
+        } else {
+            self.node = self.step(links, event);
+        }
+
+        Some((event, depth, Node::new(links, self.tree)))
+    }
+
+    /// Get the next event from the back together with the depth of the node it
+    /// refers to.
+    ///
+    /// Depths are the same as the ones produced by
+    /// [`WalkEvents::next_with_depth`], so they are relative to the node that
+    /// the walk started at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::node::Event::*;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "c1" => {
+    ///             "c2" => {},
+    ///         },
+    ///         "c3" => {}
+    ///     }
+    /// };
+    ///
+    /// let mut it = tree.walk_events();
+    /// let mut events = Vec::new();
+    ///
+    /// while let Some((e, depth, n)) = it.next_back_with_depth() {
+    ///     events.push((e, depth, n.value()));
+    /// }
+    ///
+    /// assert_eq!(
+    ///     events,
+    ///     [
+    ///         (Up, 0, "root"),
+    ///         (Next, 1, "c3"),
+    ///         (Up, 1, "c1"),
+    ///         (Down, 2, "c2"),
+    ///         (Down, 1, "c1"),
+    ///         (Next, 0, "root"),
+    ///     ]
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn next_back_with_depth(&mut self) -> Option<(Event, isize, Node<'a, T, F>)> {
+        if self.back.is_none() {
+            self.back = self.init_back();
+        }
+
+        let (node, mut event) = self.back.take()?;
+        let links = self.tree.get(node.get())?;
+        let depth = self.back_depth;
+
+        match self.node {
+            Some(front) if same(links, front, (node, event)) => {
+                // The event at the front determines how the walk was entered.
+                event = front.1;
+                self.node = None;
+            }
+            _ => {
+                self.back = self.step_back(links, event);
+
+                // Walking past the start of the tree means that there is
+                // nothing left at the front either.
+                if self.back.is_none() {
+                    self.node = None;
+                }
+            }
+        }
+
+        Some((event, depth, Node::new(links, self.tree)))
+    }
+
+    /// Find the last event of the walk, which is the last event of the last
+    /// top-level sibling reachable from the front.
+    fn init_back(&mut self) -> Option<(F::Pointer, Event)> {
+        let (mut id, _) = self.node?;
+        let mut links = self.tree.get(id.get())?;
+        let mut depth = self.depth;
+
+        while let Some(parent) = links.parent {
+            depth = depth.checked_sub(1)?;
+            id = parent;
+            links = self.tree.get(id.get())?;
+        }
+
+        while let Some(next) = links.next {
+            id = next;
+            links = self.tree.get(id.get())?;
+        }
+
+        self.back_depth = depth;
+        Some(last(id, links))
+    }
+
+    fn step_back(&mut self, links: &LinksOf<T, F>, event: Event) -> Option<(F::Pointer, Event)> {
+        if let Event::Up = event {
+            let id = links.last?;
+            self.back_depth = self.back_depth.checked_add(1)?;
+            return Some(last(id, self.tree.get(id.get())?));
+        }
+
+        if let Some(id) = links.prev {
+            return Some(last(id, self.tree.get(id.get())?));
+        }
+
+        let id = links.parent?;
+        self.back_depth = self.back_depth.checked_sub(1)?;
+        Some((id, enter(self.tree.get(id.get())?)))
+    }
+
+    fn step(&mut self, links: &LinksOf<T, F>, event: Event) -> Option<(F::Pointer, Event)> {
         if let Event::Up = event {
             if let Some(next) = links.next {
                 return Some((next, Event::Next));
@@ -210,6 +424,8 @@ where
             tree: self.tree,
             node: self.node,
             depth: self.depth,
+            back: self.back,
+            back_depth: self.back_depth,
         }
     }
 }
@@ -225,6 +441,8 @@ where
             tree: &[],
             node: None,
             depth: 0,
+            back: None,
+            back_depth: 0,
         }
     }
 }
@@ -236,11 +454,21 @@ where
 {
     type Item = (Event, Node<'a, T, F>);
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (node, event) = self.node.take()?;
-        let links = self.tree.get(node.get())?;
-        self.node = self.step(links, event);
-        let node = Node::new(links, self.tree);
+        let (event, _, node) = self.next_with_depth()?;
+        Some((event, node))
+    }
+}
+
+impl<T, F> DoubleEndedIterator for WalkEvents<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (event, _, node) = self.next_back_with_depth()?;
         Some((event, node))
     }
 }
@@ -251,3 +479,48 @@ where
     F: Flavor,
 {
 }
+
+/// Test if two positions in a walk of the node with `links` refer to the same
+/// event, which is the case if they refer to the same node and either both or
+/// neither are leaving it.
+///
+/// A node without children is only visited once, so entering and leaving it
+/// are the same event. This happens when walking from a token with
+/// [`Node::walk_from`][crate::Node::walk_from].
+#[inline]
+fn same<T, I, P, G>(links: &Links<T, I, P, G>, a: (P, Event), b: (P, Event)) -> bool
+where
+    T: Copy,
+    P: Pointer,
+{
+    a.0 == b.0 && (links.first.is_none() || matches!(a.1, Event::Up) == matches!(b.1, Event::Up))
+}
+
+/// The event with which the given node is entered.
+#[inline]
+fn enter<T, I, P, G>(links: &Links<T, I, P, G>) -> Event
+where
+    T: Copy,
+    P: Pointer,
+{
+    if links.parent.is_some() && links.prev.is_none() {
+        Event::Down
+    } else {
+        Event::Next
+    }
+}
+
+/// The last event emitted for the node `id`, which is leaving it if it has
+/// any children.
+#[inline]
+fn last<T, I, P, G>(id: P, links: &Links<T, I, P, G>) -> (P, Event)
+where
+    T: Copy,
+    P: Pointer,
+{
+    if links.last.is_some() {
+        (id, Event::Up)
+    } else {
+        (id, enter(links))
+    }
+}
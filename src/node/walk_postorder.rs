@@ -0,0 +1,237 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::node::{Node, SkipTokens};
+
+/// An iterator that walks over the tree in postorder, visiting every node
+/// exactly once after all of its descendants.
+///
+/// This is useful for passes such as type inference, where the result for a
+/// node depends on the result of its children. Walking in reverse visits
+/// every node before its descendants, with the last child first.
+///
+/// The walk follows the links stored in the tree and doesn't allocate.
+///
+/// See [`Tree::walk_postorder`][crate::Tree::walk_postorder] or
+/// [`Node::walk_postorder`].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "c1" => {
+///             "c2" => {},
+///             "c3" => {},
+///         },
+///         "c4" => {},
+///         "c5" => {
+///             "c6" => {},
+///         }
+///     },
+///     "root2" => {}
+/// };
+///
+/// let values = tree.walk_postorder().map(|n| n.value());
+/// assert!(values.eq(["c2", "c3", "c1", "c4", "c6", "c5", "root", "root2"]));
+///
+/// let values = tree.walk_postorder().rev().map(|n| n.value());
+/// assert!(values.eq(["root2", "root", "c5", "c6", "c4", "c1", "c3", "c2"]));
+///
+/// // Walking from a node only visits its subtree.
+/// let c1 = tree.first().and_then(|n| n.first()).ok_or("missing c1")?;
+///
+/// let values = c1.walk_postorder().map(|n| n.value());
+/// assert!(values.eq(["c2", "c3", "c1"]));
+///
+/// // Walking from both ends visits every node exactly once.
+/// let mut it = tree.walk_postorder();
+/// assert_eq!(it.next().map(|n| n.value()), Some("c2"));
+/// assert_eq!(it.next_back().map(|n| n.value()), Some("root2"));
+///
+/// let values = it.map(|n| n.value());
+/// assert!(values.eq(["c3", "c1", "c4", "c6", "c5", "root"]));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct WalkPostorder<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// The node at which the walk is limited to, or `None` if the walk covers
+    /// the whole tree.
+    root: Option<Node<'a, T, F>>,
+    /// The next node to visit from the front.
+    front: Option<Node<'a, T, F>>,
+    /// The next node to visit from the back.
+    back: Option<Node<'a, T, F>>,
+}
+
+impl<'a, T, F> WalkPostorder<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// Construct a walk over the whole tree, where `first` and `last` are the
+    /// first and last top-level nodes.
+    #[inline]
+    pub(crate) fn new(first: Option<Node<'a, T, F>>, last: Option<Node<'a, T, F>>) -> Self {
+        Self {
+            root: None,
+            front: first.map(leftmost),
+            back: last,
+        }
+    }
+
+    /// Construct a walk over the subtree of `node`.
+    #[inline]
+    pub(crate) fn subtree(node: Node<'a, T, F>) -> Self {
+        Self {
+            root: Some(node),
+            front: Some(leftmost(node)),
+            back: Some(node),
+        }
+    }
+
+    /// Construct a [`SkipTokens`] iterator from the remainder of this iterator.
+    /// This filters out childless nodes, also known as tokens.
+    ///
+    /// See [`SkipTokens`] for documentation.
+    #[inline]
+    #[must_use]
+    pub const fn skip_tokens(self) -> SkipTokens<Self> {
+        SkipTokens::new(self)
+    }
+
+    /// Test if `node` is the root of the walk.
+    #[inline]
+    fn is_root(&self, node: &Node<'a, T, F>) -> bool {
+        self.root.is_some_and(|root| root.id() == node.id())
+    }
+
+    /// Mark the walk as finished if both ends have reached `node`.
+    #[inline]
+    fn meet(&mut self, node: &Node<'a, T, F>, other: Option<Node<'a, T, F>>) -> bool {
+        if other.is_some_and(|other| other.id() == node.id()) {
+            self.front = None;
+            self.back = None;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Find the first node to visit in postorder in the subtree of `node`.
+#[inline]
+fn leftmost<T, F>(mut node: Node<'_, T, F>) -> Node<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    while let Some(first) = node.first() {
+        node = first;
+    }
+
+    node
+}
+
+impl<'a, T, F> Iterator for WalkPostorder<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = Node<'a, T, F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.take()?;
+
+        if self.meet(&node, self.back) {
+            return Some(node);
+        }
+
+        if !self.is_root(&node) {
+            self.front = match node.next() {
+                Some(next) => Some(leftmost(next)),
+                None => node.parent(),
+            };
+        }
+
+        Some(node)
+    }
+}
+
+impl<T, F> DoubleEndedIterator for WalkPostorder<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.take()?;
+
+        if self.meet(&node, self.front) {
+            return Some(node);
+        }
+
+        if let Some(last) = node.last() {
+            self.back = Some(last);
+            return Some(node);
+        }
+
+        let mut current = node;
+
+        while !self.is_root(&current) {
+            if let Some(prev) = current.prev() {
+                self.back = Some(prev);
+                break;
+            }
+
+            let Some(parent) = current.parent() else {
+                break;
+            };
+
+            current = parent;
+        }
+
+        Some(node)
+    }
+}
+
+impl<T, F> FusedIterator for WalkPostorder<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for WalkPostorder<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+impl<T, F> Default for WalkPostorder<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            root: None,
+            front: None,
+            back: None,
+        }
+    }
+}
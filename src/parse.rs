@@ -0,0 +1,306 @@
+//! Small combinators for writing parsers on top of a [`TreeSink`].
+//!
+//! The helpers in this module operate on any parser implementing
+//! [`TokenSource`], which is a stream of tokens that emits every token it
+//! consumes into the tree sink that it wraps. They capture the idioms which
+//! most hand-written parsers end up re-deriving, such as wrapping a production
+//! in a node or recovering from errors by skipping tokens until a known
+//! delimiter.
+//!
+//! # Examples
+//!
+//! ```
+//! use syntree::parse::{self, TokenSource};
+//! use syntree::{Builder, Checkpoint, Error, TreeSink};
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! enum Syntax {
+//!     Ident,
+//!     Number,
+//!     Comma,
+//!     Open,
+//!     Close,
+//!     Args,
+//!     Call,
+//! }
+//!
+//! use Syntax::*;
+//!
+//! struct Parser<'a> {
+//!     tokens: &'a [(Syntax, usize)],
+//!     builder: Builder<Syntax>,
+//! }
+//!
+//! impl TreeSink<Syntax> for Parser<'_> {
+//!     type Error = Error;
+//!     type Length = usize;
+//!     type Checkpoint = Checkpoint<syntree::pointer::PointerUsize>;
+//!
+//!     fn open(&mut self, value: Syntax) -> Result<(), Error> {
+//!         TreeSink::open(&mut self.builder, value)
+//!     }
+//!
+//!     fn close(&mut self) -> Result<(), Error> {
+//!         TreeSink::close(&mut self.builder)
+//!     }
+//!
+//!     fn token(&mut self, value: Syntax, len: usize) -> Result<(), Error> {
+//!         TreeSink::token(&mut self.builder, value, len)
+//!     }
+//!
+//!     fn checkpoint(&mut self) -> Result<Self::Checkpoint, Error> {
+//!         TreeSink::checkpoint(&mut self.builder)
+//!     }
+//!
+//!     fn close_at(&mut self, c: &Self::Checkpoint, value: Syntax) -> Result<(), Error> {
+//!         TreeSink::close_at(&mut self.builder, c, value)
+//!     }
+//! }
+//!
+//! impl TokenSource<Syntax> for Parser<'_> {
+//!     fn peek_n(&mut self, n: usize) -> Result<Option<Syntax>, Error> {
+//!         Ok(self.tokens.get(n).map(|&(value, _)| value))
+//!     }
+//!
+//!     fn bump(&mut self) -> Result<bool, Error> {
+//!         let Some((&(value, len), rest)) = self.tokens.split_first() else {
+//!             return Ok(false);
+//!         };
+//!
+//!         self.tokens = rest;
+//!         self.builder.token(value, len)?;
+//!         Ok(true)
+//!     }
+//! }
+//!
+//! fn call(p: &mut Parser<'_>) -> Result<(), Error> {
+//!     parse::wrap(p, Call, |p| {
+//!         parse::eat(p, Ident)?;
+//!
+//!         parse::wrap(p, Args, |p| {
+//!             parse::delimited(p, Open, Close, |p| {
+//!                 parse::separated_list(p, Comma, |p| parse::eat(p, Number))
+//!             })
+//!         })?;
+//!
+//!         Ok(())
+//!     })
+//! }
+//!
+//! let mut p = Parser {
+//!     tokens: &[(Ident, 3), (Open, 1), (Number, 1), (Comma, 1), (Number, 2), (Close, 1)],
+//!     builder: Builder::new(),
+//! };
+//!
+//! call(&mut p)?;
+//!
+//! let tree = p.builder.build()?;
+//!
+//! let expected = syntree::tree! {
+//!     Call => {
+//!         (Ident, 3),
+//!         Args => {
+//!             (Open, 1),
+//!             (Number, 1),
+//!             (Comma, 1),
+//!             (Number, 2),
+//!             (Close, 1),
+//!         }
+//!     }
+//! };
+//!
+//! assert_eq!(tree, expected);
+//! # Ok::<_, Box<dyn core::error::Error>>(())
+//! ```
+
+mod lookahead;
+
+use crate::builder::TreeSink;
+
+pub use self::lookahead::Lookahead;
+
+/// A stream of tokens which emits every token it consumes into a
+/// [`TreeSink`].
+///
+/// This is the interface which the combinators in this [module][self] use to
+/// drive a parser.
+///
+/// See [`Lookahead`] for an implementation which buffers tokens from an
+/// iterator and feeds them into a [`Builder`][crate::Builder].
+pub trait TokenSource<T>: TreeSink<T> {
+    /// Peek the value of the token `n` steps ahead without consuming anything,
+    /// or `None` if the end of input is reached before it.
+    ///
+    /// `peek_n(0)` is the next token.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the tokens could not be read.
+    fn peek_n(&mut self, n: usize) -> Result<Option<T>, Self::Error>;
+
+    /// Peek the value of the next token without consuming it, or `None` if
+    /// the end of input has been reached.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the next token could not be read.
+    #[inline]
+    fn peek(&mut self) -> Result<Option<T>, Self::Error> {
+        self.peek_n(0)
+    }
+
+    /// Test if the value of the next token is `value`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the next token could not be read.
+    #[inline]
+    fn at(&mut self, value: T) -> Result<bool, Self::Error>
+    where
+        T: PartialEq,
+    {
+        Ok(self.peek()? == Some(value))
+    }
+
+    /// Consume the next token and emit it into the sink.
+    ///
+    /// Returns `false` if the end of input has been reached.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the next token could not be read or emitted.
+    fn bump(&mut self) -> Result<bool, Self::Error>;
+}
+
+/// Consume the next token if its value is `value`.
+///
+/// Returns `true` if a token was consumed.
+///
+/// # Errors
+///
+/// Errors if the underlying source or sink errors.
+pub fn eat<P, T>(p: &mut P, value: T) -> Result<bool, P::Error>
+where
+    P: ?Sized + TokenSource<T>,
+    T: PartialEq,
+{
+    if !p.at(value)? {
+        return Ok(false);
+    }
+
+    p.bump()
+}
+
+/// Consume tokens until one whose value is in `any` is reached, or until the
+/// end of input.
+///
+/// This is the basic building block for error recovery, since the skipped
+/// tokens are still emitted into the sink and therefore preserved in the tree.
+///
+/// Returns the number of tokens which were skipped.
+///
+/// # Errors
+///
+/// Errors if the underlying source or sink errors.
+pub fn skip_until<P, T>(p: &mut P, any: &[T]) -> Result<usize, P::Error>
+where
+    P: ?Sized + TokenSource<T>,
+    T: PartialEq,
+{
+    let mut count = 0;
+
+    while let Some(value) = p.peek()? {
+        if any.contains(&value) || !p.bump()? {
+            break;
+        }
+
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Wrap everything emitted by `f` in a node with the given `value`.
+///
+/// This takes a checkpoint before calling `f` and closes it afterwards, so the
+/// node is inserted even if `f` doesn't emit anything.
+///
+/// # Errors
+///
+/// Errors if `f` or the underlying sink errors.
+pub fn wrap<P, T, O>(
+    p: &mut P,
+    value: T,
+    f: impl FnOnce(&mut P) -> Result<O, P::Error>,
+) -> Result<O, P::Error>
+where
+    P: ?Sized + TokenSource<T>,
+{
+    let c = p.checkpoint()?;
+    let output = f(p)?;
+    p.close_at(&c, value)?;
+    Ok(output)
+}
+
+/// Parse a production enclosed by the `open` and `close` tokens.
+///
+/// If the next token is not `open`, nothing is consumed and `false` is
+/// returned. Otherwise `f` is called after the `open` token has been consumed,
+/// after which any tokens left before `close` are skipped and the `close` token
+/// is consumed. A missing `close` token at the end of input is tolerated.
+///
+/// # Errors
+///
+/// Errors if `f` or the underlying source or sink errors.
+pub fn delimited<P, T, O>(
+    p: &mut P,
+    open: T,
+    close: T,
+    f: impl FnOnce(&mut P) -> Result<O, P::Error>,
+) -> Result<bool, P::Error>
+where
+    P: ?Sized + TokenSource<T>,
+    T: PartialEq,
+{
+    if !eat(p, open)? {
+        return Ok(false);
+    }
+
+    f(p)?;
+    skip_until(p, core::slice::from_ref(&close))?;
+    eat(p, close)?;
+    Ok(true)
+}
+
+/// Parse a list of items separated by `separator` tokens.
+///
+/// The item production `f` returns whether it parsed anything, and the list
+/// ends as soon as it doesn't or when an item is not followed by a separator.
+/// A trailing separator is therefore permitted.
+///
+/// Returns the number of items which were parsed.
+///
+/// # Errors
+///
+/// Errors if `f` or the underlying source or sink errors.
+pub fn separated_list<P, T>(
+    p: &mut P,
+    separator: T,
+    mut f: impl FnMut(&mut P) -> Result<bool, P::Error>,
+) -> Result<usize, P::Error>
+where
+    P: ?Sized + TokenSource<T>,
+    T: Copy + PartialEq,
+{
+    let mut count = 0;
+
+    while f(p)? {
+        count += 1;
+
+        if !eat(p, separator)? {
+            break;
+        }
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,226 @@
+use alloc::collections::VecDeque;
+
+use crate::builder::TreeSink;
+
+use super::TokenSource;
+
+/// A [`TokenSource`] which buffers tokens from an iterator and feeds every
+/// token it consumes into a [`TreeSink`], such as a [`Builder`].
+///
+/// Tokens for which the trivia predicate returns `true`, such as whitespace or
+/// comments, are never returned when peeking. Instead they are transparently
+/// emitted into the sink as soon as they are at the head of the stream, so
+/// they end up in whichever node is open when the parser looks at the next
+/// token.
+///
+/// [`Builder`]: crate::Builder
+///
+/// # Examples
+///
+/// ```
+/// use syntree::parse::{self, Lookahead, TokenSource};
+/// use syntree::Builder;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Syntax {
+///     Number,
+///     Plus,
+///     Whitespace,
+///     Operation,
+/// }
+///
+/// use Syntax::*;
+///
+/// let tokens = [(Number, 3), (Whitespace, 1), (Plus, 1), (Whitespace, 1), (Number, 2)];
+///
+/// let mut p = Lookahead::new(Builder::new(), tokens, |t| t == Whitespace);
+///
+/// assert_eq!(p.peek_n(1)?, Some(Plus));
+/// assert!(p.at(Number)?);
+///
+/// parse::wrap(&mut p, Operation, |p| {
+///     p.bump()?;
+///
+///     while parse::eat(p, Plus)? {
+///         parse::eat(p, Number)?;
+///     }
+///
+///     Ok(())
+/// })?;
+///
+/// let tree = p.into_sink().build()?;
+///
+/// let expected = syntree::tree! {
+///     Operation => {
+///         (Number, 3),
+///         (Whitespace, 1),
+///         (Plus, 1),
+///         (Whitespace, 1),
+///         (Number, 2),
+///     }
+/// };
+///
+/// assert_eq!(tree, expected);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct Lookahead<S, I, P>
+where
+    I: Iterator,
+{
+    sink: S,
+    tokens: I,
+    is_trivia: P,
+    buf: VecDeque<I::Item>,
+}
+
+impl<S, I, P, T, L> Lookahead<S, I, P>
+where
+    S: TreeSink<T, Length = L>,
+    I: Iterator<Item = (T, L)>,
+    P: FnMut(T) -> bool,
+    T: Copy,
+{
+    /// Construct a new token source reading from `tokens` and emitting into
+    /// `sink`, where `is_trivia` determines which tokens are trivia.
+    ///
+    /// Use a predicate which always returns `false` if the input has no
+    /// trivia.
+    pub fn new<U>(sink: S, tokens: U, is_trivia: P) -> Self
+    where
+        U: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            sink,
+            tokens: tokens.into_iter(),
+            is_trivia,
+            buf: VecDeque::new(),
+        }
+    }
+
+    /// Access the sink tokens are emitted into.
+    #[inline]
+    pub const fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Access the sink tokens are emitted into mutably.
+    #[inline]
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Consume the token source and return the sink.
+    ///
+    /// Any tokens which have been buffered but not consumed are discarded.
+    #[inline]
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// Emit any trivia at the head of the stream into the sink.
+    fn flush_trivia(&mut self) -> Result<(), S::Error> {
+        loop {
+            if self.buf.is_empty() {
+                let Some(token) = self.tokens.next() else {
+                    return Ok(());
+                };
+
+                self.buf.push_back(token);
+            }
+
+            match self.buf.front() {
+                Some(&(value, _)) if (self.is_trivia)(value) => {
+                    if let Some((value, len)) = self.buf.pop_front() {
+                        self.sink.token(value, len)?;
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+impl<S, I, P, T, L> TreeSink<T> for Lookahead<S, I, P>
+where
+    S: TreeSink<T, Length = L>,
+    I: Iterator<Item = (T, L)>,
+{
+    type Error = S::Error;
+    type Length = L;
+    type Checkpoint = S::Checkpoint;
+
+    #[inline]
+    fn open(&mut self, value: T) -> Result<(), Self::Error> {
+        self.sink.open(value)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Self::Error> {
+        self.sink.close()
+    }
+
+    #[inline]
+    fn token(&mut self, value: T, len: Self::Length) -> Result<(), Self::Error> {
+        self.sink.token(value, len)
+    }
+
+    #[inline]
+    fn checkpoint(&mut self) -> Result<Self::Checkpoint, Self::Error> {
+        self.sink.checkpoint()
+    }
+
+    #[inline]
+    fn close_at(&mut self, c: &Self::Checkpoint, value: T) -> Result<(), Self::Error> {
+        self.sink.close_at(c, value)
+    }
+}
+
+impl<S, I, P, T, L> TokenSource<T> for Lookahead<S, I, P>
+where
+    S: TreeSink<T, Length = L>,
+    I: Iterator<Item = (T, L)>,
+    P: FnMut(T) -> bool,
+    T: Copy,
+{
+    fn peek_n(&mut self, n: usize) -> Result<Option<T>, Self::Error> {
+        self.flush_trivia()?;
+
+        let mut seen = 0;
+
+        for &(value, _) in &self.buf {
+            if !(self.is_trivia)(value) {
+                if seen == n {
+                    return Ok(Some(value));
+                }
+
+                seen += 1;
+            }
+        }
+
+        for token in self.tokens.by_ref() {
+            let value = token.0;
+            self.buf.push_back(token);
+
+            if !(self.is_trivia)(value) {
+                if seen == n {
+                    return Ok(Some(value));
+                }
+
+                seen += 1;
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn bump(&mut self) -> Result<bool, Self::Error> {
+        self.flush_trivia()?;
+
+        let Some((value, len)) = self.buf.pop_front() else {
+            return Ok(false);
+        };
+
+        self.sink.token(value, len)?;
+        Ok(true)
+    }
+}
@@ -1,8 +1,16 @@
 //! Pointer-related types and traits.
 
+mod pointer_map;
+mod pointer_set;
+
 use core::fmt;
 use core::hash;
 
+#[cfg(feature = "alloc")]
+pub use self::pointer_map::{PointerMap, PointerMapIter};
+#[cfg(feature = "alloc")]
+pub use self::pointer_set::{PointerSet, PointerSetIter};
+
 mod sealed {
     pub trait Sealed {}
 }
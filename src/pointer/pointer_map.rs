@@ -0,0 +1,313 @@
+#![cfg(feature = "alloc")]
+
+use core::fmt;
+use core::iter::{Enumerate, FusedIterator};
+use core::marker::PhantomData;
+use core::slice;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::pointer::Pointer;
+
+/// A map from node identifiers to values backed by a dense vector.
+///
+/// Since node identifiers are dense indexes into a tree, this is both faster
+/// than a hash map and iterates in a deterministic order, which is ordered by
+/// identifier.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::FlavorDefault;
+/// use syntree::pointer::PointerMap;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "number" => {
+///             ("lit", 3)
+///         },
+///         ("ws", 1),
+///         "number" => {
+///             ("lit", 2)
+///         }
+///     }
+/// };
+///
+/// let mut depths = PointerMap::<FlavorDefault, _>::new();
+///
+/// for (depth, node) in tree.walk().with_depths() {
+///     depths.insert(node.id(), depth);
+/// }
+///
+/// let lit = tree.first().and_then(|n| n.first()).and_then(|n| n.first()).ok_or("missing lit")?;
+/// assert_eq!(depths.get(lit.id()), Some(&2));
+/// assert_eq!(depths.len(), 6);
+///
+/// let values = depths.iter().map(|(id, depth)| (tree.get(id).map(|n| n.value()), *depth));
+/// assert!(values.eq([
+///     (Some("root"), 0),
+///     (Some("number"), 1),
+///     (Some("lit"), 2),
+///     (Some("ws"), 1),
+///     (Some("number"), 1),
+///     (Some("lit"), 2),
+/// ]));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct PointerMap<F, V>
+where
+    F: Flavor,
+{
+    values: Vec<Option<V>>,
+    len: usize,
+    _marker: PhantomData<F::Pointer>,
+}
+
+impl<F, V> PointerMap<F, V>
+where
+    F: Flavor,
+{
+    /// Construct a new empty map.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Insert `value` for the node identified by `id`, returning the value
+    /// which was previously associated with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::{Pointer, PointerMap, PointerUsize};
+    ///
+    /// let id = PointerUsize::new(3).ok_or("out of bounds")?;
+    ///
+    /// let mut map = PointerMap::<syntree::FlavorDefault, _>::new();
+    /// assert_eq!(map.insert(id, "a"), None);
+    /// assert_eq!(map.insert(id, "b"), Some("a"));
+    /// assert_eq!(map.get(id), Some(&"b"));
+    /// assert_eq!(map.remove(id), Some("b"));
+    /// assert!(map.is_empty());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn insert(&mut self, id: F::Pointer, value: V) -> Option<V> {
+        let n = id.get();
+
+        if self.values.len() <= n {
+            self.values.resize_with(n + 1, || None);
+        }
+
+        let old = self.values.get_mut(n)?.replace(value);
+
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    /// Remove the value associated with the node identified by `id`.
+    pub fn remove(&mut self, id: F::Pointer) -> Option<V> {
+        let value = self.values.get_mut(id.get())?.take()?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Get the value associated with the node identified by `id`.
+    #[must_use]
+    pub fn get(&self, id: F::Pointer) -> Option<&V> {
+        self.values.get(id.get())?.as_ref()
+    }
+
+    /// Get the value associated with the node identified by `id` mutably.
+    #[must_use]
+    pub fn get_mut(&mut self, id: F::Pointer) -> Option<&mut V> {
+        self.values.get_mut(id.get())?.as_mut()
+    }
+
+    /// Test if the map has a value for the node identified by `id`.
+    #[must_use]
+    pub fn contains(&self, id: F::Pointer) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Get the number of nodes in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Test if the map is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.len = 0;
+    }
+
+    /// Iterate over the entries of the map ordered by identifier.
+    #[must_use]
+    pub fn iter(&self) -> PointerMapIter<'_, F, V> {
+        PointerMapIter {
+            iter: self.values.iter().enumerate(),
+            remaining: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, V> Default for PointerMap<F, V>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F, V> Clone for PointerMap<F, V>
+where
+    F: Flavor,
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F, V> PartialEq for PointerMap<F, V>
+where
+    F: Flavor,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<F, V> Eq for PointerMap<F, V>
+where
+    F: Flavor,
+    V: Eq,
+{
+}
+
+impl<F, V> fmt::Debug for PointerMap<F, V>
+where
+    F: Flavor,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<F, V> FromIterator<(F::Pointer, V)> for PointerMap<F, V>
+where
+    F: Flavor,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (F::Pointer, V)>,
+    {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<F, V> Extend<(F::Pointer, V)> for PointerMap<F, V>
+where
+    F: Flavor,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (F::Pointer, V)>,
+    {
+        for (id, value) in iter {
+            self.insert(id, value);
+        }
+    }
+}
+
+impl<'a, F, V> IntoIterator for &'a PointerMap<F, V>
+where
+    F: Flavor,
+{
+    type Item = (F::Pointer, &'a V);
+    type IntoIter = PointerMapIter<'a, F, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`PointerMap`].
+///
+/// See [`PointerMap::iter`].
+pub struct PointerMapIter<'a, F, V>
+where
+    F: Flavor,
+{
+    iter: Enumerate<slice::Iter<'a, Option<V>>>,
+    remaining: usize,
+    _marker: PhantomData<F::Pointer>,
+}
+
+impl<'a, F, V> Iterator for PointerMapIter<'a, F, V>
+where
+    F: Flavor,
+{
+    type Item = (F::Pointer, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (n, value) = self.iter.next()?;
+
+            if let Some(value) = value {
+                self.remaining -= 1;
+                return Some((F::Pointer::new(n)?, value));
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<F, V> ExactSizeIterator for PointerMapIter<'_, F, V> where F: Flavor {}
+
+impl<F, V> FusedIterator for PointerMapIter<'_, F, V> where F: Flavor {}
+
+impl<F, V> Clone for PointerMapIter<'_, F, V>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+            remaining: self.remaining,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,419 @@
+#![cfg(feature = "alloc")]
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::slice;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::pointer::Pointer;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of node identifiers backed by a bitset.
+///
+/// Since node identifiers are dense indexes into a tree, this is both faster
+/// and more compact than collecting them into a hash set. Iteration is always
+/// ordered by identifier, which for a tree constructed through a
+/// [`Builder`][crate::Builder] is usually the order in which nodes were
+/// added.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::FlavorDefault;
+/// use syntree::pointer::PointerSet;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "a" => {
+///             ("lit", 1)
+///         },
+///         "b" => {
+///             ("lit", 1),
+///             ("ws", 1),
+///         }
+///     }
+/// };
+///
+/// let literals = tree
+///     .walk()
+///     .filter(|n| n.value() == "lit")
+///     .map(|n| n.id())
+///     .collect::<PointerSet<FlavorDefault>>();
+///
+/// let b = tree.first().and_then(|n| n.last()).ok_or("missing b")?;
+/// let mut in_b = b.children().map(|n| n.id()).collect::<PointerSet<FlavorDefault>>();
+/// in_b.intersect_with(&literals);
+///
+/// assert_eq!(literals.len(), 2);
+/// assert_eq!(in_b.len(), 1);
+/// assert!(in_b.iter().all(|id| tree.get(id).is_some_and(|n| n.value() == "lit")));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct PointerSet<F>
+where
+    F: Flavor,
+{
+    words: Vec<u64>,
+    len: usize,
+    _marker: PhantomData<F::Pointer>,
+}
+
+impl<F> PointerSet<F>
+where
+    F: Flavor,
+{
+    /// Construct a new empty set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Insert `id` into the set, returning `true` if it wasn't already
+    /// present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::{Pointer, PointerSet, PointerUsize};
+    ///
+    /// let id = PointerUsize::new(70).ok_or("out of bounds")?;
+    ///
+    /// let mut set = PointerSet::<syntree::FlavorDefault>::new();
+    /// assert!(set.insert(id));
+    /// assert!(!set.insert(id));
+    /// assert!(set.contains(id));
+    /// assert!(set.remove(id));
+    /// assert!(set.is_empty());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn insert(&mut self, id: F::Pointer) -> bool {
+        let (word, bit) = split(id);
+
+        if self.words.len() <= word {
+            self.words.resize(word + 1, 0);
+        }
+
+        let Some(word) = self.words.get_mut(word) else {
+            return false;
+        };
+
+        if *word & bit != 0 {
+            return false;
+        }
+
+        *word |= bit;
+        self.len += 1;
+        true
+    }
+
+    /// Remove `id` from the set, returning `true` if it was present.
+    pub fn remove(&mut self, id: F::Pointer) -> bool {
+        let (word, bit) = split(id);
+
+        let Some(word) = self.words.get_mut(word) else {
+            return false;
+        };
+
+        if *word & bit == 0 {
+            return false;
+        }
+
+        *word &= !bit;
+        self.len -= 1;
+        true
+    }
+
+    /// Test if the set contains `id`.
+    #[must_use]
+    pub fn contains(&self, id: F::Pointer) -> bool {
+        let (word, bit) = split(id);
+        self.words.get(word).is_some_and(|w| *w & bit != 0)
+    }
+
+    /// Get the number of identifiers in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Test if the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all identifiers from the set.
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    /// Iterate over the identifiers in the set in ascending order.
+    #[must_use]
+    pub fn iter(&self) -> PointerSetIter<'_, F> {
+        PointerSetIter {
+            words: self.words.iter(),
+            current: 0,
+            base: 0,
+            next: 0,
+            remaining: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add all identifiers in `other` to this set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::{Pointer, PointerSet, PointerUsize};
+    ///
+    /// let ids = (0..4).map(PointerUsize::new).collect::<Option<Vec<_>>>().ok_or("out of bounds")?;
+    ///
+    /// let mut a = PointerSet::<syntree::FlavorDefault>::from_iter([ids[0], ids[1]]);
+    /// let b = PointerSet::from_iter([ids[1], ids[3]]);
+    ///
+    /// a.union_with(&b);
+    /// assert!(a.iter().eq([ids[0], ids[1], ids[3]]));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn union_with(&mut self, other: &Self) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= *b;
+        }
+
+        self.recount();
+    }
+
+    /// Retain only the identifiers which are also in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::{Pointer, PointerSet, PointerUsize};
+    ///
+    /// let ids = (0..4).map(PointerUsize::new).collect::<Option<Vec<_>>>().ok_or("out of bounds")?;
+    ///
+    /// let mut a = PointerSet::<syntree::FlavorDefault>::from_iter([ids[0], ids[1]]);
+    /// let b = PointerSet::from_iter([ids[1], ids[3]]);
+    ///
+    /// a.intersect_with(&b);
+    /// assert!(a.iter().eq([ids[1]]));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words.truncate(other.words.len());
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= *b;
+        }
+
+        self.recount();
+    }
+
+    /// Remove all identifiers which are in `other` from this set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::{Pointer, PointerSet, PointerUsize};
+    ///
+    /// let ids = (0..4).map(PointerUsize::new).collect::<Option<Vec<_>>>().ok_or("out of bounds")?;
+    ///
+    /// let mut a = PointerSet::<syntree::FlavorDefault>::from_iter([ids[0], ids[1]]);
+    /// let b = PointerSet::from_iter([ids[1], ids[3]]);
+    ///
+    /// a.difference_with(&b);
+    /// assert!(a.iter().eq([ids[0]]));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn difference_with(&mut self, other: &Self) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !*b;
+        }
+
+        self.recount();
+    }
+
+    /// Test if every identifier in this set is also in `other`.
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.iter().enumerate().all(|(n, a)| {
+            let b = other.words.get(n).copied().unwrap_or_default();
+            *a & !b == 0
+        })
+    }
+
+    fn recount(&mut self) {
+        self.len = self.words.iter().map(|w| w.count_ones() as usize).sum();
+    }
+}
+
+/// Split an identifier into the index of its word and its bit in that word.
+#[inline]
+fn split<P>(id: P) -> (usize, u64)
+where
+    P: Pointer,
+{
+    let n = id.get();
+    (n / BITS, 1 << (n % BITS))
+}
+
+impl<F> Default for PointerSet<F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Clone for PointerSet<F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F> PartialEq for PointerSet<F>
+where
+    F: Flavor,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.is_subset(other)
+    }
+}
+
+impl<F> Eq for PointerSet<F> where F: Flavor {}
+
+impl<F> fmt::Debug for PointerSet<F>
+where
+    F: Flavor,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<F> FromIterator<F::Pointer> for PointerSet<F>
+where
+    F: Flavor,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = F::Pointer>,
+    {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<F> Extend<F::Pointer> for PointerSet<F>
+where
+    F: Flavor,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = F::Pointer>,
+    {
+        for id in iter {
+            self.insert(id);
+        }
+    }
+}
+
+impl<'a, F> IntoIterator for &'a PointerSet<F>
+where
+    F: Flavor,
+{
+    type Item = F::Pointer;
+    type IntoIter = PointerSetIter<'a, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the identifiers in a [`PointerSet`].
+///
+/// See [`PointerSet::iter`].
+pub struct PointerSetIter<'a, F>
+where
+    F: Flavor,
+{
+    words: slice::Iter<'a, u64>,
+    current: u64,
+    base: usize,
+    next: usize,
+    remaining: usize,
+    _marker: PhantomData<F::Pointer>,
+}
+
+impl<F> Iterator for PointerSetIter<'_, F>
+where
+    F: Flavor,
+{
+    type Item = F::Pointer;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.current = *self.words.next()?;
+            self.base = self.next;
+            self.next += BITS;
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        self.remaining -= 1;
+
+        F::Pointer::new(self.base + bit)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<F> ExactSizeIterator for PointerSetIter<'_, F> where F: Flavor {}
+
+impl<F> FusedIterator for PointerSetIter<'_, F> where F: Flavor {}
+
+impl<F> Clone for PointerSetIter<'_, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            current: self.current,
+            base: self.base,
+            next: self.next,
+            remaining: self.remaining,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -7,6 +7,7 @@ use std::fmt;
 use std::io::{Error, Write};
 
 use crate::flavor::Flavor;
+use crate::node::Node;
 use crate::span::Span;
 use crate::tree::Tree;
 
@@ -129,6 +130,200 @@ where
     print_with_lookup(o, tree, |span| source.get(span.range()))
 }
 
+/// Pretty-print a tree without a source, using `display` to format the value
+/// of each node instead of its [`Debug`][fmt::Debug] implementation.
+///
+/// This is useful for trees whose values are noisy when debug formatted, such
+/// as when dumping a tree in user-facing tooling.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Clone, Copy)]
+/// enum Syntax {
+///     Number,
+///     Synthetic(usize),
+/// }
+///
+/// impl Syntax {
+///     fn display_name(self) -> &'static str {
+///         match self {
+///             Syntax::Number => "number",
+///             Syntax::Synthetic(..) => "synthetic",
+///         }
+///     }
+/// }
+///
+/// let tree = syntree::tree! {
+///     Syntax::Synthetic(42) => {
+///         (Syntax::Number, 3),
+///     },
+/// };
+///
+/// let mut s = Vec::new();
+/// syntree::print::print_with(&mut s, &tree, |value| value.display_name())?;
+/// # let s = String::from_utf8(s)?;
+/// # assert_eq!(s, "synthetic@0..3\n  number@0..3 +\n");
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// This would write:
+///
+/// ```text
+/// synthetic@0..3
+///   number@0..3 +
+/// ```
+pub fn print_with<O, T, F, D>(
+    mut o: O,
+    tree: &Tree<T, F>,
+    display: impl Fn(T) -> D,
+) -> Result<(), Error>
+where
+    O: Write,
+    T: Copy,
+    F: Flavor<Index: fmt::Display>,
+    D: fmt::Display,
+{
+    for (depth, node) in tree.walk().with_depths() {
+        print_line(&mut o, &node, depth as usize, &|_| None, &display)?;
+    }
+
+    Ok(())
+}
+
+/// Pretty-print a single node and its descendants with the source spans
+/// printed.
+///
+/// Indentation is relative to `node`, which is printed at depth zero. This is
+/// useful for looking at a small part of a large tree. See
+/// [`print_node_with_depth`] to also limit how deep the printing goes.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Debug, Clone, Copy)]
+/// enum Syntax {
+///     NUMBER,
+///     WHITESPACE,
+///     OPERATOR,
+///     PLUS,
+/// }
+///
+/// use Syntax::*;
+///
+/// let source = "128 + 64";
+///
+/// let tree = syntree::tree! {
+///     NUMBER => {
+///         (NUMBER, 3),
+///     },
+///     (WHITESPACE, 1),
+///     OPERATOR => {
+///         (PLUS, 1)
+///     },
+///     (WHITESPACE, 1),
+///     NUMBER => {
+///         (NUMBER, 2),
+///     },
+/// };
+///
+/// let operator = tree.children().nth(2).ok_or("missing operator")?;
+///
+/// let mut s = Vec::new();
+/// syntree::print::print_node(&mut s, &operator, source)?;
+/// # let s = String::from_utf8(s)?;
+/// # assert_eq!(s, "OPERATOR@4..5\n  PLUS@4..5 \"+\"\n");
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// This would write:
+///
+/// ```text
+/// OPERATOR@4..5
+///   PLUS@4..5 "+"
+/// ```
+pub fn print_node<O, T, F>(o: O, node: &Node<'_, T, F>, source: &str) -> Result<(), Error>
+where
+    O: Write,
+    T: Copy + fmt::Debug,
+    F: Flavor<Index: fmt::Display>,
+{
+    print_node_with_lookup(o, node, None, |span| source.get(span.range()))
+}
+
+/// Pretty-print a single node and its descendants with the source spans
+/// printed, up to and including `max_depth` levels below `node`.
+///
+/// Nodes at the depth limit which have children are followed by a `...` line
+/// in place of their children. A `max_depth` of zero only prints `node`
+/// itself.
+///
+/// # Examples
+///
+/// ```
+/// let source = "fn main() { let a = 42; }";
+///
+/// let tree = syntree::tree! {
+///     "fn" => {
+///         ("fn", 2),
+///         ("ws", 1),
+///         ("ident", 4),
+///         ("(", 1),
+///         (")", 1),
+///         ("ws", 1),
+///         "block" => {
+///             ("{", 1),
+///             ("ws", 1),
+///             "let" => {
+///                 ("let", 3),
+///                 ("ws", 1),
+///                 ("ident", 1),
+///                 ("ws", 1),
+///                 ("=", 1),
+///                 ("ws", 1),
+///                 ("number", 2),
+///                 (";", 1),
+///             },
+///             ("ws", 1),
+///             ("}", 1),
+///         }
+///     }
+/// };
+///
+/// let block = tree.first().and_then(|n| n.last()).ok_or("missing block")?;
+///
+/// let mut s = Vec::new();
+/// syntree::print::print_node_with_depth(&mut s, &block, source, 1)?;
+/// # let s = String::from_utf8(s)?;
+/// # assert_eq!(s, "\"block\"@10..25\n  \"{\"@10..11 \"{\"\n  \"ws\"@11..12 \" \"\n  \"let\"@12..23\n    ...\n  \"ws\"@23..24 \" \"\n  \"}\"@24..25 \"}\"\n");
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// This would write:
+///
+/// ```text
+/// "block"@10..25
+///   "{"@10..11 "{"
+///   "ws"@11..12 " "
+///   "let"@12..23
+///     ...
+///   "ws"@23..24 " "
+///   "}"@24..25 "}"
+/// ```
+pub fn print_node_with_depth<O, T, F>(
+    o: O,
+    node: &Node<'_, T, F>,
+    source: &str,
+    max_depth: usize,
+) -> Result<(), Error>
+where
+    O: Write,
+    T: Copy + fmt::Debug,
+    F: Flavor<Index: fmt::Display>,
+{
+    print_node_with_lookup(o, node, Some(max_depth), |span| source.get(span.range()))
+}
+
 fn print_with_lookup<'a, O, T, F>(
     mut o: O,
     tree: &Tree<T, F>,
@@ -140,18 +335,98 @@ where
     F: Flavor<Index: fmt::Display>,
 {
     for (depth, node) in tree.walk().with_depths() {
-        let n = (depth * 2) as usize;
-        let data = node.value();
-        let span = node.span();
-
-        if node.has_children() {
-            writeln!(o, "{:n$}{:?}@{}", "", data, span)?;
-        } else if let Some(source) = source(span) {
-            writeln!(o, "{:n$}{:?}@{} {:?}", "", data, span, source)?;
-        } else {
-            writeln!(o, "{:n$}{:?}@{} +", "", data, span)?;
+        print_line(&mut o, &node, depth as usize, &source, &Debugged)?;
+    }
+
+    Ok(())
+}
+
+fn print_node_with_lookup<'a, O, T, F>(
+    mut o: O,
+    root: &Node<'_, T, F>,
+    max_depth: Option<usize>,
+    source: impl Fn(&Span<F::Index>) -> Option<&'a str>,
+) -> Result<(), Error>
+where
+    O: Write,
+    T: Copy + fmt::Debug,
+    F: Flavor<Index: fmt::Display>,
+{
+    let mut node = *root;
+    let mut depth = 0usize;
+
+    loop {
+        print_line(&mut o, &node, depth, &source, &Debugged)?;
+
+        if let Some(first) = node.first() {
+            if max_depth.is_some_and(|max| depth >= max) {
+                let n = (depth + 1) * 2;
+                writeln!(o, "{:n$}...", "")?;
+            } else {
+                node = first;
+                depth += 1;
+                continue;
+            }
         }
+
+        // Climb up until we find a next sibling, but never beyond the root.
+        loop {
+            if depth == 0 {
+                return Ok(());
+            }
+
+            if let Some(next) = node.next() {
+                node = next;
+                break;
+            }
+
+            let Some(parent) = node.parent() else {
+                return Ok(());
+            };
+
+            node = parent;
+            depth -= 1;
+        }
+    }
+}
+
+fn print_line<'a, O, T, F, D>(
+    o: &mut O,
+    node: &Node<'_, T, F>,
+    depth: usize,
+    source: &impl Fn(&Span<F::Index>) -> Option<&'a str>,
+    display: &impl Fn(T) -> D,
+) -> Result<(), Error>
+where
+    O: Write,
+    T: Copy,
+    F: Flavor<Index: fmt::Display>,
+    D: fmt::Display,
+{
+    let n = depth * 2;
+    let data = display(node.value());
+    let span = node.span();
+
+    if !node.is_token() {
+        writeln!(o, "{:n$}{}@{}", "", data, span)?;
+    } else if let Some(source) = source(span) {
+        writeln!(o, "{:n$}{}@{} {:?}", "", data, span, source)?;
+    } else {
+        writeln!(o, "{:n$}{}@{} +", "", data, span)?;
     }
 
     Ok(())
 }
+
+/// Display a value using its [`Debug`][fmt::Debug] implementation.
+struct Debugged<T>(T);
+
+impl<T> fmt::Display for Debugged<T>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
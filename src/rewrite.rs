@@ -0,0 +1,383 @@
+//! Producing textual edits against the source of a tree.
+//!
+//! Tools such as formatters and code actions rarely want to construct a new
+//! tree. Instead they want to describe how the source a tree was parsed from
+//! should change, and either apply those changes or hand them to an editor.
+//!
+//! A [`Rewrite`] collects edits in terms of the nodes of a tree, such as
+//! replacing the text of a node or inserting text before or after it. Edits
+//! which would conflict with each other are rejected as they are added, and
+//! the collected edits can be applied to the original source with
+//! [`Rewrite::apply`].
+//!
+//! # Examples
+//!
+//! ```
+//! use syntree::rewrite::Rewrite;
+//!
+//! let source = "let x = 1+2;";
+//!
+//! let tree = syntree::tree! {
+//!     "let" => {
+//!         ("kw", 3),
+//!         ("ws", 1),
+//!         ("ident", 1),
+//!         ("ws", 1),
+//!         ("eq", 1),
+//!         ("ws", 1),
+//!         "add" => {
+//!             ("num", 1),
+//!             ("plus", 1),
+//!             ("num", 1),
+//!         },
+//!         ("semi", 1),
+//!     }
+//! };
+//!
+//! let add = tree.find(|n| n.value() == "add").ok_or("missing add")?;
+//! let plus = add.children().find(|n| n.value() == "plus").ok_or("missing plus")?;
+//!
+//! let mut rewrite = Rewrite::new();
+//! rewrite.insert_before(&plus, " ")?;
+//! rewrite.insert_after(&plus, " ")?;
+//! rewrite.insert_before(&add, "(")?;
+//! rewrite.insert_after(&add, ")")?;
+//!
+//! assert_eq!(rewrite.apply(source)?, "let x = (1 + 2);");
+//!
+//! // Replacing the node conflicts with the edits inside of it.
+//! assert!(rewrite.replace(&add, "3").is_err());
+//! # Ok::<_, Box<dyn core::error::Error>>(())
+//! ```
+
+use core::fmt;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::index::Index;
+use crate::node::Node;
+use crate::span::Span;
+
+/// A single textual edit, replacing the text covered by [`Edit::span`] with
+/// [`Edit::text`].
+///
+/// An edit with an empty span is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit<I = u32> {
+    span: Span<I>,
+    text: String,
+}
+
+impl<I> Edit<I>
+where
+    I: Index,
+{
+    /// The span of the source being replaced.
+    #[must_use]
+    pub fn span(&self) -> &Span<I> {
+        &self.span
+    }
+
+    /// The text to replace the span with.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Test if this edit would conflict with `other`.
+    ///
+    /// Two edits conflict if they replace overlapping ranges, or if one of
+    /// them inserts text strictly inside of a range replaced by the other.
+    /// Insertions at the same position don't conflict.
+    fn conflicts(&self, other: &Self) -> bool {
+        let (a, b) = (&self.span, &other.span);
+
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => false,
+            (true, false) => b.start < a.start && a.start < b.end,
+            (false, true) => a.start < b.start && b.start < a.end,
+            (false, false) => a.start < b.end && b.start < a.end,
+        }
+    }
+}
+
+/// A collection of non-conflicting edits to the source of a tree.
+///
+/// Edits are kept ordered by their position in the source. Insertions at the
+/// same position are ordered by when they were added, and come before any
+/// replacement which starts at that position.
+///
+/// See the [module level documentation][self] for more.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite<I = u32> {
+    edits: Vec<Edit<I>>,
+}
+
+impl<I> Rewrite<I>
+where
+    I: Index,
+{
+    /// Construct a new empty collection of edits.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { edits: Vec::new() }
+    }
+
+    /// Replace the text covered by `node` with `text`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    pub fn replace<T, F>(
+        &mut self,
+        node: &Node<'_, T, F>,
+        text: impl Into<String>,
+    ) -> Result<(), RewriteError<I>>
+    where
+        T: Copy,
+        F: Flavor<Index = I>,
+    {
+        self.replace_span(*node.span(), text)
+    }
+
+    /// Delete the text covered by `node`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    pub fn delete<T, F>(&mut self, node: &Node<'_, T, F>) -> Result<(), RewriteError<I>>
+    where
+        T: Copy,
+        F: Flavor<Index = I>,
+    {
+        self.replace_span(*node.span(), String::new())
+    }
+
+    /// Insert `text` right before `node`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    pub fn insert_before<T, F>(
+        &mut self,
+        node: &Node<'_, T, F>,
+        text: impl Into<String>,
+    ) -> Result<(), RewriteError<I>>
+    where
+        T: Copy,
+        F: Flavor<Index = I>,
+    {
+        self.insert_at(node.span().start, text)
+    }
+
+    /// Insert `text` right after `node`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    pub fn insert_after<T, F>(
+        &mut self,
+        node: &Node<'_, T, F>,
+        text: impl Into<String>,
+    ) -> Result<(), RewriteError<I>>
+    where
+        T: Copy,
+        F: Flavor<Index = I>,
+    {
+        self.insert_at(node.span().end, text)
+    }
+
+    /// Insert `text` at the given `index` in the source.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    pub fn insert_at(&mut self, index: I, text: impl Into<String>) -> Result<(), RewriteError<I>> {
+        self.replace_span(Span::point(index), text)
+    }
+
+    /// Replace the text covered by `span` with `text`.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::Conflict`] if the edit conflicts with an
+    /// edit which has already been added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    /// use syntree::rewrite::{Rewrite, RewriteError};
+    ///
+    /// let mut rewrite = Rewrite::new();
+    /// rewrite.replace_span(Span::new(0u32, 4), "a")?;
+    /// rewrite.insert_at(4, "b")?;
+    ///
+    /// assert_eq!(
+    ///     rewrite.replace_span(Span::new(2, 6), "c"),
+    ///     Err(RewriteError::Conflict {
+    ///         span: Span::new(2, 6),
+    ///         existing: Span::new(0, 4),
+    ///     })
+    /// );
+    ///
+    /// assert_eq!(rewrite.len(), 2);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn replace_span(
+        &mut self,
+        span: Span<I>,
+        text: impl Into<String>,
+    ) -> Result<(), RewriteError<I>> {
+        let edit = Edit {
+            span,
+            text: text.into(),
+        };
+
+        let key = |e: &Edit<I>| (e.span.start, e.span.end);
+        let n = self.edits.partition_point(|e| key(e) <= key(&edit));
+
+        let neighbors = n
+            .checked_sub(1)
+            .and_then(|n| self.edits.get(n))
+            .into_iter()
+            .chain(self.edits.get(n));
+
+        for existing in neighbors {
+            if existing.conflicts(&edit) {
+                return Err(RewriteError::Conflict {
+                    span,
+                    existing: existing.span,
+                });
+            }
+        }
+
+        self.edits.insert(n, edit);
+        Ok(())
+    }
+
+    /// Get the edits ordered by their position in the source.
+    #[must_use]
+    pub fn edits(&self) -> &[Edit<I>] {
+        &self.edits
+    }
+
+    /// Get the number of edits.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Test if there are no edits.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Apply the edits to `source`, producing the new source.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`RewriteError::OutOfBounds`] if the span of an edit
+    /// extends past the end of `source`, or with
+    /// [`RewriteError::NotCharBoundary`] if it doesn't start or end on a
+    /// character boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    /// use syntree::rewrite::{Rewrite, RewriteError};
+    ///
+    /// let mut rewrite = Rewrite::new();
+    /// rewrite.replace_span(Span::new(0u32, 5), "goodbye")?;
+    ///
+    /// assert_eq!(rewrite.apply("hello world")?, "goodbye world");
+    /// assert_eq!(
+    ///     rewrite.apply("hi"),
+    ///     Err(RewriteError::OutOfBounds(Span::new(0, 5)))
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn apply(&self, source: &str) -> Result<String, RewriteError<I>> {
+        let mut output = String::with_capacity(source.len());
+        let mut last = 0;
+
+        for edit in &self.edits {
+            let range = edit.span.range();
+
+            if range.end > source.len() {
+                return Err(RewriteError::OutOfBounds(edit.span));
+            }
+
+            let (Some(before), Some(_)) =
+                (source.get(last..range.start), source.get(range.clone()))
+            else {
+                return Err(RewriteError::NotCharBoundary(edit.span));
+            };
+
+            output.push_str(before);
+            output.push_str(&edit.text);
+            last = range.end;
+        }
+
+        output.push_str(source.get(last..).unwrap_or_default());
+        Ok(output)
+    }
+}
+
+impl<I> Default for Rewrite<I>
+where
+    I: Index,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error raised by [`Rewrite`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RewriteError<I = u32> {
+    /// The edit of `span` conflicts with an existing edit of `existing`.
+    Conflict {
+        /// The span of the edit being added.
+        span: Span<I>,
+        /// The span of the existing edit it conflicts with.
+        existing: Span<I>,
+    },
+    /// The span of an edit extends past the end of the source.
+    OutOfBounds(Span<I>),
+    /// The span of an edit doesn't start or end on a character boundary in
+    /// the source.
+    NotCharBoundary(Span<I>),
+}
+
+impl<I> core::error::Error for RewriteError<I> where I: fmt::Debug + fmt::Display {}
+
+impl<I> fmt::Display for RewriteError<I>
+where
+    I: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewriteError::Conflict { span, existing } => {
+                write!(f, "edit of {span} conflicts with edit of {existing}")
+            }
+            RewriteError::OutOfBounds(span) => {
+                write!(f, "edit of {span} is outside of the source")
+            }
+            RewriteError::NotCharBoundary(span) => {
+                write!(f, "edit of {span} is not on a character boundary")
+            }
+        }
+    }
+}
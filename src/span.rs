@@ -157,6 +157,21 @@ where
     /// assert_eq!(Span::new(0u32, 0u32).len(), 0);
     /// assert_eq!(Span::new(0u32, 10u32).len(), 10);
     /// ```
+    ///
+    /// The returned [Length] supports arithmetic, so lengths can be combined
+    /// generically regardless of the index being used:
+    ///
+    /// ```
+    /// use syntree::{Length, Span};
+    ///
+    /// let a = Span::new(0u32, 4u32);
+    /// let b = Span::new(6u32, 10u32);
+    ///
+    /// assert_eq!(a.len().checked_add(b.len()), Some(8));
+    /// assert_eq!(a.len().saturating_sub(b.len() + 1), 0);
+    /// ```
+    ///
+    /// [Length]: crate::Length
     #[must_use]
     #[inline]
     pub fn len(&self) -> I::Length {
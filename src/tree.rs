@@ -1,11 +1,54 @@
+mod euler_tour;
+mod find_all;
+mod flat;
+mod index_by;
+mod into_iter;
+mod link_info;
+mod node_id;
+mod node_map;
+mod path_to;
+mod query_ctx;
+mod raw;
+mod retain;
+mod sorted_by_span;
+mod token_offsets;
+mod trivia;
+mod validate;
+
+use core::cell::Cell;
 use core::fmt;
 use core::ops::Range;
 
-use crate::links::Links;
-use crate::node::{Children, Event, Node, Walk, WalkEvents};
-#[cfg(feature = "std")]
+use crate::error::BuildResult;
+use crate::kind::Kind;
+use crate::links::{Links, LinksOf};
+use crate::node::{Children, Event, Node, Walk, WalkEvents, WalkPostorder};
 use crate::Error;
-use crate::{Flavor, Index, Pointer, Span, Storage, Width};
+use crate::{Flavor, Index, Indexes, Pointer, Span, Storage, Width};
+
+use self::node_id::Fingerprint;
+
+#[cfg(feature = "alloc")]
+pub use self::euler_tour::EulerTour;
+pub use self::find_all::FindAll;
+pub use self::flat::Flat;
+#[cfg(feature = "alloc")]
+pub use self::index_by::{IndexBy, IndexByIter};
+pub use self::into_iter::IntoIter;
+pub use self::link_info::LinkInfo;
+pub use self::node_id::NodeId;
+#[cfg(feature = "alloc")]
+pub use self::node_map::{NodeMap, NodeMapIter};
+pub use self::path_to::PathTo;
+#[cfg(feature = "alloc")]
+pub use self::query_ctx::QueryCtx;
+pub use self::raw::{RawNode, TreeRaw};
+#[cfg(feature = "alloc")]
+pub use self::sorted_by_span::SortedBySpan;
+pub use self::token_offsets::TokenOffsets;
+#[cfg(feature = "alloc")]
+pub use self::trivia::Trivia;
+pub use self::validate::{SourceMismatch, ValidationError};
 
 /// A syntax tree.
 ///
@@ -24,13 +67,27 @@ use crate::{Flavor, Index, Pointer, Span, Storage, Width};
 ///
 /// To use the default values, use the [Builder::new][crate::Builder::new]
 /// constructor.
+///
+/// # Complexity
+///
+/// Every element of the tree is stored in a single contiguous allocation and
+/// is identified by its position in it. This gives the following guarantees,
+/// where `n` is the number of elements in the tree:
+/// * Looking up an element by its identifier, such as with [`Tree::get`], and
+///   following any of its links takes constant time. The id-based accessors
+///   such as [`Tree::parent_id`] and [`Tree::value_of`] do so without
+///   constructing a [`Node`].
+/// * Finding the token at an offset with the default [Indexes] takes
+///   `O(log n)` time, and finding the node which covers a span additionally
+///   walks up through its ancestors.
+/// * Walks and other iterators take constant time per element visited.
 pub struct Tree<T, F>
 where
     T: Copy,
     F: Flavor,
 {
     /// Links in the tree.
-    tree: F::Storage<Links<T, F::Index, F::Pointer>>,
+    tree: F::Storage<LinksOf<T, F>>,
     /// The span of the whole tree.
     span: Span<F::Index>,
     /// Token indexes for range searches. This contains the value of the token
@@ -41,6 +98,17 @@ where
     first: Option<F::Pointer>,
     /// The last node in the tree.
     last: Option<F::Pointer>,
+    /// Whether empty tokens are included in the indexes.
+    index_empty: bool,
+    /// Whether the tree was built with explicit spans, in which case tokens
+    /// might not be in order of their spans.
+    explicit_spans: bool,
+    /// The number of tokens in the tree.
+    tokens: usize,
+    /// A user supplied identifier of the source the tree was built from.
+    source_id: Option<u64>,
+    /// Identifies the tree in debug builds.
+    fingerprint: Fingerprint,
 }
 
 impl<T, F> Tree<T, F>
@@ -51,26 +119,85 @@ where
     /// Construct a new empty tree.
     pub(crate) const fn new_with() -> Self {
         Self {
-            tree: F::Storage::EMPTY,
+            tree: <F::Storage<_> as Storage<_>>::EMPTY,
             span: Span::point(F::Index::EMPTY),
-            indexes: F::Indexes::EMPTY,
+            indexes: <F::Indexes as Indexes<F>>::EMPTY,
             first: None,
             last: None,
+            index_empty: false,
+            explicit_spans: false,
+            tokens: 0,
+            source_id: None,
+            fingerprint: Fingerprint::new(),
         }
     }
 
-    /// Construct a new tree with the given capacity.
-    #[cfg(feature = "std")]
-    pub(crate) fn with_capacity(capacity: usize) -> Result<Self, Error<F::Error>> {
+    /// Construct a new tree with capacity for `nodes` nodes and `tokens`
+    /// entries in the token index.
+    pub(crate) fn with_capacity(nodes: usize, tokens: usize) -> BuildResult<Self, F> {
         Ok(Self {
-            tree: F::Storage::with_capacity(capacity)?,
+            tree: <F::Storage<_> as Storage<_>>::with_capacity(nodes)?,
             span: Span::point(F::Index::EMPTY),
-            indexes: F::Indexes::EMPTY,
+            indexes: <F::Indexes as Indexes<F>>::with_capacity(tokens)?,
             first: None,
             last: None,
+            index_empty: false,
+            explicit_spans: false,
+            tokens: 0,
+            source_id: None,
+            fingerprint: Fingerprint::new(),
         })
     }
 
+    /// Construct an empty tree.
+    ///
+    /// This is the same tree as the one produced by building an empty
+    /// [Builder][crate::Builder], but doesn't require going through one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{FlavorDefault, Tree};
+    ///
+    /// let tree = Tree::<&str, FlavorDefault>::empty();
+    /// assert!(tree.is_empty());
+    /// assert_eq!(tree, syntree::Builder::new().build()?);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self::new_with()
+    }
+
+    /// Construct a [Builder][crate::Builder] for a tree with the same flavor
+    /// as this one.
+    ///
+    /// This allows generic code to construct trees without naming the
+    /// builder type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Error, Flavor, Tree};
+    ///
+    /// fn single<F>(value: &'static str) -> Result<Tree<&'static str, F>, Error<F::Error, F::Pointer>>
+    /// where
+    ///     F: Flavor,
+    /// {
+    ///     let mut tree = Tree::builder();
+    ///     tree.token_empty(value)?;
+    ///     tree.build()
+    /// }
+    ///
+    /// let tree = single::<syntree::FlavorDefault>("lit")?;
+    /// assert_eq!(tree.first().map(|n| n.value()), Some("lit"));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub const fn builder() -> crate::Builder<T, F> {
+        crate::Builder::new_with()
+    }
+
     /// Get the span of the current node. The span of a node is the complete
     /// span of all its children.
     ///
@@ -100,101 +227,1320 @@ where
         &self.span
     }
 
-    /// Get mutable span from the tree.
-    pub(crate) fn span_mut(&mut self) -> &mut Span<F::Index> {
-        &mut self.span
+    /// Get the identifier of the source the tree was built from, as set with
+    /// [`Builder::set_source_id`][crate::Builder::set_source_id].
+    ///
+    /// This is typically a checksum of the source text, which allows caching
+    /// layers to check that a stored tree corresponds to the current source.
+    /// Trees produced by [`ChangeSet::modify`][crate::edit::ChangeSet::modify]
+    /// don't have a source identifier, since they no longer correspond to
+    /// the source of the original tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    /// tree.set_source_id(0x1234);
+    /// tree.token("lit", 3)?;
+    /// let tree = tree.build()?;
+    ///
+    /// assert_eq!(tree.source_id(), Some(0x1234));
+    ///
+    /// let tree = syntree::tree! {
+    ///     ("lit", 3)
+    /// };
+    ///
+    /// assert_eq!(tree.source_id(), None);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn source_id(&self) -> Option<u64> {
+        self.source_id
     }
 
-    /// The total number of elements in the tree.
+    /// Get the offset at which the tree starts.
+    ///
+    /// This is the start of [`Tree::span`], which is zero for trees produced
+    /// by a [Builder][crate::Builder] unless they've been moved with
+    /// [`Tree::rebase`].
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
-    /// let tree = tree.build()?;
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 5)
+    ///     }
+    /// };
     ///
-    /// assert_eq!(tree.len(), 0);
+    /// assert_eq!(tree.base(), 0);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn base(&self) -> F::Index {
+        self.span.start
+    }
+
+    /// Move the tree so that it starts at the offset `base`.
+    ///
+    /// Every span in the tree and every entry in its token index is shifted by
+    /// the distance between the current [`Tree::base`] and `base`, without
+    /// changing the structure of the tree. This is useful when a parsed
+    /// fragment moves within an outer document.
+    ///
+    /// This takes time proportional to the number of nodes in the tree. If an
+    /// error is raised, the tree is left unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] if a shifted span no longer fits in the
+    /// index of the tree, or with [`Error::Flavor`] if the token index could not
+    /// be rebuilt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
     ///
     /// let mut tree = syntree::tree! {
     ///     "root" => {
-    ///         "child" => {
-    ///             ("token", 2)
+    ///         "number" => {
+    ///             ("lit", 5)
     ///         },
-    ///         ("whitespace", 1),
-    ///         "child2" => {}
+    ///         ("ws", 1),
     ///     }
     /// };
     ///
-    /// assert_eq!(tree.len(), 5);
+    /// tree.rebase(10)?;
+    ///
+    /// assert_eq!(tree.base(), 10);
+    /// assert_eq!(tree.span(), Span::new(10, 16));
+    ///
+    /// let number = tree.node_with_range(10..15).ok_or("missing number")?;
+    /// assert_eq!(number.value(), "number");
+    /// assert_eq!(number.span(), Span::new(10, 15));
+    ///
+    /// tree.rebase(2)?;
+    /// assert_eq!(tree.span(), Span::new(2, 8));
+    /// assert!(tree.validate().is_ok());
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn len(&self) -> usize {
-        self.tree.len()
+    pub fn rebase(&mut self, base: F::Index) -> BuildResult<(), F> {
+        let old = self.span.start;
+
+        if old == base {
+            return Ok(());
+        }
+
+        // Every offset in the tree is expected to be at or after the current
+        // base, so shifting it is a matter of adding its distance from the
+        // base to the new base.
+        let shift = |index: F::Index| base.checked_add_len(old.len_to(index));
+
+        // Check that all spans can be shifted before modifying anything.
+        for links in self.tree.iter() {
+            shift(links.span.end).ok_or(Error::Overflow)?;
+        }
+
+        let end = shift(self.span.end).ok_or(Error::Overflow)?;
+
+        let mut indexes = <F::Indexes as Indexes<F>>::EMPTY;
+
+        for n in 0..self.indexes.len() {
+            let Some((index, id)) = self.indexes.get(n) else {
+                break;
+            };
+
+            indexes.push(shift(index).ok_or(Error::Overflow)?, id)?;
+        }
+
+        for links in self.tree.iter_mut() {
+            if let (Some(start), Some(end)) = (shift(links.span.start), shift(links.span.end)) {
+                links.span = Span::new(start, end);
+            }
+        }
+
+        self.span = Span::new(base, end);
+        self.indexes = indexes;
+        Ok(())
     }
 
-    /// Check if the current tree is empty. In that it doesn't have any
-    /// childrens at the root of the tree.
+    /// Construct a tree with the same structure and spans as this one, where
+    /// every value has been converted using `map`.
+    ///
+    /// This copies the tree in a single pass over its storage, which is much
+    /// cheaper than replaying it through a [Builder][crate::Builder]. Since
+    /// every node keeps its identifier, identifiers and [`NodeId`]s from this
+    /// tree can be used with the new tree as well.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Flavor`] if storage for the new tree could not be
+    /// allocated.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
-    /// let tree = tree.build()?;
-    /// assert!(tree.is_empty());
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    /// enum Syntax {
+    ///     Expr,
+    ///     Atom,
+    ///     Trivia,
+    /// }
+    ///
+    /// let tree = syntree::tree! {
+    ///     "binary" => {
+    ///         ("number", 1),
+    ///         ("ws", 1),
+    ///         ("plus", 1),
+    ///     }
+    /// };
+    ///
+    /// let lowered = tree.map(|value| match value {
+    ///     "binary" => Syntax::Expr,
+    ///     "ws" => Syntax::Trivia,
+    ///     _ => Syntax::Atom,
+    /// })?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     Syntax::Expr => {
+    ///         (Syntax::Atom, 1),
+    ///         (Syntax::Trivia, 1),
+    ///         (Syntax::Atom, 1),
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(lowered, expected);
+    ///
+    /// let plus = tree.first().and_then(|n| n.last()).ok_or("missing plus")?;
+    /// assert_eq!(lowered.get(plus.id()).map(|n| n.value()), Some(Syntax::Atom));
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.tree.is_empty()
+    pub fn map<U, M>(&self, mut map: M) -> BuildResult<Tree<U, F>, F>
+    where
+        U: Copy,
+        M: FnMut(T) -> U,
+    {
+        let mut tree = <F::Storage<_> as Storage<_>>::with_capacity(self.tree.len())?;
+
+        for links in self.tree.iter() {
+            Storage::push(
+                &mut tree,
+                Links {
+                    data: Cell::new(map(links.data.get())),
+                    kind: links.kind,
+                    span: links.span,
+                    parent: links.parent,
+                    prev: links.prev,
+                    next: links.next,
+                    first: links.first,
+                    last: links.last,
+                    flags: links.flags,
+                },
+            )?;
+        }
+
+        let mut indexes = <F::Indexes as Indexes<F>>::with_capacity(self.indexes.len())?;
+
+        for n in 0..self.indexes.len() {
+            let Some((index, id)) = self.indexes.get(n) else {
+                break;
+            };
+
+            indexes.push(index, id)?;
+        }
+
+        Ok(Tree {
+            tree,
+            span: self.span,
+            indexes,
+            first: self.first,
+            last: self.last,
+            index_empty: self.index_empty,
+            explicit_spans: self.explicit_spans,
+            tokens: self.tokens,
+            source_id: self.source_id,
+            fingerprint: self.fingerprint.clone(),
+        })
     }
 
-    /// Get the capacity of the tree.
+    /// Construct a condensed tree which only contains the elements whose
+    /// value matches `predicate`.
+    ///
+    /// The children of an element which is removed are moved up to take its
+    /// place among its siblings, so they end up under the nearest retained
+    /// ancestor. Every retained element keeps its span, which makes this
+    /// suitable for projecting a concrete syntax tree into a compact abstract
+    /// one by dropping trivia and punctuation.
+    ///
+    /// Identifiers are not preserved, since the removed elements no longer
+    /// occupy any space in the new tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node
+    /// identifiers, or with [`Error::Flavor`] if storage for the new tree
+    /// could not be allocated.
     ///
     /// # Examples
     ///
     /// ```
-    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
-    /// let tree = tree.build()?;
+    /// let tree = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         "args" => {
+    ///             ("open", 1),
+    ///             "group" => {
+    ///                 ("number", 1),
+    ///                 ("comma", 1),
+    ///                 ("ws", 1),
+    ///                 ("number", 2),
+    ///             },
+    ///             ("close", 1),
+    ///         },
+    ///     }
+    /// };
     ///
-    /// assert_eq!(tree.capacity(), 0);
+    /// let ast = tree.retain(|value| !matches!(value, "open" | "close" | "comma" | "ws" | "group"))?;
     ///
-    /// let mut tree = syntree::tree! {
-    ///     "root" => {
-    ///         "child" => {
-    ///             ("token", 2)
+    /// let call = ast.first().ok_or("missing call")?;
+    /// let args = call.last().ok_or("missing args")?;
+    ///
+    /// assert_eq!(
+    ///     args.children().map(|n| (n.value(), n.range())).collect::<Vec<_>>(),
+    ///     [("number", 4..5), ("number", 7..9)]
+    /// );
+    ///
+    /// assert_eq!(args.range(), 3..10);
+    /// ast.validate()?;
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn retain<P>(&self, predicate: P) -> BuildResult<Self, F>
+    where
+        P: FnMut(T) -> bool,
+    {
+        retain::retain(self, predicate)
+    }
+
+    /// Copy the subtree rooted at the node with the given `id` into `builder`
+    /// at its current position, returning the identifier of the copied node.
+    ///
+    /// Spans are rebased onto the cursor of the builder, so every token keeps
+    /// its length and the cursor is advanced past the copied tokens. Flags of
+    /// the copied nodes are preserved. This is useful for stitching fragments
+    /// of existing trees into a new one, such as when expanding templates.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::MissingNode`] if there is no node with the given
+    /// `id`, or with any error raised by the builder while inserting nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         "args" => {
+    ///             ("open", 1),
+    ///             ("close", 1),
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let call = template.first().ok_or("missing call")?;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("block")?;
+    ///
+    /// for _ in 0..2 {
+    ///     template.clone_subtree_into(call.id(), &mut tree)?;
+    ///     tree.token("semi", 1)?;
+    /// }
+    ///
+    /// let args = call.last().ok_or("missing args")?;
+    /// template.clone_subtree_into(args.id(), &mut tree)?;
+    ///
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "block" => {
+    ///         "call" => {
+    ///             ("ident", 3),
+    ///             "args" => {
+    ///                 ("open", 1),
+    ///                 ("close", 1),
+    ///             }
     ///         },
-    ///         ("whitespace", 1),
-    ///         "child2" => {}
+    ///         ("semi", 1),
+    ///         "call" => {
+    ///             ("ident", 3),
+    ///             "args" => {
+    ///                 ("open", 1),
+    ///                 ("close", 1),
+    ///             }
+    ///         },
+    ///         ("semi", 1),
+    ///         "args" => {
+    ///             ("open", 1),
+    ///             ("close", 1),
+    ///         }
     ///     }
     /// };
     ///
-    /// assert!(tree.capacity() >= 5);
+    /// assert_eq!(tree, expected);
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
-    pub fn capacity(&self) -> usize {
-        self.tree.capacity()
+    pub fn clone_subtree_into(
+        &self,
+        id: F::Pointer,
+        builder: &mut crate::Builder<T, F>,
+    ) -> BuildResult<F::Pointer, F> {
+        let node = self.get(id).ok_or(Error::MissingNode(id))?;
+
+        let mut root = None;
+        // The number of nodes which have been opened but not closed.
+        let mut open = 0usize;
+
+        for (event, node) in node.walk_events() {
+            if let Event::Up = event {
+                builder.close()?;
+                open -= 1;
+            } else {
+                let id = match node.kind() {
+                    Kind::Token => builder.token(node.value(), node.span().len())?,
+                    Kind::Node => builder.open(node.value())?,
+                };
+
+                builder.set_flag(id, node.flags())?;
+                root.get_or_insert(id);
+
+                if node.kind() == Kind::Node {
+                    if node.has_children() {
+                        open += 1;
+                    } else {
+                        builder.close()?;
+                    }
+                }
+            }
+
+            if open == 0 {
+                break;
+            }
+        }
+
+        root.ok_or(Error::MissingNode(id))
     }
 
-    /// Get all root nodes in the tree.
+    /// Replace the value of the node with the given `id`, returning the old
+    /// value or `None` if the node doesn't exist.
     ///
-    /// See [Children] for documentation.
-    pub fn children(&self) -> Children<'_, T, F> {
-        Children::new(&self.tree, self.first, self.last)
+    /// The span and structure of the tree are left untouched, so this is a
+    /// constant time operation. It's the same as calling [`Node::replace`] on
+    /// the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let ident = tree.first().and_then(|n| n.first()).ok_or("missing ident")?;
+    ///
+    /// assert_eq!(tree.replace_value(ident.id(), "renamed"), Some("ident"));
+    ///
+    /// let expected = syntree::tree! {
+    ///     "call" => {
+    ///         ("renamed", 3),
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn replace_value(&self, id: F::Pointer, value: T) -> Option<T> {
+        Some(self.get(id)?.replace(value))
     }
 
-    /// Walk the tree forwards in a depth-first fashion visiting every node
-    /// once.
+    /// Change the length of the token with the given `id`, keeping its value.
     ///
-    /// See [`Walk`] for documentation.
-    pub fn walk(&self) -> Walk<'_, T, F> {
-        Walk::new(&self.tree, self.first, Event::Next)
-    }
+    /// Every node which follows the token is shifted, and every node which
+    /// contains it is resized to account for the new length. The structure of
+    /// the tree is left untouched, so unlike a
+    /// [`ChangeSet`][crate::edit::ChangeSet] no new tree has to be constructed.
+    ///
+    /// This takes time proportional to the number of nodes in the tree. If an
+    /// error is raised, the tree is left unmodified.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::MissingNode`] if the node doesn't exist, with
+    /// [`Error::NotToken`] if it isn't a token, with [`Error::Overflow`] if a
+    /// shifted span no longer fits in the index of the tree, or with
+    /// [`Error::Flavor`] if the token index could not be rebuilt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Span;
+    ///
+    /// let mut tree = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 3),
+    ///         "args" => {
+    ///             ("number", 2)
+    ///         }
+    ///     },
+    ///     ("ws", 1)
+    /// };
+    ///
+    /// let ident = tree.first().and_then(|n| n.first()).ok_or("missing ident")?;
+    /// tree.set_token_len(ident.id(), 7)?;
+    ///
+    /// let expected = syntree::tree! {
+    ///     "call" => {
+    ///         ("ident", 7),
+    ///         "args" => {
+    ///             ("number", 2)
+    ///         }
+    ///     },
+    ///     ("ws", 1)
+    /// };
+    ///
+    /// assert_eq!(tree, expected);
+    /// assert_eq!(tree.span(), Span::new(0, 10));
+    ///
+    /// let args = tree.node_with_range(7..9).ok_or("missing args")?;
+    /// assert_eq!(args.value(), "args");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn set_token_len(&mut self, id: F::Pointer, len: F::Length) -> BuildResult<(), F> {
+        let links = self.tree.get(id.get()).ok_or(Error::MissingNode(id))?;
 
-    /// Walk the tree forwards in a depth-first fashion emitting events
-    /// indicating how the tree is being traversed.
+        if links.kind != Kind::Token {
+            return Err(Error::NotToken(id));
+        }
+
+        let old = links.span.end;
+        let new = links
+            .span
+            .start
+            .checked_add_len(len)
+            .ok_or(Error::Overflow)?;
+
+        if old == new {
+            return Ok(());
+        }
+
+        // Every offset which is affected is at or after the end of the token,
+        // so shifting it is a matter of adding its distance from the old end
+        // to the new end.
+        let shift = |index: F::Index| new.checked_add_len(old.len_to(index));
+
+        // The end of the tree is the largest offset, so if it can be shifted
+        // all other offsets can too.
+        let end = shift(self.span.end).ok_or(Error::Overflow)?;
+
+        let mut parent = links.parent;
+
+        if let Some(links) = self.tree.get_mut(id.get()) {
+            links.span.end = new;
+        }
+
+        while let Some(links) = parent.and_then(|id| self.tree.get_mut(id.get())) {
+            links.span.end = shift(links.span.end).unwrap_or(end);
+            parent = links.parent;
+        }
+
+        // Shift every node which follows the token in tree order.
+        let mut current = self.following(id);
+
+        while let Some(id) = current {
+            let Some(links) = self.tree.get_mut(id.get()) else {
+                break;
+            };
+
+            if let (Some(start), Some(end)) = (shift(links.span.start), shift(links.span.end)) {
+                links.span = Span::new(start, end);
+            }
+
+            current = match links.first {
+                Some(first) => Some(first),
+                None => self.following(id),
+            };
+        }
+
+        self.span.end = end;
+        self.reindex()
+    }
+
+    /// Get the node which follows the subtree of `id` in tree order.
+    fn following(&self, mut id: F::Pointer) -> Option<F::Pointer> {
+        loop {
+            let links = self.tree.get(id.get())?;
+
+            if let Some(next) = links.next {
+                return Some(next);
+            }
+
+            id = links.parent?;
+        }
+    }
+
+    /// Rebuild the token index from the tokens in the tree.
+    pub(crate) fn reindex(&mut self) -> BuildResult<(), F> {
+        let mut indexes = <F::Indexes as Indexes<F>>::EMPTY;
+
+        // Entries in the index must be in token order, which is not
+        // necessarily the order in which the nodes are stored. Tokens with
+        // explicit spans might also be out of order, so the index is sorted
+        // afterwards.
+        for node in Walk::<T, F>::new(&self.tree, self.first, Event::Next) {
+            if node.is_token() && (!node.span().is_empty() || self.index_empty) {
+                indexes.push(node.span().end, node.id())?;
+            }
+        }
+
+        indexes.sort();
+        self.indexes = indexes;
+        Ok(())
+    }
+
+    /// Get mutable span from the tree.
+    pub(crate) fn span_mut(&mut self) -> &mut Span<F::Index> {
+        &mut self.span
+    }
+
+    /// The total number of elements in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
+    /// let tree = tree.build()?;
+    ///
+    /// assert_eq!(tree.len(), 0);
+    ///
+    /// let mut tree = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("token", 2)
+    ///         },
+    ///         ("whitespace", 1),
+    ///         "child2" => {}
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree.len(), 5);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Get the number of tokens in the tree.
+    ///
+    /// This is tracked while the tree is being built, so it doesn't require
+    /// walking the tree. Together with [`Tree::node_count`] it adds up to
+    /// [`Tree::len`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("token", 2)
+    ///         },
+    ///         ("whitespace", 1),
+    ///         "child2" => {}
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree.token_count(), 2);
+    /// assert_eq!(tree.node_count(), 3);
+    /// assert_eq!(tree.token_count() + tree.node_count(), tree.len());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn token_count(&self) -> usize {
+        self.tokens
+    }
+
+    /// Get the number of nodes in the tree which are not tokens.
+    ///
+    /// See [`Tree::token_count`].
+    pub fn node_count(&self) -> usize {
+        self.tree.len().saturating_sub(self.tokens)
+    }
+
+    /// Get the `n`th token in the tree, counting from zero in the order in
+    /// which tokens appear.
+    ///
+    /// This allows for positional lookups in trees which don't have spans,
+    /// such as those using an [`Empty`][crate::Empty] index where
+    /// [`Tree::node_with_range`] can't be used.
+    ///
+    /// If every token in the tree is in its token index, the index is used as
+    /// an ordinal index and the lookup takes constant time. This is the case
+    /// if empty tokens were indexed with
+    /// [`Builder::set_index_empty_tokens`][crate::Builder::set_index_empty_tokens]
+    /// and the flavor stores its indexes, unless the tree was built with
+    /// [`Builder::set_explicit_spans`][crate::Builder::set_explicit_spans]
+    /// since its index is ordered by span. Otherwise the tree is walked to
+    /// find the token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Empty, Tree};
+    ///
+    /// syntree::flavor! {
+    ///     struct FlavorOrdinal {
+    ///         type Index = Empty;
+    ///     }
+    /// }
+    ///
+    /// let mut tree = Builder::<_, FlavorOrdinal>::new_with();
+    /// tree.set_index_empty_tokens(true);
+    ///
+    /// tree.open("root")?;
+    /// tree.token("a", Empty)?;
+    /// tree.open("child")?;
+    /// tree.token("b", Empty)?;
+    /// tree.close()?;
+    /// tree.token("c", Empty)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// assert_eq!(tree.nth_token(0).map(|n| n.value()), Some("a"));
+    /// assert_eq!(tree.nth_token(1).map(|n| n.value()), Some("b"));
+    /// assert_eq!(tree.nth_token(2).map(|n| n.value()), Some("c"));
+    /// assert_eq!(tree.nth_token(3).map(|n| n.value()), None);
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("a", 1),
+    ///         "b",
+    ///         ("c", 2),
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree.nth_token(1).map(|n| n.value()), Some("b"));
+    /// assert_eq!(tree.nth_token(2).map(|n| n.span().range()), Some(1..3));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn nth_token(&self, n: usize) -> Option<Node<'_, T, F>> {
+        // Tokens with explicit spans might not be in order in the index.
+        if self.index_empty && !self.explicit_spans && self.indexes.len() == self.tokens {
+            let (_, id) = self.indexes.get(n)?;
+            return self.get(id);
+        }
+
+        self.walk().filter(|node| node.is_token()).nth(n)
+    }
+
+    /// Check if the current tree is empty. In that it doesn't have any
+    /// childrens at the root of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
+    /// let tree = tree.build()?;
+    /// assert!(tree.is_empty());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Get the capacity of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut tree: syntree::Builder<()> = syntree::Builder::new();
+    /// let tree = tree.build()?;
+    ///
+    /// assert_eq!(tree.capacity(), 0);
+    ///
+    /// let mut tree = syntree::tree! {
+    ///     "root" => {
+    ///         "child" => {
+    ///             ("token", 2)
+    ///         },
+    ///         ("whitespace", 1),
+    ///         "child2" => {}
+    ///     }
+    /// };
+    ///
+    /// assert!(tree.capacity() >= 5);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.tree.capacity()
+    }
+
+    /// Get all root nodes in the tree.
+    ///
+    /// See [Children] for documentation.
+    pub fn children(&self) -> Children<'_, T, F> {
+        Children::new(&self.tree, self.first, self.last)
+    }
+
+    /// Walk the tree forwards in a depth-first fashion visiting every node
+    /// once.
+    ///
+    /// # Ordering
+    ///
+    /// Nodes are visited in depth-first tree order, where a node is visited
+    /// before its children and its children are visited in order. This isn't
+    /// necessarily the order in which nodes were inserted, since a node
+    /// inserted through for example [`Builder::close_at`] is inserted after
+    /// the nodes it wraps.
+    ///
+    /// For trees built with spans computed by the [`Builder`], tree order is
+    /// also non-decreasing order of the start of their spans. This doesn't
+    /// hold for trees built with [`Builder::set_explicit_spans`], where spans
+    /// can be provided in any order. Use [`Tree::walk_sorted_by_span`] if
+    /// ordering by span must be guaranteed.
+    ///
+    /// See [`Walk`] for documentation.
+    ///
+    /// [`Builder`]: crate::Builder
+    /// [`Builder::close_at`]: crate::Builder::close_at
+    /// [`Builder::set_explicit_spans`]: crate::Builder::set_explicit_spans
+    pub fn walk(&self) -> Walk<'_, T, F> {
+        Walk::new(&self.tree, self.first, Event::Next)
+    }
+
+    /// Iterate over every node in the tree in non-decreasing order of the
+    /// start of their spans.
+    ///
+    /// This is the same order as [`Tree::walk`] for trees built with spans
+    /// computed by the [Builder][crate::Builder], but also holds for trees
+    /// where spans were provided out of order. Nodes which start at the same
+    /// position are visited in the same order as [`Tree::walk`].
+    ///
+    /// This collects and sorts every node in the tree up front, and takes time
+    /// proportional to `n log n` in the number of nodes.
+    ///
+    /// See [`SortedBySpan`] for documentation.
+    #[cfg(feature = "alloc")]
+    pub fn walk_sorted_by_span(&self) -> SortedBySpan<'_, T, F> {
+        SortedBySpan::new(self)
+    }
+
+    /// Iterate over every node in the tree in non-decreasing order of the
+    /// start of their spans, using the buffers in `ctx`.
+    ///
+    /// This is the same as [`Tree::walk_sorted_by_span`], except that the
+    /// nodes are collected into a buffer owned by `ctx` which is reused by
+    /// subsequent queries.
+    ///
+    /// See [`QueryCtx`] for documentation.
+    #[cfg(feature = "alloc")]
+    pub fn walk_sorted_by_span_with_ctx<'a>(
+        &'a self,
+        ctx: &'a mut QueryCtx<F>,
+    ) -> SortedBySpan<'a, T, F> {
+        SortedBySpan::with_ctx(self, ctx)
+    }
+
+    /// Walk the tree in postorder, visiting every node once after all of its
+    /// descendants.
+    ///
+    /// See [`WalkPostorder`] for documentation.
+    pub fn walk_postorder(&self) -> WalkPostorder<'_, T, F> {
+        WalkPostorder::new(self.first(), self.last())
+    }
+
+    /// Walk the tree forwards in a depth-first fashion emitting events
+    /// indicating how the tree is being traversed.
+    ///
+    /// See [`WalkEvents`] for documentation.
+    pub fn walk_events(&self) -> WalkEvents<'_, T, F> {
+        WalkEvents::new(&self.tree, self.first, Event::Next)
+    }
+
+    /// Walk the tree forwards in a depth-first fashion starting at the node
+    /// identified by `id`.
+    ///
+    /// This is the same as [`Node::walk`] on the node with the given
+    /// identifier, without having to resolve the node first. If `id` doesn't
+    /// identify a node in the tree, the walk is empty.
+    ///
+    /// See [`Walk`] for documentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "c1" => {
+    ///             "c2" => {},
+    ///         },
+    ///         "c3" => {},
+    ///     },
+    ///     "root2" => {}
+    /// };
+    ///
+    /// let c1 = tree.first().and_then(|n| n.first()).ok_or("missing c1")?;
+    /// let id = c1.id();
+    ///
+    /// assert_eq!(
+    ///     tree.walk_from_id(id).map(|n| n.value()).collect::<Vec<_>>(),
+    ///     ["c1", "c2", "c3", "root2"]
+    /// );
+    ///
+    /// assert_eq!(
+    ///     tree.walk_from_id(id).inside().map(|n| n.value()).collect::<Vec<_>>(),
+    ///     ["c1", "c2"]
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn walk_from_id(&self, id: F::Pointer) -> Walk<'_, T, F> {
+        Walk::new(&self.tree, self.contains_id(id), Event::Next)
+    }
+
+    /// Walk the tree forwards in a depth-first fashion emitting events
+    /// indicating how the tree is being traversed, starting at the node
+    /// identified by `id`.
+    ///
+    /// This is the same as [`Node::walk_events`] on the node with the given
+    /// identifier, without having to resolve the node first. If `id` doesn't
+    /// identify a node in the tree, the walk is empty.
+    ///
+    /// See [`WalkEvents`] for documentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::node::Event::*;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "c1" => {
+    ///             "c2" => {},
+    ///         },
+    ///         "c3" => {},
+    ///     }
+    /// };
+    ///
+    /// let c1 = tree.first().and_then(|n| n.first()).ok_or("missing c1")?;
+    /// let id = c1.id();
+    ///
+    /// assert_eq!(
+    ///     tree.walk_events_from_id(id).map(|(e, n)| (e, n.value())).collect::<Vec<_>>(),
+    ///     [(Next, "c1"), (Down, "c2"), (Up, "c1"), (Next, "c3"), (Up, "root")]
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn walk_events_from_id(&self, id: F::Pointer) -> WalkEvents<'_, T, F> {
+        WalkEvents::new(&self.tree, self.contains_id(id), Event::Next)
+    }
+
+    /// Iterate over the value, span and [Kind] of every element in the tree
+    /// in the order in which they are stored.
+    ///
+    /// Note that the storage order is not necessarily the same as the order
+    /// in which [`Tree::walk`] visits nodes, since nodes constructed through
+    /// [`Builder::close_at`] are stored after their children.
+    ///
+    /// See [`Flat`] for documentation.
+    ///
+    /// [Kind]: crate::Kind
+    /// [`Builder::close_at`]: crate::Builder::close_at
+    pub fn flat(&self) -> Flat<'_, T, F> {
+        Flat::new(&self.tree)
+    }
+
+    /// Iterate over every node in the tree whose value is equal to `value`.
+    ///
+    /// This scans the storage of the tree directly, so nodes are visited in
+    /// the order in which they are stored. Like with [`Tree::flat`], this is
+    /// the same order as [`Tree::walk`] except for nodes constructed through
+    /// [`Builder::close_at`], which are stored after their children.
+    ///
+    /// See [`FindAll`] for documentation.
+    ///
+    /// [`Builder::close_at`]: crate::Builder::close_at
+    pub fn find_all(&self, value: T) -> FindAll<'_, T, F>
+    where
+        T: PartialEq,
+    {
+        FindAll::new(&self.tree, value)
+    }
+
+    /// Build a lookup table from a key derived from the value of every node
+    /// to the identifiers of the nodes with that key, such as all nodes of a
+    /// given kind.
+    ///
+    /// This is built in a single scan over the storage of the tree, after
+    /// which finding the nodes with a given key takes logarithmic time in the
+    /// number of keys. The identifiers for each key are in the order in
+    /// which they are stored, like with [`Tree::find_all`].
+    ///
+    /// See [`IndexBy`] for documentation.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn index_by<K, M>(&self, key: M) -> IndexBy<K, F>
+    where
+        K: Ord,
+        M: FnMut(&T) -> K,
+    {
+        IndexBy::new(self, key)
+    }
+
+    /// Iterate over the offsets of all tokens in the token index of the tree,
+    /// in order of the offsets.
+    ///
+    /// The index contains every token with a non-empty span, as well as empty
+    /// tokens if they are indexed through
+    /// [`Builder::set_index_empty_tokens`][crate::Builder::set_index_empty_tokens].
+    ///
+    /// Each item is the offset at which the token ends together with the
+    /// identifier of the token. This is the same table which is used to
+    /// perform queries such as [`Tree::node_with_span`], and can be binary
+    /// searched to find the token boundary at or before a given position.
+    ///
+    /// Serializers can store these entries alongside the nodes of the tree,
+    /// and provide them to [`TreeRaw::push_index`] when deserializing instead
+    /// of having the table constructed from the tokens of the tree.
+    ///
+    /// See [`TokenOffsets`] for documentation.
+    pub fn token_offsets(&self) -> TokenOffsets<'_, F> {
+        TokenOffsets::new(&self.indexes)
+    }
+
+    /// Write the source of the tree by visiting all of its tokens in order.
+    ///
+    /// The `token` callback is called with the value and span of each token,
+    /// including empty ones, and returns the text to write for it. This can
+    /// either be a slice of the original source or synthesized text, such as
+    /// the expansion of a token which refers to something else.
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing to the output fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug, Clone, Copy)]
+    /// enum Syntax {
+    ///     Root,
+    ///     Literal,
+    ///     Whitespace,
+    ///     Synthetic(usize),
+    /// }
+    ///
+    /// use Syntax::*;
+    ///
+    /// let source = "hello $world";
+    /// let synthetic = ["Earth"];
+    ///
+    /// let tree = syntree::tree! {
+    ///     Root => {
+    ///         (Literal, 5),
+    ///         (Whitespace, 1),
+    ///         (Synthetic(0), 6),
+    ///     }
+    /// };
+    ///
+    /// let mut output = String::new();
+    ///
+    /// tree.write_source(&mut output, |value, span| match value {
+    ///     Synthetic(id) => Cow::Owned(synthetic[id].to_uppercase()),
+    ///     _ => Cow::Borrowed(&source[span.range()]),
+    /// })?;
+    ///
+    /// assert_eq!(output, "hello EARTH");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn write_source<O, C, S>(&self, mut o: O, mut token: C) -> fmt::Result
+    where
+        O: fmt::Write,
+        C: FnMut(T, Span<F::Index>) -> S,
+        S: AsRef<str>,
+    {
+        for node in self.walk() {
+            if node.is_token() {
+                o.write_str(token(node.value(), *node.span()).as_ref())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the internal consistency of the tree.
+    ///
+    /// This verifies that:
+    /// * Parent, sibling and child links are symmetric, and every node in the
+    ///   tree is reachable exactly once.
+    /// * Tokens have no children.
+    /// * The span of every node is contained within the span of its parent.
+    /// * The spans of siblings and of tokens are monotonically non-decreasing
+    ///   in tree order, so they don't overlap.
+    /// * The token index used for span queries refers to every non-empty
+    ///   token in order of their spans, using the offset at which it ends.
+    ///
+    /// Trees built with [`Builder::set_explicit_spans`] enabled may have
+    /// tokens and siblings in any order. For them, it's only checked that
+    /// adjacent siblings don't overlap, and that tokens don't overlap in the
+    /// order of the token index.
+    ///
+    /// [`Builder::set_explicit_spans`]: crate::Builder::set_explicit_spans
+    ///
+    /// Trees produced by a [Builder][crate::Builder] with automatically
+    /// computed spans are always valid. This is primarily useful when trees
+    /// are produced through other means, such as custom spans or conversions,
+    /// and when fuzzing.
+    ///
+    /// This takes time proportional to the number of nodes in the tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors with a [`ValidationError`] describing the first inconsistency
+    /// found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Span, ValidationError};
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// tree.validate()?;
+    ///
+    /// let mut tree = syntree::Builder::new();
+    ///
+    /// tree.open("root")?;
+    /// tree.token_with("lit", Span::new(0, 4))?;
+    /// let lit = tree.token_with("lit", Span::new(2, 6))?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    /// assert_eq!(tree.validate(), Err(ValidationError::SpanOverlap(lit)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError<F::Pointer>> {
+        validate::validate(self, true)
+    }
+
+    /// Check that the links of the tree are intact, without checking spans or
+    /// the token index.
+    ///
+    /// This verifies that every link refers to a node in the tree, that the
+    /// links are symmetric and acyclic, that every node is reachable exactly
+    /// once, and that tokens have no children. It's a cheaper alternative to
+    /// [`Tree::validate`] for when the spans of a tree are trusted or
+    /// irrelevant, but the tree has been loaded from an untrusted source, such
+    /// as when it's constructed through [`TreeRaw`].
+    ///
+    /// This takes time proportional to the number of nodes in the tree and
+    /// doesn't allocate.
+    ///
+    /// # Errors
+    ///
+    /// Errors with the first [`ValidationError`] which is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Span, ValidationError};
+    ///
+    /// let mut tree = Builder::new();
+    ///
+    /// tree.open("root")?;
+    /// tree.token_with("a", Span::new(0, 4))?;
+    /// let lit = tree.token_with("b", Span::new(2, 6))?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// // The spans of the tokens overlap, but the links are intact.
+    /// assert_eq!(tree.check_links(), Ok(()));
+    /// assert_eq!(tree.validate(), Err(ValidationError::SpanOverlap(lit)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn check_links(&self) -> Result<(), ValidationError<F::Pointer>> {
+        validate::validate(self, false)
+    }
+
+    /// Verify that the tree is consistent with the `source` it was built
+    /// from.
+    ///
+    /// This checks that the span of the tree covers exactly the whole source,
+    /// and that the span of every token lies on a character boundary so that
+    /// it can be used to slice the source.
+    ///
+    /// A lexer and a builder which disagree on the length of tokens otherwise
+    /// tend to surface much later, as a panic when slicing the source.
+    ///
+    /// This takes time proportional to the number of nodes in the tree.
+    ///
+    /// # Errors
+    ///
+    /// Errors with a [`SourceMismatch`] describing the first mismatch found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::SourceMismatch;
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("ident", 3),
+    ///         ("ws", 1),
+    ///         ("string", 4),
+    ///     }
+    /// };
+    ///
+    /// tree.verify_source("foo \"ö\"")?;
+    ///
+    /// assert_eq!(
+    ///     tree.verify_source("foo"),
+    ///     Err(SourceMismatch::Length { tree: 8, source: 3 })
+    /// );
+    ///
+    /// let ws = tree.first().and_then(|n| n.children().nth(1)).ok_or("missing ws")?;
+    ///
+    /// assert_eq!(
+    ///     tree.verify_source("fooöbar"),
+    ///     Err(SourceMismatch::NotCharBoundary(ws.id()))
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn verify_source(&self, source: &str) -> Result<(), SourceMismatch<F::Pointer>> {
+        validate::verify_source(self, source)
+    }
+
+    /// Iterate over the nodes whose span contains the offset `index`, starting
+    /// with the top-level node and ending with the innermost node.
+    ///
+    /// A span contains an offset if it starts at or before it and ends after
+    /// it, so empty nodes and the offset at the very end of the input are
+    /// never part of a path.
+    ///
+    /// See [`PathTo`] for documentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///     },
+    ///     "root2" => {
+    ///         ("lit", 2)
+    ///     }
+    /// };
+    ///
+    /// let path = tree.path_to(2).map(|n| n.value()).collect::<Vec<_>>();
+    /// assert_eq!(path, ["root", "number", "lit"]);
+    ///
+    /// let path = tree.path_to(5).map(|n| n.value()).collect::<Vec<_>>();
+    /// assert_eq!(path, ["root", "ws"]);
+    ///
+    /// let path = tree.path_to(7).map(|n| n.value()).collect::<Vec<_>>();
+    /// assert_eq!(path, ["root2", "lit"]);
+    ///
+    /// assert_eq!(tree.path_to(8).count(), 0);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[inline]
+    pub fn path_to(&self, index: F::Index) -> PathTo<'_, T, F> {
+        PathTo::new(self.first(), index)
+    }
+
+    /// Find the first node in the tree matching the given predicate.
+    ///
+    /// Nodes are visited in the same depth-first order as [`Tree::walk`], and
+    /// the search stops as soon as a matching node is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let lit = tree.find(|n| n.value() == "lit").ok_or("missing lit")?;
+    /// assert_eq!(lit.range(), 0..5);
+    ///
+    /// assert!(tree.find(|n| n.value() == "missing").is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn find<'a, P>(&'a self, mut predicate: P) -> Option<Node<'a, T, F>>
+    where
+        P: FnMut(Node<'a, T, F>) -> bool,
+    {
+        self.walk().find(|n| predicate(*n))
+    }
+
+    /// Apply the given function to nodes in the tree, returning the first
+    /// non-`None` result.
+    ///
+    /// Nodes are visited in the same depth-first order as [`Tree::walk`], and
+    /// the search stops as soon as the function returns a value.
+    ///
+    /// # Examples
     ///
-    /// See [`WalkEvents`] for documentation.
-    pub fn walk_events(&self) -> WalkEvents<'_, T, F> {
-        WalkEvents::new(&self.tree, self.first, Event::Next)
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         "ident" => {
+    ///             ("lit", 3)
+    ///         }
+    ///     }
+    /// };
+    ///
+    /// let span = tree.find_map(|n| (n.value() == "ident").then(|| *n.span()));
+    /// assert_eq!(span, Some(syntree::Span::new(5, 8)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn find_map<'a, P, O>(&'a self, predicate: P) -> Option<O>
+    where
+        P: FnMut(Node<'a, T, F>) -> Option<O>,
+    {
+        self.walk().find_map(predicate)
     }
 
     /// Get the first child node in the tree.
@@ -241,16 +1587,72 @@ where
     }
 
     /// Get a mutable reference to an element in the tree.
-    pub(crate) fn get_mut(
-        &mut self,
-        id: F::Pointer,
-    ) -> Option<&mut Links<T, F::Index, F::Pointer>> {
+    pub(crate) fn get_mut(&mut self, id: F::Pointer) -> Option<&mut LinksOf<T, F>> {
         self.tree.get_mut(id.get())
     }
 
     /// Push a new node into the tree with the specified links.
-    pub(crate) fn push(&mut self, links: Links<T, F::Index, F::Pointer>) -> Result<(), F::Error> {
-        self.tree.push(links)
+    pub(crate) fn push(&mut self, links: LinksOf<T, F>) -> Result<(), F::Error> {
+        let is_token = links.kind == Kind::Token;
+        self.tree.push(links)?;
+
+        if is_token {
+            self.tokens += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Remove every element with an identifier of `len` or more, along with
+    /// their entries in the token index.
+    ///
+    /// Links to the removed elements from the remaining ones are not updated.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        for links in self.tree.get(len..).unwrap_or_default() {
+            if links.kind == Kind::Token {
+                self.tokens -= 1;
+            }
+        }
+
+        self.tree.truncate(len);
+
+        // Tokens are indexed in the order in which they are inserted, so the
+        // entries of the removed ones are at the end of the index.
+        let mut n = self.indexes.len();
+
+        while let Some(last) = n.checked_sub(1) {
+            match self.indexes.get(last) {
+                Some((_, id)) if id.get() >= len => n = last,
+                _ => break,
+            }
+        }
+
+        self.indexes.truncate(n);
+    }
+
+    /// Test if empty tokens are included in the indexes of the tree.
+    pub(crate) const fn index_empty(&self) -> bool {
+        self.index_empty
+    }
+
+    /// Set if empty tokens are included in the indexes of the tree.
+    pub(crate) fn set_index_empty(&mut self, index_empty: bool) {
+        self.index_empty = index_empty;
+    }
+
+    /// Test if the tree was built with explicit spans.
+    pub(crate) const fn explicit_spans(&self) -> bool {
+        self.explicit_spans
+    }
+
+    /// Mark the tree as built with explicit spans.
+    pub(crate) fn set_explicit_spans(&mut self) {
+        self.explicit_spans = true;
+    }
+
+    /// Set the identifier of the source the tree was built from.
+    pub(crate) fn set_source_id(&mut self, source_id: Option<u64>) {
+        self.source_id = source_id;
     }
 
     /// Push the given index.
@@ -259,17 +1661,17 @@ where
     }
 
     /// Optionally get the links at the given location.
-    pub(crate) fn links_at_mut(
-        &mut self,
-        index: F::Pointer,
-    ) -> Option<&mut Links<T, F::Index, F::Pointer>> {
+    pub(crate) fn links_at_mut(&mut self, index: F::Pointer) -> Option<&mut LinksOf<T, F>> {
         self.tree.get_mut(index.get())
     }
 
-    /// Get the ndoe at the given index.
+    /// Get the node at the given index.
     ///
-    /// Note that an id might be re-used across different trees. This behavior
-    /// is never unsafe, but is not well-defined.
+    /// Identifiers are allocated deterministically in the order in which nodes
+    /// are inserted, so an id will be re-used across different trees. Using an
+    /// id with a tree it wasn't taken from is never unsafe, but it refers to an
+    /// unrelated node. To catch such mistakes in debug builds, use
+    /// [`Tree::node_id`] and [`Tree::get_by_id`].
     ///
     /// # Examples
     ///
@@ -301,6 +1703,309 @@ where
         Some(Node::new(cur, &self.tree))
     }
 
+    /// Number every node in the tree by the position at which it's entered and
+    /// exited in a depth-first walk.
+    ///
+    /// This takes time proportional to the number of nodes in the tree, after
+    /// which ancestry checks and subtree membership tests are constant time.
+    ///
+    /// See [`EulerTour`] for documentation.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn euler_tour(&self) -> EulerTour<F::Pointer> {
+        EulerTour::new(self)
+    }
+
+    /// Attach comments to the nodes they document, following the conventions
+    /// used by most documentation tools and formatters.
+    ///
+    /// The `pred` classifies each element as [`Trivia`], or returns `None` if
+    /// it's not trivia. Since the tree doesn't store any text, distinguishing
+    /// between [`Trivia::Whitespace`] and [`Trivia::Newline`] is up to the
+    /// caller, typically by looking at the source covered by the span of the
+    /// element.
+    ///
+    /// Comments are attached among their siblings, where any element which is
+    /// not trivia counts as a neighbor, including tokens:
+    /// * A comment which follows a neighbor on the same line is a trailing
+    ///   comment, and is attached to that neighbor.
+    /// * Any other comment is attached to the next neighbor which follows it.
+    /// * Comments with no neighbor following them are attached to the
+    ///   preceding neighbor, or to the parent if they are the only siblings.
+    ///   Top-level comments in a tree without any other elements are not
+    ///   attached to anything.
+    ///
+    /// The returned map associates each node which has comments attached to
+    /// it with the identifiers of those comments in the order they appear.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Trivia;
+    ///
+    /// // fn a() {} // a
+    /// // /* b */
+    /// // fn b() {}
+    /// let tree = syntree::tree! {
+    ///     "fn" => {
+    ///         ("fn", 2),
+    ///         ("ws", 1),
+    ///         ("ident", 1),
+    ///         ("body", 5),
+    ///     },
+    ///     ("ws", 1),
+    ///     ("comment", 4),
+    ///     ("nl", 1),
+    ///     ("comment", 7),
+    ///     ("nl", 1),
+    ///     "fn" => {
+    ///         ("fn", 2),
+    ///         ("ws", 1),
+    ///         ("ident", 1),
+    ///         ("body", 5),
+    ///     },
+    /// };
+    ///
+    /// let map = tree.attach_trivia(|n| match n.value() {
+    ///     "comment" => Some(Trivia::Comment),
+    ///     "ws" => Some(Trivia::Whitespace),
+    ///     "nl" => Some(Trivia::Newline),
+    ///     _ => None,
+    /// });
+    ///
+    /// let attached = |node: syntree::Node<'_, _, _>| {
+    ///     map.get(node.id())
+    ///         .into_iter()
+    ///         .flatten()
+    ///         .filter_map(|&id| Some(*tree.get(id)?.span()))
+    ///         .map(|span| span.range())
+    ///         .collect::<Vec<_>>()
+    /// };
+    ///
+    /// let a = tree.first().ok_or("missing a")?;
+    /// let b = tree.last().ok_or("missing b")?;
+    ///
+    /// assert_eq!(attached(a), [10..14]);
+    /// assert_eq!(attached(b), [15..22]);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn attach_trivia<'a, P>(&'a self, pred: P) -> NodeMap<F, alloc::vec::Vec<F::Pointer>>
+    where
+        P: FnMut(Node<'a, T, F>) -> Option<Trivia>,
+    {
+        trivia::attach(self, pred)
+    }
+
+    /// Get the raw links of the node identified by `id`, or `None` if there is
+    /// no such node.
+    ///
+    /// See [`LinkInfo`] for documentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let number = root.first().ok_or("missing number")?;
+    /// let ws = root.last().ok_or("missing ws")?;
+    ///
+    /// let links = tree.links(number.id()).ok_or("missing links")?;
+    /// assert_eq!(links.parent, Some(root.id()));
+    /// assert_eq!(links.prev, None);
+    /// assert_eq!(links.next, Some(ws.id()));
+    /// assert_eq!(links.first.and_then(|id| tree.get(id)).map(|n| n.value()), Some("lit"));
+    /// assert_eq!(links.first, links.last);
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn links(&self, id: F::Pointer) -> Option<LinkInfo<F::Pointer>> {
+        let links = self.tree.get(id.get())?;
+
+        Some(LinkInfo {
+            parent: links.parent,
+            prev: links.prev,
+            next: links.next,
+            first: links.first,
+            last: links.last,
+        })
+    }
+
+    /// Get the identifier of the parent of the element identified by `id`.
+    ///
+    /// Returns `None` if there is no such element, or if it's at the top level
+    /// of the tree. This takes constant time and doesn't construct a [`Node`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         "number" => {
+    ///             ("lit", 5)
+    ///         },
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?.id();
+    /// let number = tree.first_child_id(root).ok_or("missing number")?;
+    /// let ws = tree.next_id(number).ok_or("missing ws")?;
+    /// let lit = tree.first_child_id(number).ok_or("missing lit")?;
+    ///
+    /// assert_eq!(tree.parent_id(lit), Some(number));
+    /// assert_eq!(tree.parent_id(number), Some(root));
+    /// assert_eq!(tree.parent_id(root), None);
+    /// assert_eq!(tree.prev_id(ws), Some(number));
+    /// assert_eq!(tree.last_child_id(root), Some(ws));
+    ///
+    /// assert_eq!(tree.value_of(lit), Some("lit"));
+    /// assert_eq!(tree.span_of(ws), Some(syntree::Span::new(5, 6)));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn parent_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get())?.parent
+    }
+
+    /// Get the identifier of the previous sibling of the element identified by
+    /// `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn prev_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get())?.prev
+    }
+
+    /// Get the identifier of the next sibling of the element identified by
+    /// `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn next_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get())?.next
+    }
+
+    /// Get the identifier of the first child of the element identified by
+    /// `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn first_child_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get())?.first
+    }
+
+    /// Get the identifier of the last child of the element identified by
+    /// `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn last_child_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get())?.last
+    }
+
+    /// Get the value of the element identified by `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn value_of(&self, id: F::Pointer) -> Option<T> {
+        Some(self.tree.get(id.get())?.data.get())
+    }
+
+    /// Get the span of the element identified by `id`.
+    ///
+    /// This takes constant time and doesn't construct a [`Node`]. See
+    /// [`Tree::parent_id`] for an example.
+    #[must_use]
+    pub fn span_of(&self, id: F::Pointer) -> Option<Span<F::Index>> {
+        Some(self.tree.get(id.get())?.span)
+    }
+
+    /// Return `id` if it identifies a node in the tree.
+    #[inline]
+    fn contains_id(&self, id: F::Pointer) -> Option<F::Pointer> {
+        self.tree.get(id.get()).map(|_| id)
+    }
+
+    /// Get an identifier for `node` which remembers that it belongs to this
+    /// tree.
+    ///
+    /// See [`NodeId`] for documentation.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this panics if `node` doesn't belong to this tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let id = tree.node_id(&root);
+    /// assert_eq!(id.pointer(), root.id());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn node_id(&self, node: &Node<'_, T, F>) -> NodeId<F::Pointer> {
+        debug_assert!(
+            node.belongs_to(&self.tree),
+            "node doesn't belong to this tree"
+        );
+
+        NodeId::new(node.id(), &self.fingerprint)
+    }
+
+    /// Get the node with the given identifier.
+    ///
+    /// See [`NodeId`] for documentation.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this panics if `id` was constructed from a different
+    /// tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3)
+    ///     }
+    /// };
+    ///
+    /// let root = tree.first().ok_or("missing root")?;
+    /// let id = tree.node_id(&root);
+    ///
+    /// let root = tree.get_by_id(id).ok_or("missing root")?;
+    /// assert_eq!(root.value(), "root");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn get_by_id(&self, id: NodeId<F::Pointer>) -> Option<Node<'_, T, F>> {
+        id.check(&self.fingerprint);
+        self.get(id.pointer())
+    }
+
     /// Access the [Span] of the node as a [Range].
     ///
     /// # Examples
@@ -401,6 +2106,31 @@ where
     /// assert_eq!(child.value(), "child");
     /// # Ok::<_, Box<dyn core::error::Error>>(())
     /// ```
+    ///
+    /// Indexed empty tokens are only found by queries for a single point:
+    ///
+    /// ```
+    /// let mut tree = syntree::Builder::new();
+    /// tree.set_index_empty_tokens(true);
+    ///
+    /// tree.token("a", 3)?;
+    /// tree.token_empty("e")?;
+    /// tree.token_empty("e")?;
+    /// tree.token("b", 2)?;
+    /// tree.token("c", 2)?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let node = tree.node_with_range(3..3).ok_or("missing at 3")?;
+    /// assert_eq!(node.value(), "e");
+    ///
+    /// let node = tree.node_with_range(3..5).ok_or("missing at 3..5")?;
+    /// assert_eq!(node.value(), "b");
+    ///
+    /// let node = tree.node_with_range(3..4).ok_or("missing at 3..4")?;
+    /// assert_eq!(node.value(), "b");
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
     #[must_use]
     pub fn node_with_range(&self, span: Range<usize>) -> Option<Node<'_, T, F>> {
         let start = F::Index::from_usize(span.start)?;
@@ -513,33 +2243,193 @@ where
         self.node_with_span_internal(span.start, span.end)
     }
 
+    /// Find the token which contains the offset `index`, using the token
+    /// index of the tree.
+    ///
+    /// A token contains an offset if it starts at or before it and ends after
+    /// it, so empty tokens are never returned. This returns `None` if `index`
+    /// falls outside of every token. To only look for tokens within a
+    /// particular node, see [`Node::token_at`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("ident", 3),
+    ///         "number" => {
+    ///             ("lit", 2),
+    ///         },
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(tree.token_at(0).map(|n| n.value()), Some("ident"));
+    /// assert_eq!(tree.token_at(4).map(|n| n.value()), Some("lit"));
+    /// assert!(tree.token_at(5).is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn token_at(&self, index: F::Index) -> Option<Node<'_, T, F>> {
+        let node = self.get(self.indexes.find(index)?)?;
+
+        if node.is_token() && node.span().contains(&index) {
+            return Some(node);
+        }
+
+        None
+    }
+
+    /// Find the innermost empty node or token positioned at the insertion
+    /// point `index`.
+    ///
+    /// Empty nodes, such as error nodes inserted by a parser where something
+    /// was missing, don't cover any tokens and are therefore never returned by
+    /// [`Tree::node_with_span`], which instead returns a neighboring or
+    /// enclosing node. If multiple empty nodes are positioned at the same point and
+    /// are equally deep, the first one in the tree is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::{Builder, Span};
+    ///
+    /// let mut tree = Builder::new();
+    ///
+    /// tree.open("call")?;
+    /// tree.token("ident", 3)?;
+    /// tree.token("open", 1)?;
+    /// tree.open("args")?;
+    /// tree.open("error")?;
+    /// tree.close()?;
+    /// tree.close()?;
+    /// tree.token("close", 1)?;
+    /// tree.close()?;
+    ///
+    /// let tree = tree.build()?;
+    ///
+    /// let node = tree.node_with_span(Span::point(4)).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "call");
+    ///
+    /// let node = tree.node_at_insertion_point(4).ok_or("missing node")?;
+    /// assert_eq!(node.value(), "error");
+    /// assert_eq!(node.span(), Span::point(4));
+    ///
+    /// assert!(tree.node_at_insertion_point(3).is_none());
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn node_at_insertion_point(&self, index: F::Index) -> Option<Node<'_, T, F>> {
+        let mut best = None::<(usize, Node<'_, T, F>)>;
+        let mut current = self.first();
+        let mut depth = 0usize;
+
+        while let Some(node) = current {
+            let span = node.span();
+
+            if span.start > index {
+                // Siblings are ordered, so none of the remaining siblings
+                // can contain the index.
+                (current, depth) = self.climb(node, depth);
+                continue;
+            }
+
+            if span.end >= index {
+                if span.is_empty() && best.map_or(true, |(d, _)| depth > d) {
+                    best = Some((depth, node));
+                }
+
+                if let Some(first) = node.first() {
+                    current = Some(first);
+                    depth += 1;
+                    continue;
+                }
+            }
+
+            match node.next() {
+                Some(next) => current = Some(next),
+                None => (current, depth) = self.climb(node, depth),
+            }
+        }
+
+        best.map(|(_, node)| node)
+    }
+
+    /// Climb from `node` to the closest ancestor which has a next sibling and
+    /// return that sibling along with its depth.
+    fn climb<'a>(
+        &'a self,
+        node: Node<'a, T, F>,
+        mut depth: usize,
+    ) -> (Option<Node<'a, T, F>>, usize) {
+        let mut node = node;
+
+        while let Some(parent) = node.parent() {
+            depth = depth.saturating_sub(1);
+
+            if let Some(next) = parent.next() {
+                return (Some(next), depth);
+            }
+
+            node = parent;
+        }
+
+        (None, depth)
+    }
+
     fn node_with_span_internal(&self, start: F::Index, end: F::Index) -> Option<Node<'_, T, F>> {
-        let result = self.indexes.binary_search_by(|f| f.index.cmp(&start));
+        // Empty tokens can only contain queries for a single point.
+        let empty = if start == end {
+            self.empty_token_at(start)
+        } else {
+            None
+        };
 
-        let n = match result {
-            Ok(n) => n.saturating_add(1),
-            Err(n) => n,
+        let id = match empty {
+            Some(id) => id,
+            None => self.indexes.find(start)?,
         };
 
-        let mut node = self.get(self.indexes.get(n)?.id)?;
+        let mut node = self.get(id)?;
 
         while let Some(parent) = node.parent() {
             node = parent;
 
-            if parent.span().end >= end {
+            if parent.span().start <= start && parent.span().end >= end {
                 break;
             }
         }
 
         Some(node)
     }
+
+    /// Find the first empty token at the given index, if empty tokens are
+    /// being indexed.
+    fn empty_token_at(&self, index: F::Index) -> Option<F::Pointer> {
+        if !self.index_empty {
+            return None;
+        }
+
+        for n in self.indexes.lower_bound(index)..self.indexes.len() {
+            let (at, id) = self.indexes.get(n)?;
+
+            if at != index {
+                break;
+            }
+
+            if self.get(id)?.span().is_empty() {
+                return Some(id);
+            }
+        }
+
+        None
+    }
 }
 
 impl<T, F> Clone for Tree<T, F>
 where
     T: Copy,
     F: Flavor<Indexes: Clone, Width: Width<Pointer: Clone>>,
-    F::Storage<Links<T, F::Index, F::Pointer>>: Clone,
+    F::Storage<LinksOf<T, F>>: Clone,
 {
     #[inline]
     fn clone(&self) -> Self {
@@ -549,6 +2439,11 @@ where
             indexes: self.indexes.clone(),
             first: self.first,
             last: self.last,
+            index_empty: self.index_empty,
+            explicit_spans: self.explicit_spans,
+            tokens: self.tokens,
+            source_id: self.source_id,
+            fingerprint: self.fingerprint.clone(),
         }
     }
 }
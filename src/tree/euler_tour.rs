@@ -0,0 +1,145 @@
+#![cfg(feature = "alloc")]
+
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::node::Event;
+use crate::pointer::{Pointer, PointerUsize};
+
+use super::Tree;
+
+/// Preorder numbering of every node in a tree as constructed by
+/// [`Tree::euler_tour`].
+///
+/// Every node is assigned the position at which it's entered in a depth-first
+/// walk of the tree, and the position at which the walk exits it after having
+/// visited all of its descendants. The descendants of a node therefore occupy
+/// the contiguous range of positions between the two, which means that
+/// ancestry and subtree membership can be tested in constant time.
+///
+/// The numbering is a snapshot of the tree, and is not updated if the tree is
+/// modified.
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "c1" => {
+///             ("lit", 1),
+///         },
+///         "c2" => {
+///             ("lit", 2),
+///         }
+///     }
+/// };
+///
+/// let tour = tree.euler_tour();
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// let c1 = root.first().ok_or("missing c1")?;
+/// let c2 = root.last().ok_or("missing c2")?;
+/// let lit = c2.first().ok_or("missing lit")?;
+///
+/// assert_eq!(tour.enter(root.id()), Some(0));
+/// assert_eq!(tour.exit(root.id()), Some(5));
+/// assert_eq!(tour.subtree(c2.id()), Some(3..5));
+///
+/// assert!(tour.is_ancestor(root.id(), lit.id()));
+/// assert!(tour.is_ancestor(c2.id(), lit.id()));
+/// assert!(!tour.is_ancestor(c1.id(), lit.id()));
+/// assert!(!tour.is_ancestor(lit.id(), lit.id()));
+///
+/// let subtree = tour.subtree(c2.id()).ok_or("missing c2")?;
+///
+/// let values = subtree
+///     .filter_map(|n| tree.get(tour.node_at(n)?))
+///     .map(|n| n.value())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(values, ["c2", "lit"]);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct EulerTour<P = PointerUsize> {
+    /// Enter and exit position of every node, indexed by identifier.
+    ranges: Vec<Range<usize>>,
+    /// Nodes in the order in which they are entered.
+    order: Vec<P>,
+}
+
+impl<P> EulerTour<P>
+where
+    P: Pointer,
+{
+    pub(super) fn new<T, F>(tree: &Tree<T, F>) -> Self
+    where
+        T: Copy,
+        F: Flavor<Pointer = P>,
+    {
+        let mut ranges = alloc::vec![0..0; tree.len()];
+        let mut order = Vec::with_capacity(tree.len());
+
+        for (event, node) in tree.walk_events() {
+            let n = order.len();
+
+            let Some(range) = ranges.get_mut(node.id().get()) else {
+                continue;
+            };
+
+            match event {
+                Event::Next | Event::Down => {
+                    order.push(node.id());
+                    *range = n..n + 1;
+                }
+                Event::Up => {
+                    range.end = n;
+                }
+            }
+        }
+
+        Self { ranges, order }
+    }
+
+    /// Get the position at which the node identified by `id` is entered,
+    /// which is its position in a preorder walk of the tree.
+    #[must_use]
+    pub fn enter(&self, id: P) -> Option<usize> {
+        Some(self.ranges.get(id.get())?.start)
+    }
+
+    /// Get the position at which the node identified by `id` is exited, which
+    /// is the position right after its last descendant.
+    #[must_use]
+    pub fn exit(&self, id: P) -> Option<usize> {
+        Some(self.ranges.get(id.get())?.end)
+    }
+
+    /// Get the range of positions occupied by the node identified by `id` and
+    /// all of its descendants.
+    #[must_use]
+    pub fn subtree(&self, id: P) -> Option<Range<usize>> {
+        self.ranges.get(id.get()).cloned()
+    }
+
+    /// Get the identifier of the node entered at position `n`.
+    #[must_use]
+    pub fn node_at(&self, n: usize) -> Option<P> {
+        self.order.get(n).copied()
+    }
+
+    /// Test if the node identified by `a` is a proper ancestor of the node
+    /// identified by `b`.
+    ///
+    /// A node is not an ancestor of itself.
+    #[must_use]
+    pub fn is_ancestor(&self, a: P, b: P) -> bool {
+        let (Some(a), Some(b)) = (self.ranges.get(a.get()), self.ranges.get(b.get())) else {
+            return false;
+        };
+
+        a.start < b.start && b.start < a.end
+    }
+}
@@ -0,0 +1,115 @@
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::flavor::Flavor;
+use crate::links::LinksOf;
+use crate::node::Node;
+
+/// An iterator over every node in a tree with a given value, in the order in
+/// which they are stored.
+///
+/// This scans the storage of the tree directly instead of following links,
+/// which makes it faster than filtering a [`Tree::walk`][crate::Tree::walk].
+///
+/// See [`Tree::find_all`][crate::Tree::find_all].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "error" => {
+///             ("ident", 3),
+///         },
+///         ("ws", 1),
+///         "item" => {
+///             ("ident", 2),
+///             "error" => {}
+///         }
+///     }
+/// };
+///
+/// let errors = tree.find_all("error").map(|n| n.parent().map(|p| p.value()));
+/// assert!(errors.eq([Some("root"), Some("item")]));
+///
+/// let idents = tree.find_all("ident").map(|n| n.span().range());
+/// assert!(idents.eq([0..3, 4..6]));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct FindAll<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: &'a [LinksOf<T, F>],
+    iter: slice::Iter<'a, LinksOf<T, F>>,
+    value: T,
+}
+
+impl<'a, T, F> FindAll<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) fn new(tree: &'a [LinksOf<T, F>], value: T) -> Self {
+        Self {
+            tree,
+            iter: tree.iter(),
+            value,
+        }
+    }
+}
+
+impl<'a, T, F> Iterator for FindAll<'a, T, F>
+where
+    T: Copy + PartialEq,
+    F: Flavor,
+{
+    type Item = Node<'a, T, F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let links = self.iter.find(|links| links.data.get() == self.value)?;
+        Some(Node::new(links, self.tree))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.iter.len()))
+    }
+}
+
+impl<T, F> DoubleEndedIterator for FindAll<'_, T, F>
+where
+    T: Copy + PartialEq,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let links = self.iter.rfind(|links| links.data.get() == self.value)?;
+        Some(Node::new(links, self.tree))
+    }
+}
+
+impl<T, F> FusedIterator for FindAll<'_, T, F>
+where
+    T: Copy + PartialEq,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for FindAll<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            iter: self.iter.clone(),
+            value: self.value,
+        }
+    }
+}
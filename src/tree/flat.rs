@@ -0,0 +1,131 @@
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::flavor::Flavor;
+use crate::kind::Kind;
+use crate::links::LinksOf;
+use crate::span::Span;
+
+/// An iterator over the value, span and kind of every element in a tree, in
+/// the order in which they are stored.
+///
+/// This does not construct any [Node][crate::Node] handles or follow any
+/// links, which makes it suitable for fast scans over an entire tree.
+///
+/// See [`Tree::flat`][crate::Tree::flat].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::{Kind, Span};
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("number", 3),
+///         ("ident", 2),
+///     }
+/// };
+///
+/// let items = tree.flat().collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     items,
+///     [
+///         ("root", Span::new(0, 5), Kind::Node),
+///         ("number", Span::new(0, 3), Kind::Token),
+///         ("ident", Span::new(3, 5), Kind::Token),
+///     ]
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct Flat<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    iter: slice::Iter<'a, LinksOf<T, F>>,
+}
+
+impl<'a, T, F> Flat<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) fn new(tree: &'a [LinksOf<T, F>]) -> Self {
+        Self { iter: tree.iter() }
+    }
+}
+
+impl<T, F> Iterator for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = (T, Span<F::Index>, Kind);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let links = self.iter.next()?;
+        Some((links.data.get(), links.span, links.kind))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, F> DoubleEndedIterator for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let links = self.iter.next_back()?;
+        Some((links.data.get(), links.span, links.kind))
+    }
+}
+
+impl<T, F> ExactSizeIterator for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<T, F> FusedIterator for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<T, F> Default for Flat<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self { iter: [].iter() }
+    }
+}
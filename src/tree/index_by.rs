@@ -0,0 +1,253 @@
+#![cfg(feature = "alloc")]
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::Range;
+use core::slice;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::pointer::Pointer;
+
+use super::Tree;
+
+/// A lookup table from a key derived from the value of every node to the
+/// identifiers of the nodes with that key.
+///
+/// See [`Tree::index_by`][super::Tree::index_by].
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// enum Syntax {
+///     Root,
+///     Fn,
+///     Ident,
+///     Whitespace,
+/// }
+///
+/// use Syntax::*;
+///
+/// let tree = syntree::tree! {
+///     Root => {
+///         Fn => {
+///             (Ident, 3),
+///             (Whitespace, 1),
+///         },
+///         Fn => {
+///             (Ident, 5),
+///         }
+///     }
+/// };
+///
+/// let index = tree.index_by(|value| *value);
+///
+/// assert_eq!(index.get(&Fn).len(), 2);
+/// assert!(index.get(&Root).iter().eq(tree.first().map(|n| n.id()).iter()));
+///
+/// let idents = index.get(&Ident).iter().flat_map(|&id| tree.span_of(id)).map(|s| s.len());
+/// assert!(idents.eq([3, 5]));
+///
+/// let keys = index.iter().map(|(key, ids)| (*key, ids.len()));
+/// assert!(keys.eq([(Root, 1), (Fn, 2), (Ident, 2), (Whitespace, 1)]));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct IndexBy<K, F>
+where
+    F: Flavor,
+{
+    /// Keys sorted in ascending order, together with the range of their
+    /// identifiers in `ids`.
+    keys: Vec<(K, Range<usize>)>,
+    /// Node identifiers grouped by key.
+    ids: Vec<F::Pointer>,
+}
+
+impl<K, F> IndexBy<K, F>
+where
+    K: Ord,
+    F: Flavor,
+{
+    pub(super) fn new<T, M>(tree: &Tree<T, F>, mut key: M) -> Self
+    where
+        T: Copy,
+        M: FnMut(&T) -> K,
+    {
+        let mut entries = Vec::with_capacity(tree.len());
+
+        for (n, links) in tree.tree.iter().enumerate() {
+            if let Some(id) = F::Pointer::new(n) {
+                entries.push((key(&links.data.get()), id));
+            }
+        }
+
+        // NB: The sort is stable, which keeps the identifiers for each key in
+        // the order in which they are stored.
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut keys = Vec::<(K, Range<usize>)>::new();
+        let mut ids = Vec::with_capacity(entries.len());
+
+        for (key, id) in entries {
+            let n = ids.len();
+            ids.push(id);
+
+            match keys.last_mut() {
+                Some((last, range)) if *last == key => {
+                    range.end = n + 1;
+                }
+                _ => {
+                    keys.push((key, n..n + 1));
+                }
+            }
+        }
+
+        Self { keys, ids }
+    }
+
+    /// Get the identifiers of the nodes with the given `key`, in the order in
+    /// which they are stored in the tree.
+    ///
+    /// This is empty if there are no such nodes.
+    #[must_use]
+    pub fn get(&self, key: &K) -> &[F::Pointer] {
+        let Ok(n) = self.keys.binary_search_by(|(k, _)| k.cmp(key)) else {
+            return &[];
+        };
+
+        self.keys
+            .get(n)
+            .and_then(|(_, range)| self.ids.get(range.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Test if there are any nodes with the given `key`.
+    #[must_use]
+    pub fn contains(&self, key: &K) -> bool {
+        !self.get(key).is_empty()
+    }
+}
+
+impl<K, F> IndexBy<K, F>
+where
+    F: Flavor,
+{
+    /// Iterate over every key in ascending order together with the
+    /// identifiers of the nodes with that key.
+    #[must_use]
+    pub fn iter(&self) -> IndexByIter<'_, K, F> {
+        IndexByIter {
+            keys: self.keys.iter(),
+            ids: &self.ids,
+        }
+    }
+
+    /// Get the number of distinct keys in the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Test if the index is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<K, F> Clone for IndexBy<K, F>
+where
+    K: Clone,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            ids: self.ids.clone(),
+        }
+    }
+}
+
+impl<K, F> fmt::Debug for IndexBy<K, F>
+where
+    K: fmt::Debug,
+    F: Flavor,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, F> IntoIterator for &'a IndexBy<K, F>
+where
+    F: Flavor,
+{
+    type Item = (&'a K, &'a [F::Pointer]);
+    type IntoIter = IndexByIter<'a, K, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of an [`IndexBy`] together with the identifiers
+/// of the nodes with each key.
+///
+/// See [`IndexBy::iter`].
+pub struct IndexByIter<'a, K, F>
+where
+    F: Flavor,
+{
+    keys: slice::Iter<'a, (K, Range<usize>)>,
+    ids: &'a [F::Pointer],
+}
+
+impl<'a, K, F> Iterator for IndexByIter<'a, K, F>
+where
+    F: Flavor,
+{
+    type Item = (&'a K, &'a [F::Pointer]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, range) = self.keys.next()?;
+        Some((key, self.ids.get(range.clone()).unwrap_or_default()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<K, F> DoubleEndedIterator for IndexByIter<'_, K, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, range) = self.keys.next_back()?;
+        Some((key, self.ids.get(range.clone()).unwrap_or_default()))
+    }
+}
+
+impl<K, F> ExactSizeIterator for IndexByIter<'_, K, F> where F: Flavor {}
+
+impl<K, F> FusedIterator for IndexByIter<'_, K, F> where F: Flavor {}
+
+impl<K, F> Clone for IndexByIter<'_, K, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            ids: self.ids,
+        }
+    }
+}
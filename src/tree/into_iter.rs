@@ -0,0 +1,122 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::pointer::Pointer;
+use crate::span::Span;
+
+use super::Tree;
+
+/// An owning iterator over the nodes of a tree in document order.
+///
+/// Every node is yielded together with its span and its depth in the tree,
+/// where top-level nodes have a depth of zero.
+///
+/// See the [`IntoIterator`] implementation of [`Tree`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Span;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "number" => {
+///             ("lit", 3)
+///         },
+///         ("ws", 1),
+///     },
+///     ("eof", 0)
+/// };
+///
+/// let nodes = tree.into_iter().collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     nodes,
+///     [
+///         ("root", Span::new(0, 4), 0),
+///         ("number", Span::new(0, 3), 1),
+///         ("lit", Span::new(0, 3), 2),
+///         ("ws", Span::new(3, 4), 1),
+///         ("eof", Span::new(4, 4), 0),
+///     ]
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct IntoIter<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: Tree<T, F>,
+    // The next node and its depth.
+    node: Option<(F::Pointer, isize)>,
+}
+
+impl<T, F> IntoIter<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) fn new(tree: Tree<T, F>) -> Self {
+        let node = tree.first.map(|id| (id, 0));
+        Self { tree, node }
+    }
+}
+
+impl<T, F> Iterator for IntoIter<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = (T, Span<F::Index>, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.node.take()?;
+        let links = self.tree.tree.get(id.get())?;
+        let item = (links.data.get(), links.span, depth);
+
+        self.node = if let Some(first) = links.first {
+            Some((first, depth.checked_add(1)?))
+        } else {
+            let mut links = links;
+            let mut depth = depth;
+
+            loop {
+                if let Some(next) = links.next {
+                    break Some((next, depth));
+                }
+
+                let Some(parent) = links.parent else {
+                    break None;
+                };
+
+                links = self.tree.tree.get(parent.get())?;
+                depth = depth.checked_sub(1)?;
+            }
+        };
+
+        Some(item)
+    }
+}
+
+impl<T, F> FusedIterator for IntoIter<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> IntoIterator for Tree<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = (T, Span<F::Index>, isize);
+    type IntoIter = IntoIter<T, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
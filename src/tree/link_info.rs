@@ -0,0 +1,25 @@
+use crate::pointer::PointerUsize;
+
+/// The raw links of a node as returned by [`Tree::links`].
+///
+/// Every link is the identifier of another node in the same tree, which can be
+/// resolved with [`Tree::get`]. This is intended for debugging and for
+/// algorithms which are more naturally expressed over raw links than through
+/// iterators.
+///
+/// [`Tree::links`]: crate::Tree::links
+/// [`Tree::get`]: crate::Tree::get
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LinkInfo<P = PointerUsize> {
+    /// The parent of the node, or `None` if it's a top-level node.
+    pub parent: Option<P>,
+    /// The previous sibling of the node.
+    pub prev: Option<P>,
+    /// The next sibling of the node.
+    pub next: Option<P>,
+    /// The first child of the node.
+    pub first: Option<P>,
+    /// The last child of the node.
+    pub last: Option<P>,
+}
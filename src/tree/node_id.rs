@@ -0,0 +1,227 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+#[cfg(debug_assertions)]
+use core::cell::Cell;
+#[cfg(debug_assertions)]
+use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// The identifier of a node which remembers which tree it was constructed
+/// from.
+///
+/// Plain identifiers as returned by [`Node::id`] are allocated in the order in
+/// which nodes are inserted into a tree, so the same identifier is going to be
+/// present in most trees. Using an identifier from one tree with another is
+/// never unsafe, but it silently refers to an unrelated node.
+///
+/// A `NodeId` is constructed with [`Tree::node_id`] and resolved with
+/// [`Tree::get_by_id`]. In debug builds it also carries a fingerprint of the
+/// tree it was constructed from, and resolving it with a different tree
+/// panics. In release builds it's exactly as large as the underlying pointer.
+///
+/// Trees share a fingerprint with the tree they were cloned from, and trees
+/// which are modified in place through methods such as [`Tree::rebase`] keep
+/// their fingerprint, since identifiers remain valid.
+///
+/// Comparisons and hashing only consider the underlying pointer.
+///
+/// [`Node::id`]: crate::Node::id
+/// [`Tree::node_id`]: crate::Tree::node_id
+/// [`Tree::get_by_id`]: crate::Tree::get_by_id
+/// [`Tree::rebase`]: crate::Tree::rebase
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("lit", 3)
+///     }
+/// };
+///
+/// let lit = tree.first().and_then(|n| n.first()).ok_or("missing lit")?;
+/// let id = tree.node_id(&lit);
+///
+/// assert_eq!(id.pointer(), lit.id());
+///
+/// let lit = tree.get_by_id(id).ok_or("missing lit")?;
+/// assert_eq!(lit.value(), "lit");
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// Using an identifier with the wrong tree panics in debug builds:
+///
+/// ```should_panic
+/// let a = syntree::tree! {
+///     "root" => {
+///         ("lit", 3)
+///     }
+/// };
+///
+/// let b = syntree::tree! {
+///     "root" => {
+///         ("lit", 3)
+///     }
+/// };
+///
+/// let root = a.first().ok_or("missing root")?;
+/// let id = a.node_id(&root);
+///
+/// // In debug builds this panics.
+/// # #[cfg(not(debug_assertions))] panic!();
+/// let _ = b.get_by_id(id);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct NodeId<P> {
+    pointer: P,
+    #[cfg(debug_assertions)]
+    tree: usize,
+}
+
+impl<P> NodeId<P>
+where
+    P: Copy,
+{
+    #[inline]
+    pub(crate) fn new(pointer: P, _fingerprint: &Fingerprint) -> Self {
+        Self {
+            pointer,
+            #[cfg(debug_assertions)]
+            tree: _fingerprint.get(),
+        }
+    }
+
+    /// Get the underlying pointer of the identifier.
+    #[inline]
+    #[must_use]
+    pub const fn pointer(&self) -> P {
+        self.pointer
+    }
+
+    /// Check that this identifier was constructed from the tree with the given
+    /// fingerprint.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn check(&self, _fingerprint: &Fingerprint) {
+        #[cfg(debug_assertions)]
+        {
+            assert_eq!(
+                self.tree,
+                _fingerprint.get(),
+                "node identifier used with a tree it doesn't belong to"
+            );
+        }
+    }
+}
+
+impl<P> Clone for NodeId<P>
+where
+    P: Copy,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for NodeId<P> where P: Copy {}
+
+impl<P> PartialEq for NodeId<P>
+where
+    P: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.pointer == other.pointer
+    }
+}
+
+impl<P> Eq for NodeId<P> where P: Eq {}
+
+impl<P> PartialOrd for NodeId<P>
+where
+    P: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.pointer.partial_cmp(&other.pointer)
+    }
+}
+
+impl<P> Ord for NodeId<P>
+where
+    P: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.pointer.cmp(&other.pointer)
+    }
+}
+
+impl<P> Hash for NodeId<P>
+where
+    P: Hash,
+{
+    #[inline]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.pointer.hash(state);
+    }
+}
+
+impl<P> fmt::Debug for NodeId<P>
+where
+    P: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.pointer.fmt(f)
+    }
+}
+
+/// A fingerprint which identifies a tree in debug builds.
+///
+/// It's allocated lazily the first time it's used, since trees can be
+/// constructed in constant contexts.
+pub(crate) struct Fingerprint {
+    #[cfg(debug_assertions)]
+    id: Cell<usize>,
+}
+
+impl Fingerprint {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            id: Cell::new(0),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn get(&self) -> usize {
+        static NEXT: AtomicUsize = AtomicUsize::new(1);
+
+        let mut id = self.id.get();
+
+        if id == 0 {
+            id = NEXT.fetch_add(1, AtomicOrdering::Relaxed);
+            self.id.set(id);
+        }
+
+        id
+    }
+}
+
+impl Clone for Fingerprint {
+    #[inline]
+    fn clone(&self) -> Self {
+        // Clones must share the fingerprint, so it has to be allocated first.
+        Self {
+            #[cfg(debug_assertions)]
+            id: Cell::new(self.get()),
+        }
+    }
+}
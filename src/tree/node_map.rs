@@ -0,0 +1,196 @@
+#![cfg(feature = "alloc")]
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::slice;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::pointer::Pointer;
+
+/// A map from node identifiers to values, such as the one produced by
+/// [`Tree::attach_trivia`][super::Tree::attach_trivia].
+///
+/// Entries are stored sorted by identifier, so iteration order is
+/// deterministic and lookups take logarithmic time.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Trivia;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("comment", 5),
+///         ("nl", 1),
+///         "item" => {
+///             ("lit", 3)
+///         }
+///     }
+/// };
+///
+/// let map = tree.attach_trivia(|n| match n.value() {
+///     "comment" => Some(Trivia::Comment),
+///     "nl" => Some(Trivia::Newline),
+///     _ => None,
+/// });
+///
+/// let root = tree.first().ok_or("missing root")?;
+/// let comment = root.first().ok_or("missing comment")?;
+/// let item = root.last().ok_or("missing item")?;
+///
+/// assert_eq!(map.len(), 1);
+/// assert_eq!(map.get(item.id()), Some(&vec![comment.id()]));
+/// assert!(!map.contains(root.id()));
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct NodeMap<F, V>
+where
+    F: Flavor,
+{
+    entries: Vec<(F::Pointer, V)>,
+}
+
+impl<F, V> NodeMap<F, V>
+where
+    F: Flavor,
+{
+    /// Construct a map from entries which are sorted by identifier and
+    /// contain no duplicates.
+    pub(crate) fn from_sorted(entries: Vec<(F::Pointer, V)>) -> Self {
+        debug_assert!(entries.windows(2).all(|w| w[0].0.get() < w[1].0.get()));
+        Self { entries }
+    }
+
+    /// Get the value associated with the node identified by `id`.
+    #[must_use]
+    pub fn get(&self, id: F::Pointer) -> Option<&V> {
+        let n = self.search(id).ok()?;
+        Some(&self.entries.get(n)?.1)
+    }
+
+    /// Test if the map has a value for the node identified by `id`.
+    #[must_use]
+    pub fn contains(&self, id: F::Pointer) -> bool {
+        self.search(id).is_ok()
+    }
+
+    /// Get the number of nodes in the map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Test if the map is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the entries of the map ordered by identifier.
+    #[must_use]
+    pub fn iter(&self) -> NodeMapIter<'_, F, V> {
+        NodeMapIter {
+            iter: self.entries.iter(),
+        }
+    }
+
+    fn search(&self, id: F::Pointer) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by_key(&id.get(), |(id, _)| id.get())
+    }
+}
+
+impl<F, V> Clone for NodeMap<F, V>
+where
+    F: Flavor,
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<F, V> fmt::Debug for NodeMap<F, V>
+where
+    F: Flavor,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(id, value)| (id, value)))
+            .finish()
+    }
+}
+
+impl<'a, F, V> IntoIterator for &'a NodeMap<F, V>
+where
+    F: Flavor,
+{
+    type Item = (F::Pointer, &'a V);
+    type IntoIter = NodeMapIter<'a, F, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`NodeMap`].
+///
+/// See [`NodeMap::iter`].
+pub struct NodeMapIter<'a, F, V>
+where
+    F: Flavor,
+{
+    iter: slice::Iter<'a, (F::Pointer, V)>,
+}
+
+impl<'a, F, V> Iterator for NodeMapIter<'a, F, V>
+where
+    F: Flavor,
+{
+    type Item = (F::Pointer, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, value) = self.iter.next()?;
+        Some((*id, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<F, V> DoubleEndedIterator for NodeMapIter<'_, F, V>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (id, value) = self.iter.next_back()?;
+        Some((*id, value))
+    }
+}
+
+impl<F, V> ExactSizeIterator for NodeMapIter<'_, F, V> where F: Flavor {}
+
+impl<F, V> FusedIterator for NodeMapIter<'_, F, V> where F: Flavor {}
+
+impl<F, V> Clone for NodeMapIter<'_, F, V>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
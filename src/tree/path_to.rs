@@ -0,0 +1,97 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::node::Node;
+
+/// An iterator over the nodes containing an offset, from the outermost to the
+/// innermost.
+///
+/// See [`Tree::path_to`][crate::Tree::path_to].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         "call" => {
+///             ("ident", 3),
+///             "args" => {
+///                 ("number", 2),
+///             },
+///         },
+///     }
+/// };
+///
+/// let path = tree.path_to(4).map(|n| n.value()).collect::<Vec<_>>();
+/// assert_eq!(path, ["root", "call", "args", "number"]);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct PathTo<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    siblings: Option<Node<'a, T, F>>,
+    index: F::Index,
+}
+
+impl<'a, T, F> PathTo<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) const fn new(siblings: Option<Node<'a, T, F>>, index: F::Index) -> Self {
+        Self { siblings, index }
+    }
+}
+
+impl<'a, T, F> Iterator for PathTo<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = Node<'a, T, F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.siblings.take()?;
+
+        loop {
+            let span = node.span();
+
+            // Siblings are ordered, so nothing after this node can contain
+            // the index.
+            if span.start > self.index {
+                return None;
+            }
+
+            if span.contains(&self.index) {
+                self.siblings = node.first();
+                return Some(node);
+            }
+
+            node = node.next()?;
+        }
+    }
+}
+
+impl<T, F> FusedIterator for PathTo<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for PathTo<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            siblings: self.siblings,
+            index: self.index,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "alloc")]
+
+use core::fmt;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+
+/// Reusable scratch buffers for tree queries which would otherwise allocate.
+///
+/// A context is passed to the `*_with_ctx` variants of queries such as
+/// [`Tree::walk_sorted_by_span_with_ctx`][super::Tree::walk_sorted_by_span_with_ctx].
+/// The buffers it owns are retained between queries, so repeatedly querying
+/// trees with the same context only allocates once the buffers need to grow.
+///
+/// Note that walks, [`Tree::path_to`][super::Tree::path_to] and span queries
+/// such as [`Tree::node_with_range`][super::Tree::node_with_range] navigate
+/// the links stored in the tree and never allocate, so they don't have
+/// variants which take a context.
+///
+/// # Examples
+///
+/// ```
+/// use syntree::QueryCtx;
+///
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("lit", 3),
+///         ("ws", 1),
+///     }
+/// };
+///
+/// let mut ctx = QueryCtx::new();
+///
+/// for _ in 0..2 {
+///     let values = tree.walk_sorted_by_span_with_ctx(&mut ctx).map(|n| n.value());
+///     assert!(values.eq(["root", "lit", "ws"]));
+/// }
+/// ```
+pub struct QueryCtx<F>
+where
+    F: Flavor,
+{
+    /// Buffer used when sorting nodes by span.
+    pub(super) sorted: Vec<F::Pointer>,
+}
+
+impl<F> QueryCtx<F>
+where
+    F: Flavor,
+{
+    /// Construct a new context with empty buffers.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { sorted: Vec::new() }
+    }
+}
+
+impl<F> Default for QueryCtx<F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> fmt::Debug for QueryCtx<F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryCtx").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,468 @@
+use core::cell::Cell;
+use core::fmt;
+
+use crate::error::{BuildResult, Error};
+use crate::flags::Flags;
+use crate::flavor::{Flavor, FlavorDefault};
+use crate::index::Indexes;
+use crate::kind::Kind;
+use crate::links::Links;
+use crate::pointer::Pointer;
+use crate::span::Span;
+
+use super::Tree;
+
+/// A raw node record used with [`TreeRaw`].
+///
+/// Every link refers to another node by the identifier which was returned by
+/// [`TreeRaw::push`] when that node was added.
+pub struct RawNode<T, F>
+where
+    F: Flavor,
+{
+    /// The value of the node.
+    pub value: T,
+    /// Whether the node is a token or a node.
+    pub kind: Kind,
+    /// The span of the node.
+    pub span: Span<F::Index>,
+    /// The parent of the node, or `None` if it's a top-level node.
+    pub parent: Option<F::Pointer>,
+    /// The previous sibling of the node.
+    pub prev: Option<F::Pointer>,
+    /// The next sibling of the node.
+    pub next: Option<F::Pointer>,
+    /// The first child of the node.
+    pub first: Option<F::Pointer>,
+    /// The last child of the node.
+    pub last: Option<F::Pointer>,
+}
+
+/// Construct a [Tree] directly from raw node records.
+///
+/// This is intended for deserializers and conversions from other tree formats,
+/// which already know the full structure of the tree and shouldn't be forced
+/// to replay it through a [Builder][crate::Builder] one node at a time.
+///
+/// Nodes are identified by the order in which they are pushed and may refer to
+/// nodes which have not been pushed yet. The structure is only checked once
+/// [`TreeRaw::build`] is called, at which point the tree is validated using
+/// [`Tree::validate`] and its token index is constructed, unless it was
+/// provided through [`TreeRaw::push_index`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::pointer::PointerUsize;
+/// use syntree::{Kind, RawNode, Span, TreeRaw};
+///
+/// let p = |n| PointerUsize::new(n);
+///
+/// let mut raw = TreeRaw::new();
+///
+/// raw.push(RawNode {
+///     value: "root",
+///     kind: Kind::Node,
+///     span: Span::new(0, 4),
+///     parent: None,
+///     prev: None,
+///     next: None,
+///     first: p(1),
+///     last: p(2),
+/// })?;
+///
+/// raw.push(RawNode {
+///     value: "lit",
+///     kind: Kind::Token,
+///     span: Span::new(0, 3),
+///     parent: p(0),
+///     prev: None,
+///     next: p(2),
+///     first: None,
+///     last: None,
+/// })?;
+///
+/// raw.push(RawNode {
+///     value: "ws",
+///     kind: Kind::Token,
+///     span: Span::new(3, 4),
+///     parent: p(0),
+///     prev: p(1),
+///     next: None,
+///     first: None,
+///     last: None,
+/// })?;
+///
+/// let tree = raw.build()?;
+///
+/// let expected = syntree::tree! {
+///     "root" => {
+///         ("lit", 3),
+///         ("ws", 1),
+///     }
+/// };
+///
+/// assert_eq!(tree, expected);
+/// assert_eq!(tree.token_offsets().map(|(o, _)| o).collect::<Vec<_>>(), [3, 4]);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+///
+/// Inconsistent records are rejected when building:
+///
+/// ```
+/// use syntree::pointer::PointerUsize;
+/// use syntree::{Error, Kind, RawNode, Span, TreeRaw, ValidationError};
+///
+/// let mut raw = TreeRaw::new();
+///
+/// raw.push(RawNode {
+///     value: "root",
+///     kind: Kind::Node,
+///     span: Span::new(0, 4),
+///     parent: None,
+///     prev: None,
+///     next: None,
+///     first: PointerUsize::new(1),
+///     last: PointerUsize::new(1),
+/// })?;
+///
+/// let lit = raw.push(RawNode {
+///     value: "lit",
+///     kind: Kind::Token,
+///     span: Span::new(0, 3),
+///     // This should be the root.
+///     parent: PointerUsize::new(1),
+///     prev: None,
+///     next: None,
+///     first: None,
+///     last: None,
+/// })?;
+///
+/// assert_eq!(
+///     raw.build(),
+///     Err(Error::Invalid(ValidationError::ParentMismatch(lit)))
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct TreeRaw<T, F = FlavorDefault>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: Tree<T, F>,
+}
+
+impl<T> TreeRaw<T, FlavorDefault>
+where
+    T: Copy,
+{
+    /// Construct a new empty raw tree using the default flavor.
+    ///
+    /// For a constructor that can use custom flavors, use [TreeRaw::new_with].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::new_with()
+    }
+}
+
+impl<T, F> TreeRaw<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    /// Construct a new empty raw tree with a custom flavor.
+    #[must_use]
+    pub const fn new_with() -> Self {
+        Self {
+            tree: Tree::new_with(),
+        }
+    }
+
+    /// Configure whether empty tokens should be included in the token index
+    /// of the built tree.
+    ///
+    /// See [`Builder::set_index_empty_tokens`][crate::Builder::set_index_empty_tokens].
+    pub fn set_index_empty_tokens(&mut self, enabled: bool) {
+        self.tree.set_index_empty(enabled);
+    }
+
+    /// Set the identifier of the source the built tree corresponds to, such
+    /// as one which was stored alongside a serialized tree.
+    ///
+    /// See [`Builder::set_source_id`][crate::Builder::set_source_id].
+    pub fn set_source_id(&mut self, id: u64) {
+        self.tree.set_source_id(Some(id));
+    }
+
+    /// Set the flags in `flag` on the raw node identified by `id`, in addition
+    /// to any flags which are already set on it.
+    ///
+    /// See [`Builder::set_flag`][crate::Builder::set_flag].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::MissingNode`] if no node with the identifier `id`
+    /// has been pushed.
+    pub fn set_flag(&mut self, id: F::Pointer, flag: F::Flags) -> BuildResult<(), F> {
+        let links = self.tree.get_mut(id).ok_or(Error::MissingNode(id))?;
+        links.flags = links.flags.union(flag);
+        Ok(())
+    }
+
+    /// Push an entry to the token index of the tree, as previously read from
+    /// [`Tree::token_offsets`].
+    ///
+    /// If any entries are pushed, they are used as the token index of the
+    /// built tree instead of constructing it from the tokens of the tree. The
+    /// entries are checked against the tree by [`TreeRaw::build`], which visits
+    /// every token. Use [`TreeRaw::build_unchecked`] to skip checking them.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Flavor`] if the token index could not be grown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::PointerUsize;
+    /// use syntree::{RawNode, TreeRaw};
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3),
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let mut raw = TreeRaw::new();
+    ///
+    /// for node in (0..tree.len()).filter_map(|n| tree.get(PointerUsize::new(n)?)) {
+    ///     raw.push(RawNode {
+    ///         value: node.value(),
+    ///         kind: node.kind(),
+    ///         span: *node.span(),
+    ///         parent: node.parent().map(|n| n.id()),
+    ///         prev: node.prev().map(|n| n.id()),
+    ///         next: node.next().map(|n| n.id()),
+    ///         first: node.first().map(|n| n.id()),
+    ///         last: node.last().map(|n| n.id()),
+    ///     })?;
+    /// }
+    ///
+    /// for (offset, id) in tree.token_offsets() {
+    ///     raw.push_index(offset, id)?;
+    /// }
+    ///
+    /// let copy = raw.build()?;
+    /// assert_eq!(copy, tree);
+    /// assert!(copy.token_offsets().eq(tree.token_offsets()));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    ///
+    /// Entries which don't match the tree are rejected when building:
+    ///
+    /// ```
+    /// use syntree::pointer::PointerUsize;
+    /// use syntree::{Error, Kind, RawNode, Span, TreeRaw, ValidationError};
+    ///
+    /// let mut raw = TreeRaw::new();
+    ///
+    /// let lit = raw.push(RawNode {
+    ///     value: "lit",
+    ///     kind: Kind::Token,
+    ///     span: Span::new(0, 3),
+    ///     parent: None,
+    ///     prev: None,
+    ///     next: None,
+    ///     first: None,
+    ///     last: None,
+    /// })?;
+    ///
+    /// raw.push_index(4, lit)?;
+    ///
+    /// assert_eq!(
+    ///     raw.build(),
+    ///     Err(Error::Invalid(ValidationError::IndexMismatch(0)))
+    /// );
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn push_index(&mut self, index: F::Index, id: F::Pointer) -> BuildResult<(), F> {
+        self.tree.indexes_mut().push(index, id)?;
+        Ok(())
+    }
+
+    /// Push a raw node and return its identifier.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Overflow`] in case we run out of node identifiers.
+    pub fn push(&mut self, node: RawNode<T, F>) -> BuildResult<F::Pointer, F> {
+        let id = F::Pointer::new(self.tree.len()).ok_or(Error::Overflow)?;
+
+        if node.parent.is_none() {
+            if node.prev.is_none() {
+                self.tree.first = Some(id);
+            }
+
+            if node.next.is_none() {
+                self.tree.last = Some(id);
+            }
+
+            self.tree.span.end = self.tree.span.end.max(node.span.end);
+        }
+
+        self.tree.push(Links {
+            data: Cell::new(node.value),
+            kind: node.kind,
+            span: node.span,
+            parent: node.parent,
+            prev: node.prev,
+            next: node.next,
+            first: node.first,
+            last: node.last,
+            flags: <F::Flags as Flags>::EMPTY,
+        })?;
+
+        Ok(id)
+    }
+
+    /// Validate the raw nodes and construct a [Tree] from them.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Invalid`] if the nodes do not form a valid tree
+    /// or if the entries pushed with [`TreeRaw::push_index`] don't match it,
+    /// or with [`Error::Flavor`] if the token index could not be grown.
+    pub fn build(self) -> BuildResult<Tree<T, F>, F> {
+        // The index is only checked if entries were provided.
+        self.tree.validate().map_err(Error::Invalid)?;
+        self.build_unchecked()
+    }
+
+    /// Construct a [Tree] from the raw nodes without validating them.
+    ///
+    /// This is intended for trees which are already known to be valid, such
+    /// as ones read back from storage which was written by the same program.
+    /// Unlike [`TreeRaw::build`], this doesn't visit every node, so providing
+    /// the token index through [`TreeRaw::push_index`] avoids the cost of
+    /// constructing it. Otherwise the token index is constructed from the
+    /// tokens of the tree.
+    ///
+    /// If the nodes don't form a valid tree or the token index doesn't match
+    /// it, the behavior of the built tree is unspecified. This can't cause
+    /// memory unsafety, but queries might return incorrect results, panic or
+    /// never finish.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`Error::Flavor`] if the token index has to be
+    /// constructed and could not be grown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::pointer::PointerUsize;
+    /// use syntree::{RawNode, TreeRaw};
+    ///
+    /// let tree = syntree::tree! {
+    ///     "root" => {
+    ///         ("lit", 3),
+    ///         ("ws", 1),
+    ///     }
+    /// };
+    ///
+    /// let mut raw = TreeRaw::new();
+    ///
+    /// for node in (0..tree.len()).filter_map(|n| tree.get(PointerUsize::new(n)?)) {
+    ///     raw.push(RawNode {
+    ///         value: node.value(),
+    ///         kind: node.kind(),
+    ///         span: *node.span(),
+    ///         parent: node.parent().map(|n| n.id()),
+    ///         prev: node.prev().map(|n| n.id()),
+    ///         next: node.next().map(|n| n.id()),
+    ///         first: node.first().map(|n| n.id()),
+    ///         last: node.last().map(|n| n.id()),
+    ///     })?;
+    /// }
+    ///
+    /// for (offset, id) in tree.token_offsets() {
+    ///     raw.push_index(offset, id)?;
+    /// }
+    ///
+    /// let copy = raw.build_unchecked()?;
+    /// assert_eq!(copy, tree);
+    /// assert_eq!(copy.token_at(3).map(|n| n.value()), Some("ws"));
+    /// # Ok::<_, Box<dyn core::error::Error>>(())
+    /// ```
+    pub fn build_unchecked(self) -> BuildResult<Tree<T, F>, F> {
+        let mut tree = self.tree;
+
+        if tree.indexes.is_empty() {
+            tree.reindex()?;
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<T, F> Default for TreeRaw<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new_with()
+    }
+}
+
+impl<T, F> fmt::Debug for TreeRaw<T, F>
+where
+    T: Copy + fmt::Debug,
+    F: Flavor<Index: fmt::Debug>,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeRaw")
+            .field("len", &self.tree.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Clone for RawNode<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for RawNode<T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> fmt::Debug for RawNode<T, F>
+where
+    T: fmt::Debug,
+    F: Flavor<Index: fmt::Debug>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawNode")
+            .field("value", &self.value)
+            .field("kind", &self.kind)
+            .field("span", &self.span)
+            .field("parent", &self.parent)
+            .field("prev", &self.prev)
+            .field("next", &self.next)
+            .field("first", &self.first)
+            .field("last", &self.last)
+            .finish()
+    }
+}
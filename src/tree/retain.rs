@@ -0,0 +1,100 @@
+#![cfg(feature = "alloc")]
+
+use core::cell::Cell;
+
+use alloc::vec::Vec;
+
+use crate::error::{BuildResult, Error};
+use crate::flavor::Flavor;
+use crate::index::Indexes;
+use crate::kind::Kind;
+use crate::links::Links;
+use crate::node::Event;
+use crate::pointer::Pointer;
+
+use super::Tree;
+
+pub(super) fn retain<T, F, P>(tree: &Tree<T, F>, mut predicate: P) -> BuildResult<Tree<T, F>, F>
+where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(T) -> bool,
+{
+    let mut output = Tree::<T, F>::with_capacity(0, 0)?;
+    output.span = tree.span;
+    output.index_empty = tree.index_empty;
+    output.source_id = tree.source_id;
+
+    // Whether each node with children which is currently being walked was
+    // retained.
+    let mut walked = Vec::new();
+    // The retained ancestors of the current node in the new tree.
+    let mut parents = Vec::<F::Pointer>::new();
+
+    for (event, node) in tree.walk_events() {
+        if let Event::Up = event {
+            if walked.pop() == Some(true) {
+                parents.pop();
+            }
+
+            continue;
+        }
+
+        let kept = predicate(node.value());
+
+        if node.has_children() {
+            walked.push(kept);
+        }
+
+        if !kept {
+            continue;
+        }
+
+        let id = F::Pointer::new(output.tree.len()).ok_or(Error::Overflow)?;
+        let parent = parents.last().copied();
+
+        let prev = match parent {
+            Some(parent) => output.tree.get_mut(parent.get()).and_then(|links| {
+                links.first.get_or_insert(id);
+                links.last.replace(id)
+            }),
+            None => {
+                output.first.get_or_insert(id);
+                output.last.replace(id)
+            }
+        };
+
+        if let Some(prev) = prev.and_then(|prev| output.tree.get_mut(prev.get())) {
+            prev.next = Some(id);
+        }
+
+        let span = *node.span();
+
+        output.push(Links {
+            data: Cell::new(node.value()),
+            kind: node.kind(),
+            span,
+            parent,
+            prev,
+            next: None,
+            first: None,
+            last: None,
+            flags: node.flags(),
+        })?;
+
+        match node.kind() {
+            Kind::Token => {
+                if !span.is_empty() || output.index_empty {
+                    output.indexes.push(span.end, id)?;
+                }
+            }
+            Kind::Node => {
+                if node.has_children() {
+                    parents.push(id);
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "alloc")]
+
+use core::iter::FusedIterator;
+use core::slice;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::node::Node;
+
+use super::{QueryCtx, Tree};
+
+/// An iterator over every node in a tree, ordered by the start of their spans.
+///
+/// Nodes which start at the same position are visited in the same order as
+/// [`Tree::walk`], so a node is always visited before any of its descendants
+/// which start where it does.
+///
+/// See [`Tree::walk_sorted_by_span`].
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Span;
+///
+/// let mut tree = syntree::Builder::new();
+///
+/// tree.open("root")?;
+/// tree.token_with("late", Span::new(4, 6))?;
+/// tree.token_with("early", Span::new(0, 2))?;
+/// tree.close()?;
+///
+/// let tree = tree.build()?;
+///
+/// assert_eq!(
+///     tree.walk().map(|n| n.value()).collect::<Vec<_>>(),
+///     ["root", "late", "early"],
+/// );
+///
+/// assert_eq!(
+///     tree.walk_sorted_by_span().map(|n| n.value()).collect::<Vec<_>>(),
+///     ["root", "early", "late"],
+/// );
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct SortedBySpan<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree: &'a Tree<T, F>,
+    ids: Ids<'a, F::Pointer>,
+}
+
+/// The identifiers being iterated over, which are either owned by the
+/// iterator or borrowed from a [`QueryCtx`].
+enum Ids<'a, P> {
+    Owned(vec::IntoIter<P>),
+    Borrowed(slice::Iter<'a, P>),
+}
+
+impl<'a, T, F> SortedBySpan<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    pub(super) fn new(tree: &'a Tree<T, F>) -> Self {
+        let mut ids = Vec::with_capacity(tree.len());
+        sort(tree, &mut ids);
+
+        Self {
+            tree,
+            ids: Ids::Owned(ids.into_iter()),
+        }
+    }
+
+    pub(super) fn with_ctx(tree: &'a Tree<T, F>, ctx: &'a mut QueryCtx<F>) -> Self {
+        ctx.sorted.clear();
+        sort(tree, &mut ctx.sorted);
+
+        Self {
+            tree,
+            ids: Ids::Borrowed(ctx.sorted.iter()),
+        }
+    }
+}
+
+/// Collect the identifiers of every node in `tree` ordered by the start of
+/// their spans.
+fn sort<T, F>(tree: &Tree<T, F>, ids: &mut Vec<F::Pointer>)
+where
+    T: Copy,
+    F: Flavor,
+{
+    ids.extend(tree.walk().map(|n| n.id()));
+    // NB: The sort is stable, which preserves the walk order of nodes
+    // starting at the same position.
+    ids.sort_by_key(|&id| tree.span_of(id).map(|span| span.start));
+}
+
+impl<'a, T, F> Iterator for SortedBySpan<'a, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    type Item = Node<'a, T, F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = match &mut self.ids {
+            Ids::Owned(iter) => iter.next()?,
+            Ids::Borrowed(iter) => *iter.next()?,
+        };
+
+        self.tree.get(id)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T, F> DoubleEndedIterator for SortedBySpan<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let id = match &mut self.ids {
+            Ids::Owned(iter) => iter.next_back()?,
+            Ids::Borrowed(iter) => *iter.next_back()?,
+        };
+
+        self.tree.get(id)
+    }
+}
+
+impl<T, F> ExactSizeIterator for SortedBySpan<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        match &self.ids {
+            Ids::Owned(iter) => iter.len(),
+            Ids::Borrowed(iter) => iter.len(),
+        }
+    }
+}
+
+impl<T, F> FusedIterator for SortedBySpan<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+}
+
+impl<T, F> Clone for SortedBySpan<'_, T, F>
+where
+    T: Copy,
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        let ids = match &self.ids {
+            Ids::Owned(iter) => Ids::Owned(iter.clone()),
+            Ids::Borrowed(iter) => Ids::Borrowed(iter.clone()),
+        };
+
+        Self {
+            tree: self.tree,
+            ids,
+        }
+    }
+}
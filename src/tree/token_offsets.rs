@@ -0,0 +1,132 @@
+use core::iter::FusedIterator;
+
+use crate::flavor::Flavor;
+use crate::index::Indexes;
+
+/// An iterator over the offsets of tokens in a tree.
+///
+/// See [`Tree::token_offsets`][crate::Tree::token_offsets].
+///
+/// # Examples
+///
+/// ```
+/// let tree = syntree::tree! {
+///     "root" => {
+///         ("lit", 3),
+///         ("ws", 1),
+///         "empty",
+///         ("lit", 2),
+///     }
+/// };
+///
+/// let offsets = tree.token_offsets().map(|(o, _)| o).collect::<Vec<_>>();
+/// assert_eq!(offsets, [3, 4, 6]);
+///
+/// let (_, id) = tree.token_offsets().last().ok_or("missing last token")?;
+/// let node = tree.get(id).ok_or("missing node")?;
+/// assert_eq!(node.value(), "lit");
+/// assert_eq!(node.range(), 4..6);
+/// # Ok::<_, Box<dyn core::error::Error>>(())
+/// ```
+pub struct TokenOffsets<'a, F>
+where
+    F: Flavor,
+{
+    indexes: Option<&'a F::Indexes>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, F> TokenOffsets<'a, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    pub(crate) fn new(indexes: &'a F::Indexes) -> Self {
+        Self {
+            indexes: Some(indexes),
+            start: 0,
+            end: indexes.len(),
+        }
+    }
+}
+
+impl<F> Iterator for TokenOffsets<'_, F>
+where
+    F: Flavor,
+{
+    type Item = (F::Index, F::Pointer);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+
+        let item = self.indexes?.get(self.start)?;
+        self.start += 1;
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<F> DoubleEndedIterator for TokenOffsets<'_, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+
+        let item = self.indexes?.get(self.end - 1)?;
+        self.end -= 1;
+        Some(item)
+    }
+}
+
+impl<F> ExactSizeIterator for TokenOffsets<'_, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl<F> FusedIterator for TokenOffsets<'_, F> where F: Flavor {}
+
+impl<F> Clone for TokenOffsets<'_, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            indexes: self.indexes,
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<F> Default for TokenOffsets<'_, F>
+where
+    F: Flavor,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            indexes: None,
+            start: 0,
+            end: 0,
+        }
+    }
+}
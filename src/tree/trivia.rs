@@ -0,0 +1,112 @@
+#![cfg(feature = "alloc")]
+
+use core::mem;
+
+use alloc::vec::Vec;
+
+use crate::flavor::Flavor;
+use crate::node::{Children, Node};
+use crate::pointer::Pointer;
+
+use super::{NodeMap, Tree};
+
+/// The classification of a trivia element as used by
+/// [`Tree::attach_trivia`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Trivia {
+    /// A comment which should be attached to a neighboring node.
+    Comment,
+    /// Whitespace which doesn't contain a line break.
+    Whitespace,
+    /// Trivia which contains one or more line breaks.
+    Newline,
+}
+
+pub(super) fn attach<'a, T, F, P>(tree: &'a Tree<T, F>, mut pred: P) -> NodeMap<F, Vec<F::Pointer>>
+where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(Node<'a, T, F>) -> Option<Trivia>,
+{
+    let mut entries = Vec::new();
+
+    attach_siblings(tree.children(), None, &mut pred, &mut entries);
+
+    for node in tree.walk() {
+        if !node.is_token() {
+            attach_siblings(node.children(), Some(node), &mut pred, &mut entries);
+        }
+    }
+
+    // Nodes can have comments attached from different lists of siblings, such
+    // as a trailing comment following a node whose last child is a comment.
+    // So merge entries for the same node and put its comments in source order.
+    entries.sort_by_key(|(id, _): &(F::Pointer, Vec<F::Pointer>)| id.get());
+
+    let mut merged = Vec::<(F::Pointer, Vec<F::Pointer>)>::with_capacity(entries.len());
+
+    for (id, comments) in entries {
+        match merged.last_mut() {
+            Some((last, existing)) if *last == id => {
+                existing.extend(comments);
+                existing.sort_by_key(|&id| tree.get(id).map(|n| n.span().start));
+            }
+            _ => merged.push((id, comments)),
+        }
+    }
+
+    NodeMap::from_sorted(merged)
+}
+
+/// Attach the comments among a list of siblings.
+fn attach_siblings<'a, T, F, P>(
+    children: Children<'a, T, F>,
+    parent: Option<Node<'a, T, F>>,
+    pred: &mut P,
+    entries: &mut Vec<(F::Pointer, Vec<F::Pointer>)>,
+) where
+    T: Copy,
+    F: Flavor,
+    P: FnMut(Node<'a, T, F>) -> Option<Trivia>,
+{
+    // The last sibling which isn't trivia, and whether we're still on the same
+    // line as it.
+    let mut prev = None;
+    let mut same_line = false;
+    // Comments waiting to be attached to the next sibling which isn't trivia.
+    let mut pending = Vec::new();
+
+    for node in children {
+        match pred(node) {
+            None => {
+                if !pending.is_empty() {
+                    entries.push((node.id(), mem::take(&mut pending)));
+                }
+
+                prev = Some(node.id());
+                same_line = true;
+            }
+            Some(Trivia::Whitespace) => {}
+            Some(Trivia::Newline) => {
+                same_line = false;
+            }
+            Some(Trivia::Comment) => match prev {
+                Some(prev) if same_line => {
+                    entries.push((prev, alloc::vec![node.id()]));
+                }
+                _ => {
+                    pending.push(node.id());
+                }
+            },
+        }
+    }
+
+    if pending.is_empty() {
+        return;
+    }
+
+    if let Some(id) = prev.or(parent.map(|n| n.id())) {
+        entries.push((id, pending));
+    }
+}
@@ -0,0 +1,396 @@
+use core::fmt;
+
+use crate::flavor::Flavor;
+use crate::index::{Index, Indexes};
+use crate::kind::Kind;
+use crate::links::LinksOf;
+use crate::pointer::{Pointer, PointerUsize};
+use crate::span::Span;
+
+use super::Tree;
+
+/// An inconsistency detected by [`Tree::validate`].
+///
+/// The `P` parameter is the [Pointer] type of the tree being validated, which
+/// is used to identify the node where the inconsistency was detected.
+///
+/// [Pointer]: crate::pointer::Pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError<P = PointerUsize> {
+    /// A link points to a node which is not in the tree.
+    MissingNode(P),
+    /// The parent link of the given node does not point to the node it is a
+    /// child of.
+    ParentMismatch(P),
+    /// The previous sibling link of the given node does not point to the
+    /// sibling which precedes it.
+    PrevMismatch(P),
+    /// The last child link of the given node does not point to its last child.
+    ///
+    /// If this is `None`, the last node of the tree itself does not match the
+    /// last top-level node.
+    LastMismatch(Option<P>),
+    /// The given token has children.
+    TokenWithChildren(P),
+    /// The span of the given node ends before it starts.
+    InvalidSpan(P),
+    /// The span of the given node is not contained within the span of its
+    /// parent.
+    SpanOutsideParent(P),
+    /// The span of the given node starts before the preceding sibling or token
+    /// ends.
+    ///
+    /// For trees built with
+    /// [`Builder::set_explicit_spans`][crate::Builder::set_explicit_spans],
+    /// the span of the given node instead overlaps with the preceding sibling,
+    /// or the span of the given token overlaps with the token preceding it in
+    /// the token index.
+    SpanOverlap(P),
+    /// The entry at the given position in the token index does not correspond
+    /// to the token which it should refer to.
+    IndexMismatch(usize),
+    /// Following the links of the tree visits more nodes than are stored in it.
+    Cycle,
+    /// Some nodes stored in the tree can't be reached by following its links.
+    Unreachable,
+}
+
+impl<P> core::error::Error for ValidationError<P> where P: fmt::Debug {}
+
+impl<P> fmt::Display for ValidationError<P>
+where
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingNode(p) => {
+                write!(f, "missing node with id `{p:?}`")
+            }
+            ValidationError::ParentMismatch(p) => {
+                write!(f, "parent link of node `{p:?}` does not match its parent")
+            }
+            ValidationError::PrevMismatch(p) => {
+                write!(
+                    f,
+                    "previous link of node `{p:?}` does not match its previous sibling"
+                )
+            }
+            ValidationError::LastMismatch(Some(p)) => {
+                write!(f, "last link of node `{p:?}` does not match its last child")
+            }
+            ValidationError::LastMismatch(None) => {
+                write!(f, "last link of tree does not match its last node")
+            }
+            ValidationError::TokenWithChildren(p) => {
+                write!(f, "token `{p:?}` has children")
+            }
+            ValidationError::InvalidSpan(p) => {
+                write!(f, "span of node `{p:?}` ends before it starts")
+            }
+            ValidationError::SpanOutsideParent(p) => {
+                write!(f, "span of node `{p:?}` is not contained in its parent")
+            }
+            ValidationError::SpanOverlap(p) => {
+                write!(f, "span of node `{p:?}` overlaps with preceding nodes")
+            }
+            ValidationError::IndexMismatch(n) => {
+                write!(f, "token index entry {n} does not match the tree")
+            }
+            ValidationError::Cycle => {
+                write!(f, "links of the tree form a cycle")
+            }
+            ValidationError::Unreachable => {
+                write!(f, "tree contains unreachable nodes")
+            }
+        }
+    }
+}
+
+/// A mismatch between a tree and its source detected by
+/// [`Tree::verify_source`].
+///
+/// The `P` parameter is the [Pointer] type of the tree being verified, which
+/// is used to identify the token where the mismatch was detected.
+///
+/// [Pointer]: crate::pointer::Pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SourceMismatch<P = PointerUsize> {
+    /// The span of the tree does not cover the whole source.
+    Length {
+        /// The end of the span of the tree.
+        tree: usize,
+        /// The length of the source.
+        source: usize,
+    },
+    /// The span of the given token extends past the end of the source.
+    OutOfBounds(P),
+    /// The span of the given token does not start or end on a character
+    /// boundary in the source.
+    NotCharBoundary(P),
+}
+
+impl<P> core::error::Error for SourceMismatch<P> where P: fmt::Debug {}
+
+impl<P> fmt::Display for SourceMismatch<P>
+where
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceMismatch::Length { tree, source } => {
+                write!(
+                    f,
+                    "tree spans {tree} bytes but the source is {source} bytes long"
+                )
+            }
+            SourceMismatch::OutOfBounds(p) => {
+                write!(f, "span of token `{p:?}` is outside of the source")
+            }
+            SourceMismatch::NotCharBoundary(p) => {
+                write!(
+                    f,
+                    "span of token `{p:?}` is not on a character boundary in the source"
+                )
+            }
+        }
+    }
+}
+
+pub(super) fn verify_source<T, F>(
+    tree: &Tree<T, F>,
+    source: &str,
+) -> Result<(), SourceMismatch<F::Pointer>>
+where
+    T: Copy,
+    F: Flavor,
+{
+    let end = tree.span().end.as_usize();
+
+    if end != source.len() {
+        return Err(SourceMismatch::Length {
+            tree: end,
+            source: source.len(),
+        });
+    }
+
+    for node in tree.walk() {
+        if !node.is_token() {
+            continue;
+        }
+
+        let range = node.range();
+
+        if range.end > source.len() {
+            return Err(SourceMismatch::OutOfBounds(node.id()));
+        }
+
+        if !source.is_char_boundary(range.start) || !source.is_char_boundary(range.end) {
+            return Err(SourceMismatch::NotCharBoundary(node.id()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the tree, where `spans` indicates whether spans and the token
+/// index should be checked in addition to the links of the tree.
+pub(super) fn validate<T, F>(
+    tree: &Tree<T, F>,
+    spans: bool,
+) -> Result<(), ValidationError<F::Pointer>>
+where
+    T: Copy,
+    F: Flavor,
+{
+    // Flavors which do not maintain an index, such as those using `EmptyVec`,
+    // are not checked against it.
+    let check_index = spans && !tree.indexes.is_empty();
+
+    let mut visited = 0usize;
+    let mut tokens = 0usize;
+    let mut last_token_end = None;
+
+    let mut parent = None::<F::Pointer>;
+    let mut prev = None::<F::Pointer>;
+    let mut current = tree.first;
+
+    loop {
+        let Some(id) = current else {
+            let last = match parent {
+                Some(p) => get(tree, p)?.last,
+                None => tree.last,
+            };
+
+            if last != prev {
+                return Err(ValidationError::LastMismatch(parent));
+            }
+
+            let Some(p) = parent else {
+                break;
+            };
+
+            let links = get(tree, p)?;
+            prev = Some(p);
+            parent = links.parent;
+            current = links.next;
+            continue;
+        };
+
+        visited += 1;
+
+        if visited > tree.tree.len() {
+            return Err(ValidationError::Cycle);
+        }
+
+        let links = get(tree, id)?;
+
+        if links.parent != parent {
+            return Err(ValidationError::ParentMismatch(id));
+        }
+
+        if links.prev != prev {
+            return Err(ValidationError::PrevMismatch(id));
+        }
+
+        let span = links.span;
+
+        if spans {
+            if span.start > span.end {
+                return Err(ValidationError::InvalidSpan(id));
+            }
+
+            if let Some(p) = parent {
+                if !contains(&get(tree, p)?.span, &span) {
+                    return Err(ValidationError::SpanOutsideParent(id));
+                }
+            }
+
+            if let Some(p) = prev {
+                let prev = &get(tree, p)?.span;
+
+                let overlaps = if tree.explicit_spans {
+                    overlaps(prev, &span)
+                } else {
+                    prev.end > span.start
+                };
+
+                if overlaps {
+                    return Err(ValidationError::SpanOverlap(id));
+                }
+            }
+        }
+
+        if links.kind == Kind::Token {
+            if links.first.is_some() || links.last.is_some() {
+                return Err(ValidationError::TokenWithChildren(id));
+            }
+
+            // Tokens with explicit spans might be out of order, in which case
+            // overlapping tokens are found through the token index instead.
+            if spans && !tree.explicit_spans && last_token_end.is_some_and(|end| end > span.start) {
+                return Err(ValidationError::SpanOverlap(id));
+            }
+
+            last_token_end = Some(span.end);
+
+            if !span.is_empty() || tree.index_empty {
+                tokens += 1;
+            }
+        }
+
+        if let Some(first) = links.first {
+            parent = Some(id);
+            prev = None;
+            current = Some(first);
+        } else {
+            if links.last.is_some() {
+                return Err(ValidationError::LastMismatch(Some(id)));
+            }
+
+            prev = Some(id);
+            current = links.next;
+        }
+    }
+
+    if visited != tree.tree.len() {
+        return Err(ValidationError::Unreachable);
+    }
+
+    if check_index {
+        validate_index(tree, tokens)?;
+    }
+
+    Ok(())
+}
+
+/// Validate that the token index refers to `tokens` tokens in order of their
+/// spans, which must not overlap.
+fn validate_index<T, F>(tree: &Tree<T, F>, tokens: usize) -> Result<(), ValidationError<F::Pointer>>
+where
+    T: Copy,
+    F: Flavor,
+{
+    let len = tree.indexes.len();
+    let mut last_end = None;
+
+    for n in 0..len {
+        let Some((offset, id)) = tree.indexes.get(n) else {
+            return Err(ValidationError::IndexMismatch(n));
+        };
+
+        let span = match tree.tree.get(id.get()) {
+            Some(links) if links.kind == Kind::Token && links.span.end == offset => links.span,
+            _ => return Err(ValidationError::IndexMismatch(n)),
+        };
+
+        if let Some(end) = last_end {
+            if offset < end {
+                return Err(ValidationError::IndexMismatch(n));
+            }
+
+            if span.start < end {
+                return Err(ValidationError::SpanOverlap(id));
+            }
+        }
+
+        last_end = Some(offset);
+    }
+
+    if len != tokens {
+        return Err(ValidationError::IndexMismatch(len.min(tokens)));
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn get<T, F>(
+    tree: &Tree<T, F>,
+    id: F::Pointer,
+) -> Result<&LinksOf<T, F>, ValidationError<F::Pointer>>
+where
+    T: Copy,
+    F: Flavor,
+{
+    tree.tree
+        .get(id.get())
+        .ok_or(ValidationError::MissingNode(id))
+}
+
+#[inline]
+fn contains<I>(outer: &Span<I>, inner: &Span<I>) -> bool
+where
+    I: Ord,
+{
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+#[inline]
+fn overlaps<I>(a: &Span<I>, b: &Span<I>) -> bool
+where
+    I: Ord,
+{
+    a.start < b.end && b.start < a.end
+}
@@ -0,0 +1,130 @@
+/// The unit in which spans and the lengths of tokens are measured.
+///
+/// Rust strings are indexed by UTF-8 bytes, but other tooling measures text
+/// differently. The language server protocol for example uses UTF-16 code
+/// units by default. A flavor declares which unit its spans are in through
+/// [`Flavor::UNIT`], which is what conversions such as [`Node::span_in`] use
+/// as their starting point.
+///
+/// The unit is not enforced when building a tree. It's up to the lexer to
+/// produce token lengths in the declared unit, which is what [`Unit::len`]
+/// helps with.
+///
+/// [`Flavor::UNIT`]: crate::Flavor::UNIT
+/// [`Node::span_in`]: crate::Node::span_in
+///
+/// # Examples
+///
+/// ```
+/// use syntree::Unit;
+///
+/// let text = "aö😀";
+///
+/// assert_eq!(Unit::Bytes.len(text), 7);
+/// assert_eq!(Unit::Chars.len(text), 3);
+/// assert_eq!(Unit::Utf16.len(text), 4);
+///
+/// // Offset 3 in bytes is right before the emoji.
+/// assert_eq!(Unit::Bytes.convert(Unit::Utf16, text, 3), Some(2));
+/// assert_eq!(Unit::Utf16.convert(Unit::Chars, text, 2), Some(2));
+/// // Offset 3 in UTF-16 code units is in the middle of the emoji.
+/// assert_eq!(Unit::Utf16.convert(Unit::Bytes, text, 3), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Unit {
+    /// UTF-8 bytes, which is how Rust strings are indexed.
+    Bytes,
+    /// Unicode scalar values, or [`char`]s.
+    Chars,
+    /// UTF-16 code units.
+    Utf16,
+}
+
+impl Unit {
+    /// Get the length of `text` measured in this unit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Unit;
+    ///
+    /// assert_eq!(Unit::Bytes.len("ö"), 2);
+    /// assert_eq!(Unit::Chars.len("ö"), 1);
+    /// assert_eq!(Unit::Utf16.len("ö"), 1);
+    /// ```
+    #[must_use]
+    pub fn len(self, text: &str) -> usize {
+        match self {
+            Unit::Bytes => text.len(),
+            Unit::Chars => text.chars().count(),
+            Unit::Utf16 => text.chars().map(char::len_utf16).sum(),
+        }
+    }
+
+    /// Get the length of the character `c` measured in this unit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Unit;
+    ///
+    /// assert_eq!(Unit::Bytes.len_char('😀'), 4);
+    /// assert_eq!(Unit::Chars.len_char('😀'), 1);
+    /// assert_eq!(Unit::Utf16.len_char('😀'), 2);
+    /// ```
+    #[must_use]
+    pub fn len_char(self, c: char) -> usize {
+        match self {
+            Unit::Bytes => c.len_utf8(),
+            Unit::Chars => 1,
+            Unit::Utf16 => c.len_utf16(),
+        }
+    }
+
+    /// Convert `offset` in `source` measured in this unit into an offset
+    /// measured in the unit `to`.
+    ///
+    /// Returns `None` if the offset is past the end of the source or doesn't
+    /// fall on a character boundary.
+    ///
+    /// Converting from anything but bytes takes time proportional to the
+    /// offset, since the source has to be scanned from the start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use syntree::Unit;
+    ///
+    /// let source = "let ö = 1;";
+    ///
+    /// assert_eq!(Unit::Chars.convert(Unit::Bytes, source, 6), Some(7));
+    /// assert_eq!(Unit::Bytes.convert(Unit::Chars, source, 7), Some(6));
+    /// assert_eq!(Unit::Bytes.convert(Unit::Chars, source, 5), None);
+    /// assert_eq!(Unit::Bytes.convert(Unit::Chars, source, 100), None);
+    /// ```
+    #[must_use]
+    pub fn convert(self, to: Unit, source: &str, offset: usize) -> Option<usize> {
+        if let Unit::Bytes = self {
+            return Some(to.len(source.get(..offset)?));
+        }
+
+        let mut from_offset = 0usize;
+        let mut to_offset = 0usize;
+        let mut chars = source.chars();
+
+        loop {
+            if from_offset == offset {
+                return Some(to_offset);
+            }
+
+            if from_offset > offset {
+                return None;
+            }
+
+            let c = chars.next()?;
+            from_offset += self.len_char(c);
+            to_offset += to.len_char(c);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use syntree::Trivia;
+
+fn classify(value: &str) -> Option<Trivia> {
+    match value {
+        "comment" => Some(Trivia::Comment),
+        "ws" => Some(Trivia::Whitespace),
+        "nl" => Some(Trivia::Newline),
+        _ => None,
+    }
+}
+
+#[test]
+fn dangling_comments() -> Result<()> {
+    let tree = syntree::tree! {
+        "block" => {
+            ("open", 1),
+            ("nl", 1),
+            ("comment", 3),
+        },
+        "empty" => {
+            ("comment", 3),
+        },
+        ("comment", 3),
+    };
+
+    let map = tree.attach_trivia(|n| classify(n.value()));
+
+    let block = tree.first().context("missing block")?;
+    let open = block.first().context("missing open")?;
+    let empty = block.next().context("missing empty")?;
+
+    let comments = |id| {
+        map.get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&id| Some(tree.get(id)?.span().start))
+            .collect::<Vec<_>>()
+    };
+
+    // Comments at the end of a list of siblings are attached to the previous
+    // sibling, or the parent if there is none. The top-level comment trails
+    // the `empty` node on the same line, so it's attached after the comment
+    // inside of it.
+    assert_eq!(comments(open.id()), [2]);
+    assert_eq!(comments(empty.id()), [5, 8]);
+    assert_eq!(map.len(), 2);
+
+    let attached = map.iter().map(|(id, c)| (id, c.len())).collect::<Vec<_>>();
+    assert_eq!(attached, [(open.id(), 1), (empty.id(), 2)]);
+    Ok(())
+}
@@ -123,3 +123,275 @@ fn test_nested_checkpoints2() -> Result<()> {
     assert_eq!(tree, expected);
     Ok(())
 }
+
+#[test]
+fn test_checkpoint_after_glue() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let a = tree.checkpoint()?;
+    tree.token(Lit, 1)?;
+    tree.token(Lit, 1)?;
+    let b = tree.checkpoint()?;
+    tree.glue_last(2, Number)?;
+    tree.token(Whitespace, 1)?;
+    tree.close_at(&b, Number)?;
+    tree.close_at(&a, Root)?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        Root => {
+            (Number, 2),
+            Number => {
+                (Whitespace, 1)
+            }
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_adopt_nested_checkpoints() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let a = tree.checkpoint()?;
+    let b = tree.checkpoint()?;
+    tree.token("lit", 3)?;
+
+    let mut clone = tree.clone();
+    let clone_a = clone.adopt(&a);
+    let clone_b = clone.adopt(&b);
+    clone.close_at(&clone_b, "inner")?;
+    clone.close_at(&clone_a, "outer")?;
+
+    tree.close_at(&a, "number")?;
+
+    let expected = syntree::tree! {
+        "outer" => {
+            "inner" => {
+                ("lit", 3)
+            }
+        }
+    };
+
+    assert_eq!(clone.build()?, expected);
+
+    let expected = syntree::tree! {
+        "number" => {
+            ("lit", 3)
+        }
+    };
+
+    assert_eq!(tree.build()?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_checkpoint_after_wrap() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let a = tree.checkpoint()?;
+    tree.token("x", 1)?;
+    let b = tree.checkpoint()?;
+    // The wrapping node takes the identifier which `b` was taken for.
+    tree.close_at(&a, "a")?;
+    tree.token("y", 1)?;
+    tree.close_at(&b, "b")?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "a" => {
+            ("x", 1)
+        },
+        "b" => {
+            ("y", 1)
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_checkpoint_inside_closed_node_before_wrap() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let c0 = tree.checkpoint()?;
+    tree.open("a")?;
+    // Taken for the node which the wrapping node below gets.
+    tree.checkpoint()?;
+    tree.close()?;
+    tree.close_at(&c0, "w")?;
+    let c2 = tree.checkpoint()?;
+    tree.token("t", 1)?;
+    tree.close_at(&c2, "x")?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "w" => {
+            "a"
+        },
+        "x" => {
+            ("t", 1)
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_interleaved_checkpoints_in_loop() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let c = tree.checkpoint()?;
+    tree.token(Lit, 1)?;
+
+    for _ in 0..3 {
+        tree.token(Whitespace, 1)?;
+        let operand = tree.checkpoint()?;
+        tree.close_at(&c, Number)?;
+        tree.token(Lit, 1)?;
+        tree.close_at(&operand, Number)?;
+    }
+
+    tree.close_at(&c, Root)?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        Root => {
+            Number => {
+                Number => {
+                    Number => {
+                        (Lit, 1),
+                        (Whitespace, 1)
+                    },
+                    Number => {
+                        (Lit, 1)
+                    },
+                    (Whitespace, 1)
+                },
+                Number => {
+                    (Lit, 1)
+                },
+                (Whitespace, 1)
+            },
+            Number => {
+                (Lit, 1)
+            }
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_checkpoint_rebased_after_open_above() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let c = tree.checkpoint()?;
+    let lit = tree.token("lit", 3)?;
+    tree.open_above(lit, "inner")?;
+    tree.token("ws", 1)?;
+    tree.close_at(&c, "outer")?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "outer" => {
+            "inner" => {
+                ("lit", 3)
+            },
+            ("ws", 1)
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_checkpoint_rebased_after_fixed() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let c = tree.checkpoint()?;
+    let fixed = tree.checkpoint_fixed()?;
+    tree.token("lit", 3)?;
+    tree.close_at_fixed(fixed, "inner")?;
+    tree.close_at(&c, "outer")?;
+    tree.token("ws", 1)?;
+    tree.close_at(&c, "root")?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "root" => {
+            "outer" => {
+                "inner" => {
+                    ("lit", 3)
+                }
+            },
+            ("ws", 1)
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_fixed_checkpoint_after_wrap() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    let c = tree.checkpoint()?;
+    tree.token("y", 1)?;
+    let d = tree.checkpoint_fixed()?;
+    // The wrapping node takes the identifier of the next node to be inserted
+    // when `d` was taken.
+    tree.close_at(&c, "C")?;
+    tree.token("z", 1)?;
+    tree.close_at_fixed(d, "D")?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "C" => {
+            ("y", 1)
+        },
+        "D" => {
+            ("z", 1)
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
+
+#[test]
+fn test_fixed_checkpoint_empty() -> Result<()> {
+    let mut tree = syntree::Builder::new();
+
+    tree.open("root")?;
+    tree.token("x", 1)?;
+    let d = tree.checkpoint_fixed()?;
+    tree.close_at_fixed(d, "empty")?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+
+    let expected = syntree::tree! {
+        "root" => {
+            ("x", 1),
+            ("empty", (1, 1)) => {}
+        }
+    };
+
+    assert_eq!(tree, expected);
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use syntree::merge::Choice;
+
+#[test]
+fn merge_with_empty() -> Result<(), Box<dyn core::error::Error>> {
+    let tree = syntree::tree! {
+        "root" => {
+            ("lit", 3),
+            ("ws", 1),
+        }
+    };
+
+    let empty = syntree::Tree::empty();
+
+    let merged = syntree::merge(&tree, &empty, |_| Choice::A)?;
+    assert_eq!(merged, tree);
+
+    let merged = syntree::merge(&empty, &tree, |_| Choice::A)?;
+    assert_eq!(merged, tree);
+    Ok(())
+}
+
+#[test]
+fn merge_same_span() -> Result<(), Box<dyn core::error::Error>> {
+    let a = syntree::tree! {
+        "a" => {
+            ("lit", 3),
+        }
+    };
+
+    let b = syntree::tree! {
+        "b" => {
+            "c" => {
+                ("lit", 3),
+            }
+        }
+    };
+
+    let mut conflicts = 0;
+
+    let merged = syntree::merge(&a, &b, |c| {
+        conflicts += 1;
+        assert_eq!((c.a().value(), c.b().value()), ("lit", "lit"));
+        Choice::A
+    })?;
+
+    let expected = syntree::tree! {
+        "a" => {
+            "b" => {
+                "c" => {
+                    ("lit", 3),
+                }
+            }
+        }
+    };
+
+    assert_eq!(merged, expected);
+    assert_eq!(conflicts, 1);
+    Ok(())
+}
+
+#[test]
+fn merge_remove_open_container() -> Result<(), Box<dyn core::error::Error>> {
+    let a = syntree::tree! {
+        "a" => {
+            ("x", 1),
+            ("y", 1),
+        },
+        ("z", 1),
+    };
+
+    let b = syntree::tree! {
+        ("x", 1),
+        "b" => {
+            ("y", 1),
+            ("z", 1),
+        }
+    };
+
+    let mut conflicts = Vec::new();
+
+    let merged = syntree::merge(&a, &b, |c| {
+        conflicts.push((c.a().value(), c.b().value()));
+
+        if c.a().is_token() {
+            Choice::A
+        } else {
+            Choice::B
+        }
+    })?;
+
+    // "a" is removed once it's found to overlap "b", and its children are kept
+    // in its place.
+    let expected = syntree::tree! {
+        ("x", 1),
+        "b" => {
+            ("y", 1),
+            ("z", 1),
+        }
+    };
+
+    assert_eq!(merged, expected);
+    assert_eq!(conflicts, [("x", "x"), ("a", "b"), ("y", "y"), ("z", "z")]);
+    Ok(())
+}
+
+#[test]
+fn merge_remove_overlapping() -> Result<(), Box<dyn core::error::Error>> {
+    let a = syntree::tree! {
+        "a" => {
+            ("x", 2),
+        },
+        ("y", 2),
+    };
+
+    let b = syntree::tree! {
+        ("x", 1),
+        "b" => {
+            ("y", 2),
+        },
+        ("z", 1),
+    };
+
+    let merged = syntree::merge(&a, &b, |_| Choice::A)?;
+
+    let expected = syntree::tree! {
+        "a" => {
+            ("x", 2),
+        },
+        ("y", 2),
+    };
+
+    assert_eq!(merged, expected);
+    Ok(())
+}
+
+#[test]
+fn merge_preserves_order() -> Result<(), Box<dyn core::error::Error>> {
+    let a = syntree::tree! {
+        "stmt" => {
+            ("lit", 3),
+            "empty" => {},
+            ("semi", 1),
+        },
+        "stmt" => {
+            ("lit", 2),
+        }
+    };
+
+    let merged = syntree::merge(&a, &a, |_| Choice::B)?;
+
+    let expected = syntree::tree! {
+        "stmt" => {
+            "stmt" => {
+                ("lit", 3),
+                "empty" => {
+                    "empty" => {}
+                },
+                ("semi", 1),
+            }
+        },
+        "stmt" => {
+            "stmt" => {
+                ("lit", 2),
+            }
+        }
+    };
+
+    assert_eq!(merged, expected);
+    Ok(())
+}
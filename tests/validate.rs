@@ -0,0 +1,373 @@
+use anyhow::Result;
+use syntree::edit::ChangeSet;
+use syntree::{Builder, Span, ValidationError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Root,
+    Number,
+    Lit,
+    Whitespace,
+}
+
+use Syntax::{Lit, Number, Root, Whitespace};
+
+#[test]
+fn validate_close_at() -> Result<()> {
+    let mut tree = Builder::new();
+
+    let outer = tree.checkpoint()?;
+
+    for _ in 0..4 {
+        let c = tree.checkpoint()?;
+        tree.token(Lit, 2)?;
+        tree.token_empty(Whitespace)?;
+        tree.close_at(&c, Number)?;
+        tree.token(Whitespace, 1)?;
+    }
+
+    tree.close_at(&outer, Root)?;
+    tree.token(Whitespace, 1)?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+    Ok(())
+}
+
+#[test]
+fn validate_index_empty() -> Result<()> {
+    let mut tree = Builder::new();
+    tree.set_index_empty_tokens(true);
+
+    tree.open(Root)?;
+    tree.token_empty(Whitespace)?;
+    tree.token(Lit, 2)?;
+    tree.token_empty(Whitespace)?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+    Ok(())
+}
+
+#[test]
+fn validate_edit() -> Result<()> {
+    let tree = syntree::tree! {
+        Root => {
+            Number => {
+                (Lit, 1),
+                (Lit, 2),
+            },
+            (Whitespace, 3),
+            Number => {
+                (Lit, 4),
+            },
+        }
+    };
+
+    for node in tree.walk() {
+        let mut change_set = ChangeSet::new();
+        change_set.remove(node.id());
+        change_set.modify(&tree)?.validate()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn validate_nested_close_at() -> Result<()> {
+    let mut tree = Builder::new();
+
+    tree.open(Root)?;
+    let c = tree.checkpoint()?;
+    tree.token(Whitespace, 1)?;
+    tree.token(Lit, 2)?;
+    tree.close_at(&c, Number)?;
+    tree.close()?;
+
+    let c = tree.checkpoint()?;
+    tree.token(Lit, 2)?;
+    tree.token(Whitespace, 1)?;
+    tree.close_at(&c, Number)?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+
+    let root = tree
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("missing root"))?;
+    assert_eq!(root.last().map(|n| n.value()), Some(Number));
+    assert_eq!(tree.last().map(|n| n.value()), Some(Number));
+    Ok(())
+}
+
+#[test]
+fn validate_close_at_from() -> Result<()> {
+    let mut tree = Builder::new();
+
+    tree.open(Root)?;
+    let c = tree.checkpoint()?;
+    tree.token(Whitespace, 1)?;
+    tree.token(Lit, 2)?;
+    tree.token(Lit, 2)?;
+    tree.close_at_from(&c, 1, Number)?;
+    tree.token(Whitespace, 1)?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+
+    let root = tree
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("missing root"))?;
+    let values = root.children().map(|n| n.value()).collect::<Vec<_>>();
+    assert_eq!(values, [Whitespace, Number, Whitespace]);
+    Ok(())
+}
+
+#[test]
+fn validate_edit_last_child() -> Result<()> {
+    let tree = syntree::tree! {
+        Root => {
+            Number => {
+                (Lit, 1),
+                (Lit, 2),
+            },
+            (Whitespace, 3),
+        }
+    };
+
+    let lit = tree
+        .first()
+        .and_then(|n| n.first())
+        .and_then(|n| n.last())
+        .ok_or_else(|| anyhow::anyhow!("missing lit"))?;
+
+    let mut change_set = ChangeSet::new();
+    change_set.remove(lit.id());
+    let output = change_set.modify(&tree)?;
+    output.validate()?;
+
+    let expected = syntree::tree! {
+        Root => {
+            Number => {
+                (Lit, 1),
+            },
+            (Whitespace, 3),
+        }
+    };
+
+    assert_eq!(output, expected);
+    Ok(())
+}
+
+#[test]
+fn validate_edit_replace_range() -> Result<()> {
+    let tree = syntree::tree! {
+        Root => {
+            (Lit, 1),
+            (Whitespace, 1),
+            (Lit, 2),
+        },
+        (Whitespace, 1)
+    };
+
+    let root = tree
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("missing root"))?;
+    let first = root
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("missing first"))?;
+    let last = root.last().ok_or_else(|| anyhow::anyhow!("missing last"))?;
+
+    let mut change_set = ChangeSet::new();
+    change_set.replace_range(
+        first.id()..=last.id(),
+        syntree::tree! { Number => { (Lit, 4) } },
+    );
+    let output = change_set.modify(&tree)?;
+    output.validate()?;
+
+    let expected = syntree::tree! {
+        Root => {
+            Number => {
+                (Lit, 4),
+            },
+        },
+        (Whitespace, 1)
+    };
+
+    assert_eq!(output, expected);
+
+    let mut change_set = ChangeSet::new();
+    change_set.replace_range(last.id()..=first.id(), syntree::tree! { (Lit, 4) });
+    assert!(change_set.modify(&tree).is_err());
+    Ok(())
+}
+
+#[test]
+fn validate_set_token_len() -> Result<()> {
+    let mut tree = Builder::new();
+
+    tree.open(Root)?;
+    tree.open(Number)?;
+    tree.token(Lit, 2)?;
+    tree.close()?;
+    tree.open(Number)?;
+    tree.close()?;
+    tree.token(Whitespace, 1)?;
+    tree.close()?;
+    tree.token(Lit, 3)?;
+
+    let mut tree = tree.build()?;
+
+    let ids = tree
+        .walk()
+        .filter(|n| n.is_token())
+        .map(|n| n.id())
+        .collect::<Vec<_>>();
+
+    for (n, id) in ids.into_iter().enumerate() {
+        tree.set_token_len(id, n * 2)?;
+        tree.validate()?;
+    }
+
+    let root = tree
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("missing root"))?
+        .id();
+    assert!(tree.set_token_len(root, 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn validate_token_with() -> Result<()> {
+    let mut tree = Builder::new();
+
+    tree.open(Root)?;
+    tree.token_with(Lit, Span::new(0, 3))?;
+    tree.token_with(Whitespace, Span::new(3, 4))?;
+    tree.token_with(Lit, Span::new(4, 6))?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+
+    // Tokens inserted with a span are indexed by where they end, just like
+    // tokens inserted with `token`.
+    let offsets = tree.token_offsets().map(|(end, _)| end).collect::<Vec<_>>();
+    assert_eq!(offsets, [3, 4, 6]);
+
+    let token = tree
+        .token_at(1)
+        .ok_or_else(|| anyhow::anyhow!("missing token at 1"))?;
+    assert_eq!(token.span(), Span::new(0, 3));
+
+    let token = tree
+        .token_at(4)
+        .ok_or_else(|| anyhow::anyhow!("missing token at 4"))?;
+    assert_eq!(token.span(), Span::new(4, 6));
+    Ok(())
+}
+
+#[test]
+fn validate_token_with_empty() -> Result<()> {
+    for index_empty in [false, true] {
+        let mut tree = Builder::new();
+        tree.set_index_empty_tokens(index_empty);
+
+        tree.open(Root)?;
+        tree.token_with(Lit, Span::new(0, 2))?;
+        tree.token_with(Whitespace, Span::point(2))?;
+        tree.token_with(Lit, Span::new(2, 3))?;
+        tree.close()?;
+
+        let tree = tree.build()?;
+        tree.validate()?;
+
+        let expected = if index_empty { 3 } else { 2 };
+        assert_eq!(tree.token_offsets().count(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn validate_explicit_spans_out_of_order() -> Result<()> {
+    let mut tree = Builder::new();
+    tree.set_explicit_spans(true);
+
+    tree.open_with(Root, Span::new(0, 12))?;
+    tree.token_with(Lit, Span::new(10, 12))?;
+    tree.token_with(Lit, Span::new(0, 2))?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+
+    let offsets = tree.token_offsets().map(|(end, _)| end).collect::<Vec<_>>();
+    assert_eq!(offsets, [2, 12]);
+
+    let token = tree
+        .token_at(1)
+        .ok_or_else(|| anyhow::anyhow!("missing token at 1"))?;
+    assert_eq!(token.span(), Span::new(0, 2));
+
+    let token = tree
+        .token_at(11)
+        .ok_or_else(|| anyhow::anyhow!("missing token at 11"))?;
+    assert_eq!(token.span(), Span::new(10, 12));
+
+    assert!(tree.token_at(5).is_none());
+
+    // Tokens which overlap are still reported, even if they're not next to
+    // each other. The one which ends last is reported.
+    let mut tree = Builder::new();
+    tree.set_explicit_spans(true);
+
+    tree.open_with(Root, Span::new(0, 12))?;
+    let lit = tree.token_with(Lit, Span::new(10, 12))?;
+    tree.token_with(Whitespace, Span::new(0, 2))?;
+    tree.token_with(Lit, Span::new(8, 11))?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    assert_eq!(tree.validate(), Err(ValidationError::SpanOverlap(lit)));
+    Ok(())
+}
+
+#[test]
+fn validate_implicit_spans_out_of_order() -> Result<()> {
+    let mut tree = Builder::new();
+
+    tree.token_with(Lit, Span::new(5, 10))?;
+    let early = tree.token_with(Lit, Span::new(0, 5))?;
+
+    let tree = tree.build()?;
+
+    // Without explicit spans, tokens must be in order of their spans.
+    assert_eq!(tree.validate(), Err(ValidationError::SpanOverlap(early)));
+    Ok(())
+}
+
+#[test]
+fn nth_token_explicit_spans_index_empty() -> Result<()> {
+    let mut tree = Builder::new();
+    tree.set_explicit_spans(true);
+    tree.set_index_empty_tokens(true);
+
+    tree.open_with("root", Span::new(0, 10))?;
+    tree.token_with("late", Span::new(5, 10))?;
+    tree.token_with("early", Span::new(0, 5))?;
+    tree.close()?;
+
+    let tree = tree.build()?;
+    tree.validate()?;
+
+    // Tokens are counted in tree order, even though the index is sorted by
+    // span.
+    assert_eq!(tree.nth_token(0).map(|n| n.value()), Some("late"));
+    assert_eq!(tree.nth_token(1).map(|n| n.value()), Some("early"));
+    assert!(tree.nth_token(2).is_none());
+    Ok(())
+}
@@ -49,3 +49,199 @@ fn walk_trees2() -> Result<(), Box<dyn core::error::Error>> {
     assert_eq!(values, ["n2", "n3", "n5"]);
     Ok(())
 }
+
+#[test]
+fn walk_events_rev() -> Result<(), Box<dyn core::error::Error>> {
+    let tree = syntree::tree! {
+        "n1" => {
+            "n2",
+            "n3" => {
+                "n4"
+            },
+            "n5",
+        },
+        "n6" => {
+            "n7"
+        }
+    };
+
+    for node in tree.walk() {
+        let mut forward = Vec::new();
+        let mut it = node.walk_events();
+
+        while let Some((e, depth, n)) = it.next_with_depth() {
+            forward.push((e, depth, n.id()));
+        }
+
+        let mut backward = Vec::new();
+        let mut it = node.walk_events();
+
+        while let Some((e, depth, n)) = it.next_back_with_depth() {
+            backward.push((e, depth, n.id()));
+        }
+
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        for split in 0..=forward.len() {
+            let mut it = node.walk_events();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+
+            for _ in 0..split {
+                front.extend(it.next_with_depth().map(|(e, d, n)| (e, d, n.id())));
+            }
+
+            while let Some((e, d, n)) = it.next_back_with_depth() {
+                back.push((e, d, n.id()));
+            }
+
+            back.reverse();
+            front.extend(back);
+            assert_eq!(forward, front);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn walk_rev() -> Result<(), Box<dyn core::error::Error>> {
+    let tree = syntree::tree! {
+        "n1" => {
+            "n2",
+            "n3" => {
+                "n4"
+            },
+            "n5",
+        },
+        "n6" => {
+            "n7"
+        }
+    };
+
+    for node in tree.walk() {
+        let forward = node.walk().map(|n| n.id()).collect::<Vec<_>>();
+        let mut backward = node.walk().rev().map(|n| n.id()).collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let forward = node.walk_from().map(|n| n.id()).collect::<Vec<_>>();
+        let mut backward = node.walk_from().rev().map(|n| n.id()).collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let forward = node
+            .walk()
+            .with_depths()
+            .map(|(d, n)| (d, n.id()))
+            .collect::<Vec<_>>();
+
+        let mut backward = node
+            .walk()
+            .with_depths()
+            .rev()
+            .map(|(d, n)| (d, n.id()))
+            .collect::<Vec<_>>();
+
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn walk_with_parents() -> Result<(), Box<dyn core::error::Error>> {
+    let tree = syntree::tree! {
+        "n1" => {
+            "n2",
+            "n3" => {
+                "n4"
+            },
+            "n5",
+        },
+        "n6" => {
+            "n7"
+        }
+    };
+
+    for node in tree.walk() {
+        for walk in [node.walk(), node.walk_from()] {
+            let expected = walk
+                .clone()
+                .map(|n| (n.parent().map(|p| p.id()), n.id()))
+                .collect::<Vec<_>>();
+
+            let actual = walk
+                .with_parents()
+                .map(|(p, n)| (p.map(|p| p.id()), n.id()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn walk_postorder() -> Result<(), Box<dyn core::error::Error>> {
+    let tree = syntree::tree! {
+        "n1" => {
+            "n2",
+            "n3" => {
+                "n4"
+            },
+            "n5",
+        },
+        "n6" => {
+            "n7"
+        },
+        "n8"
+    };
+
+    let expected = ["n2", "n4", "n3", "n5", "n1", "n7", "n6", "n8"];
+
+    let values = tree.walk_postorder().map(|n| n.value()).collect::<Vec<_>>();
+    assert_eq!(values, expected);
+
+    let mut values = tree
+        .walk_postorder()
+        .rev()
+        .map(|n| n.value())
+        .collect::<Vec<_>>();
+    values.reverse();
+    assert_eq!(values, expected);
+
+    // Alternating between both ends visits every node exactly once.
+    for split in 0..=expected.len() {
+        let mut it = tree.walk_postorder();
+        let front = it
+            .by_ref()
+            .take(split)
+            .map(|n| n.value())
+            .collect::<Vec<_>>();
+        let mut back = it.rev().map(|n| n.value()).collect::<Vec<_>>();
+        back.reverse();
+        assert_eq!([front, back].concat(), expected);
+    }
+
+    for node in tree.walk() {
+        let expected = node.walk().inside().count();
+
+        let values = node.walk_postorder().map(|n| n.id()).collect::<Vec<_>>();
+        assert_eq!(values.len(), expected);
+        assert_eq!(values.last(), Some(&node.id()));
+
+        let mut rev = node
+            .walk_postorder()
+            .rev()
+            .map(|n| n.id())
+            .collect::<Vec<_>>();
+        rev.reverse();
+        assert_eq!(values, rev);
+    }
+
+    Ok(())
+}